@@ -1,11 +1,19 @@
-use clap::Parser;
+use crate::web_options::WebOptions;
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
+use std::fs::File;
 
 /// Backend configuration of ServerBee.
 /// ServerBee 的后端配置项。
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, disable_version_flag = true)]
 pub struct Args {
+    /// Print version.
+    /// 打印版本。
+    #[clap(short = 'V', action = ArgAction::Version)]
+    pub print_version: Option<bool>,
+
     /// Port, default is 9527.
     /// 端口号，默认 9527。
     #[clap(short, long)]
@@ -21,10 +29,186 @@ pub struct Args {
     #[clap(short, long, default_value = "false")]
     pub interactive: bool,
 
+    /// Don't ask anything, for unattended installs.
+    /// 不进行任何询问, 用于无人值守安装。
+    #[clap(short, long, conflicts_with = "interactive")]
+    pub yes: bool,
+
+    /// YAML file with any of these options, given as `snake_case` keys, the arguments take precedence.
+    /// 包含以上任意选项的 YAML 文件, 键名为 `snake_case` 格式, 命令行参数优先。
+    #[clap(long)]
+    pub config: Option<String>,
+
+    /// Language of the deployer, en or zh.
+    /// 部署程序的语言, en 或 zh。
+    #[clap(long)]
+    pub locale: Option<String>,
+
     /// Allow downloading of pre-release versions.
     /// 允许下载预发布版本。
     #[clap(long)]
     pub pre_version: bool,
+
+    /// Minisign public key, or the path of its .pub file, the checksum manifest must be signed with.
+    /// 校验清单签名所用的 minisign 公钥, 或其 .pub 文件路径。
+    #[clap(long)]
+    pub public_key: Option<String>,
+
+    /// Download and run serverbee-web without verifying its checksum, unsafe.
+    /// 不校验校验和直接下载并运行 serverbee-web, 不安全。
+    #[clap(long)]
+    pub skip_verify: bool,
+
+    /// Previous versions kept for rollback, default is 2.
+    /// 保留用于回滚的旧版本数量，默认 2。
+    #[clap(long)]
+    pub keep: Option<usize>,
+
+    /// Install serverbee-web from a local zip instead of downloading it, needs --version.
+    /// 从本地 zip 安装 serverbee-web 而不是下载, 需要 --version。
+    #[clap(long)]
+    pub archive: Option<String>,
+
+    /// Artifact server to download from instead of db.serverbee.app.
+    /// 用于下载的制品服务器, 替代 db.serverbee.app。
+    #[clap(long)]
+    pub mirror: Option<String>,
+
+    /// Install this version instead of the latest one, an empty value installs the latest again.
+    /// 安装指定版本而不是最新版本, 传入空值恢复安装最新版本。
+    #[clap(long)]
+    pub version: Option<String>,
+
+    /// Don't look up the latest version online.
+    /// 不在线查询最新版本。
+    #[clap(long, num_args = 0..=1, default_missing_value = "true")]
+    pub no_version_check: Option<bool>,
+
+    /// Token serverbee-web uses to communicate with the APP.
+    /// serverbee-web 用于和 APP 通信的 Token。
+    #[clap(long)]
+    pub app_token: Option<String>,
+
+    /// Token serverbee-web uses to communicate with the server.
+    /// serverbee-web 用于和服务器通信的 Token。
+    #[clap(long)]
+    pub server_token: Option<String>,
+
+    /// ServerHub address of serverbee-web, ip or domain.
+    /// serverbee-web 的服务器地址, ip 或 domain。
+    #[clap(long)]
+    pub server_host: Option<String>,
+
+    /// Disable SSL between serverbee-web and ServerHub.
+    /// 禁用 serverbee-web 与 ServerHub 之间的 SSL。
+    #[clap(long, num_args = 0..=1, default_missing_value = "true")]
+    pub disable_ssl: Option<bool>,
+
+    /// Data directory of serverbee-web.
+    /// serverbee-web 的数据目录。
+    #[clap(long)]
+    pub data_dir: Option<String>,
+
+    /// Log directory of serverbee-web.
+    /// serverbee-web 的日志目录。
+    #[clap(long)]
+    pub log_dir: Option<String>,
+
+    /// Config file of serverbee-web.
+    /// serverbee-web 的配置文件。
+    #[clap(long)]
+    pub web_config: Option<String>,
+
+    /// Keep running and restart serverbee-web when it crashes.
+    /// 保持运行, 在 serverbee-web 崩溃时重启它。
+    #[clap(long, num_args = 0..=1, default_missing_value = "true")]
+    pub supervise: Option<bool>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The options of `Args` read from the file given by `--config`.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ArgsFile {
+    port: Option<u16>,
+    auto_launch: Option<bool>,
+    yes: Option<bool>,
+    locale: Option<String>,
+    pre_version: Option<bool>,
+    public_key: Option<String>,
+    skip_verify: Option<bool>,
+    keep: Option<usize>,
+    archive: Option<String>,
+    mirror: Option<String>,
+    version: Option<String>,
+    no_version_check: Option<bool>,
+    app_token: Option<String>,
+    server_token: Option<String>,
+    server_host: Option<String>,
+    disable_ssl: Option<bool>,
+    data_dir: Option<String>,
+    log_dir: Option<String>,
+    web_config: Option<String>,
+    supervise: Option<bool>,
+}
+
+impl Args {
+    /// Fill the options not given as arguments from the `--config` file.
+    pub fn merge_file(&mut self) -> Result<()> {
+        let path = match &self.config {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let read = || -> Result<ArgsFile> { Ok(serde_yaml::from_reader(File::open(path)?)?) };
+        let file = read().map_err(|e| anyhow!("{}: {}", path, e))?;
+
+        self.port = self.port.or(file.port);
+        self.auto_launch = self.auto_launch.or(file.auto_launch);
+        self.yes |= file.yes.unwrap_or_default();
+        self.locale = self.locale.take().or(file.locale);
+        self.pre_version |= file.pre_version.unwrap_or_default();
+        self.public_key = self.public_key.take().or(file.public_key);
+        self.skip_verify |= file.skip_verify.unwrap_or_default();
+        self.keep = self.keep.or(file.keep);
+        self.archive = self.archive.take().or(file.archive);
+        self.mirror = self.mirror.take().or(file.mirror);
+        self.version = self.version.take().or(file.version);
+        self.no_version_check = self.no_version_check.or(file.no_version_check);
+        self.app_token = self.app_token.take().or(file.app_token);
+        self.server_token = self.server_token.take().or(file.server_token);
+        self.server_host = self.server_host.take().or(file.server_host);
+        self.disable_ssl = self.disable_ssl.or(file.disable_ssl);
+        self.data_dir = self.data_dir.take().or(file.data_dir);
+        self.log_dir = self.log_dir.take().or(file.log_dir);
+        self.web_config = self.web_config.take().or(file.web_config);
+        self.supervise = self.supervise.or(file.supervise);
+        Ok(())
+    }
+
+    pub fn web_options(&self) -> WebOptions {
+        WebOptions {
+            app_token: self.app_token.clone(),
+            server_token: self.server_token.clone(),
+            server_host: self.server_host.clone(),
+            disable_ssl: self.disable_ssl,
+            data_dir: self.data_dir.clone(),
+            log_dir: self.log_dir.clone(),
+            config: self.web_config.clone(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Stop the running serverbee-web and run the previous version again.
+    /// 停止正在运行的 serverbee-web 并重新运行上一个版本。
+    Rollback,
+
+    /// Show what the supervisor is doing.
+    /// 查看守护进程的状态。
+    Status,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
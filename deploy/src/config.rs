@@ -1,7 +1,9 @@
 use crate::cli::Port;
 use crate::constant::BASE_URL;
 use crate::storage_config::StorageConfig;
-use anyhow::Result;
+use crate::verify::{sha256_file, Manifest, PublicKey, MANIFEST_NAME, SIGNATURE_NAME};
+use crate::web_options::WebOptions;
+use anyhow::{anyhow, Result};
 use auto_launch::AutoLaunchBuilder;
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -10,12 +12,12 @@ use log4rs::append::console::ConsoleAppender;
 use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Root};
 use log4rs::encode::pattern::PatternEncoder;
-use reqwest::header;
+use reqwest::{header, StatusCode};
 use std::cmp::min;
-use std::env;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::{env, fs};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -152,26 +154,252 @@ impl Config {
     }
 
     // Ex CWD/0.0.1/
-    pub fn web_bin_dir(&self) -> PathBuf {
-        Config::current_dir().join(self.get_version())
+    pub fn version_dir(version: &str) -> PathBuf {
+        Config::current_dir().join(version)
     }
 
-    pub fn web_bin_path(&self) -> PathBuf {
+    pub fn version_bin_path(version: &str) -> PathBuf {
         if cfg!(target_os = "windows") {
-            self.web_bin_dir().join("serverbee-web.exe")
+            Config::version_dir(version).join("serverbee-web.exe")
         } else {
-            self.web_bin_dir().join("serverbee-web")
+            Config::version_dir(version).join("serverbee-web")
         }
     }
 
+    pub fn web_bin_dir(&self) -> PathBuf {
+        Config::version_dir(&self.get_version())
+    }
+
+    pub fn web_bin_path(&self) -> PathBuf {
+        Config::version_bin_path(&self.get_version())
+    }
+
     pub fn web_bin_zip_path(&self) -> PathBuf {
         self.web_bin_dir().join(self.get_filename())
     }
 
-    pub fn bin_zip_url(&self) -> PathBuf {
-        Path::new(BASE_URL)
+    pub fn get_archive(&self) -> Option<PathBuf> {
+        self.storage_config.get_archive().map(PathBuf::from)
+    }
+
+    pub fn get_mirror(&self) -> Option<String> {
+        self.storage_config.get_mirror()
+    }
+
+    pub fn get_pinned_version(&self) -> Option<String> {
+        self.storage_config.get_version()
+    }
+
+    pub fn set_source(
+        &mut self,
+        archive: Option<String>,
+        mirror: Option<String>,
+        version: Option<String>,
+    ) {
+        self.storage_config.set_source(archive, mirror, version);
+    }
+
+    pub fn get_no_version_check(&self) -> bool {
+        self.storage_config.get_no_version_check()
+    }
+
+    pub fn set_no_version_check(&mut self, no_version_check: bool) {
+        self.storage_config.set_no_version_check(no_version_check);
+    }
+
+    pub fn get_web_options(&self) -> WebOptions {
+        self.storage_config.get_web_options()
+    }
+
+    pub fn set_web_options(&mut self, web: WebOptions) {
+        self.storage_config.set_web_options(web);
+    }
+
+    pub fn get_supervise(&self) -> bool {
+        self.storage_config.get_supervise()
+    }
+
+    pub fn set_supervise(&mut self, supervise: bool) {
+        self.storage_config.set_supervise(supervise);
+    }
+
+    // Ex BASE_URL/0.0.1/name, from the mirror when one is set
+    pub fn release_url(&self, name: &str) -> PathBuf {
+        Path::new(self.get_mirror().as_deref().unwrap_or(BASE_URL))
             .join(self.get_version())
-            .join(self.get_filename())
+            .join(name)
+    }
+
+    pub fn bin_zip_url(&self) -> PathBuf {
+        self.release_url(&self.get_filename())
+    }
+
+    /// The zip to unpack, the local archive when one is set.
+    pub fn bin_zip_path(&self) -> PathBuf {
+        self.get_archive()
+            .unwrap_or_else(|| self.web_bin_zip_path())
+    }
+
+    /// Where a file of the release is read, next to the local archive when one is set.
+    fn release_file_location(&self, name: &str) -> PathBuf {
+        match self.get_archive() {
+            Some(archive) => archive.with_file_name(name),
+            None => self.release_url(name),
+        }
+    }
+
+    async fn fetch_release_file(&self, name: &str) -> Result<Option<String>> {
+        let location = self.release_file_location(name);
+        if self.get_archive().is_none() {
+            return Config::fetch_text(&location).await;
+        }
+        match fs::read_to_string(&location) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Ex CWD/0.0.1/serverbee-web.sha256, written once the archive is verified
+    pub fn web_bin_checksum_path(&self) -> PathBuf {
+        self.web_bin_dir().join("serverbee-web.sha256")
+    }
+
+    pub fn get_active_version(&self) -> Option<String> {
+        self.storage_config.get_active_version()
+    }
+
+    pub fn get_previous_versions(&self) -> Vec<String> {
+        self.storage_config.get_previous_versions()
+    }
+
+    pub fn get_skip_version(&self) -> Option<String> {
+        self.storage_config.get_skip_version()
+    }
+
+    pub fn get_pid(&self) -> Option<u32> {
+        self.storage_config.get_pid()
+    }
+
+    pub fn set_active(
+        &mut self,
+        version: String,
+        previous_versions: Vec<String>,
+        skip_version: Option<String>,
+        pid: u32,
+    ) {
+        self.storage_config
+            .set_active(version, previous_versions, skip_version, pid);
+    }
+
+    pub fn get_keep_versions(&self) -> usize {
+        self.storage_config.get_keep_versions()
+    }
+
+    pub fn set_keep_versions(&mut self, keep_versions: usize) {
+        self.storage_config.set_keep_versions(keep_versions);
+    }
+
+    pub fn get_public_key(&self) -> Option<String> {
+        self.storage_config.get_public_key()
+    }
+
+    /// Remember the key, the contents of the file when given the path of a `.pub` file.
+    pub fn set_public_key(&mut self, public_key: String) -> Result<()> {
+        let public_key = match fs::read_to_string(&public_key) {
+            Ok(content) => content,
+            Err(_) => public_key,
+        };
+        PublicKey::parse(&public_key)?;
+        self.storage_config
+            .set_public_key(public_key.trim().to_string());
+        Ok(())
+    }
+
+    /// Whether the installed serverbee-web is the one verified when it was unpacked.
+    pub fn is_bin_verified(&self) -> bool {
+        match fs::read_to_string(self.web_bin_checksum_path()) {
+            Ok(expected) => {
+                sha256_file(&self.web_bin_path()).ok().as_deref() == Some(expected.trim())
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn mark_bin_verified(&self) -> Result<()> {
+        fs::write(
+            self.web_bin_checksum_path(),
+            sha256_file(&self.web_bin_path())?,
+        )?;
+        Ok(())
+    }
+
+    /// Text at `url`, `None` when it doesn't exist.
+    async fn fetch_text(url: &Path) -> Result<Option<String>> {
+        let url = url.to_str().unwrap();
+        let response = reqwest::Client::new()
+            .get(url)
+            .header(header::USER_AGENT, "reqwest")
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.text().await?))
+    }
+
+    /// Checksums of the release, the signature is required once a public key is set.
+    pub async fn fetch_manifest(&self) -> Result<Manifest> {
+        let manifest = self
+            .fetch_release_file(MANIFEST_NAME)
+            .await?
+            .ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    t!(
+                        "manifest_missing",
+                        location = self.release_file_location(MANIFEST_NAME).display()
+                    )
+                )
+            })?;
+
+        if let Some(public_key) = self.get_public_key() {
+            let signature = self
+                .fetch_release_file(SIGNATURE_NAME)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{}",
+                        t!(
+                            "signature_missing",
+                            url = self.release_file_location(SIGNATURE_NAME).display()
+                        )
+                    )
+                })?;
+            PublicKey::parse(&public_key)?
+                .verify(manifest.as_bytes(), &signature)
+                .map_err(|e| anyhow!("{}", t!("signature_invalid", error = e)))?;
+            info!("{}", t!("signature_verified"));
+        }
+        Manifest::parse(&manifest)
+    }
+
+    /// Check the archive against the manifest, a download is removed when it doesn't match.
+    pub fn verify_bin_zip(&self, manifest: &Manifest) -> Result<()> {
+        let path = self.bin_zip_path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if let Err(e) = manifest.verify(&name, &path) {
+            if self.get_archive().is_none() {
+                let _ = fs::remove_file(&path);
+                return Err(anyhow!("{}", t!("verify_failed", error = e)));
+            }
+            return Err(anyhow!("{}", t!("archive_verify_failed", error = e)));
+        }
+        info!("{}", t!("checksum_verified", path = path.display()));
+        Ok(())
     }
 
     pub fn set_auto_launch(&mut self, enable: bool) {
@@ -216,6 +444,10 @@ impl Config {
         Config::current_dir().join("deploy.log")
     }
 
+    pub fn supervisor_status_path() -> PathBuf {
+        Config::current_dir().join("supervisor-status.json")
+    }
+
     async fn get_latest_version_from_status(include_pre: Option<bool>) -> Result<String> {
         let can_pre = include_pre.unwrap_or(false);
         let client = reqwest::Client::new();
@@ -320,7 +552,8 @@ impl Config {
             .send()
             .await
             .map_err(|_| format!("Failed to Download from '{}'", self.bin_zip_url().display()))
-            .unwrap();
+            .unwrap()
+            .error_for_status()?;
         let total_size = res
             .content_length()
             .ok_or(format!(
@@ -1 +1,26 @@
+use std::time::Duration;
+
 pub(crate) const BASE_URL: &str = "https://db.serverbee.app/cli";
+
+/// Previous versions kept for `rollback` unless `--keep` says otherwise.
+pub(crate) const DEFAULT_KEEP_VERSIONS: usize = 2;
+
+/// How long a stopping serverbee-web may take before it is killed.
+pub(crate) const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a started serverbee-web has to answer `/version`.
+pub(crate) const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before a crashed serverbee-web is started again, doubled for every further crash.
+pub(crate) const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest delay between two restarts.
+pub(crate) const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Crashes within `CRASH_LOOP_WINDOW` after which the supervisor gives up.
+pub(crate) const CRASH_LOOP_LIMIT: usize = 5;
+
+pub(crate) const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(300);
+
+/// Exit code after a crash loop, the systemd unit doesn't restart the deployer on it.
+pub(crate) const CRASH_LOOP_EXIT_CODE: i32 = 3;
@@ -3,21 +3,27 @@
 mod cli;
 mod config;
 mod constant;
+mod process;
 mod storage_config;
+mod supervisor;
+mod test;
+mod upgrade;
+mod verify;
+mod web_options;
 
-use crate::cli::Port;
+use crate::cli::{Command, Port};
 use crate::config::Config;
-use anyhow::Result;
+use crate::constant::CRASH_LOOP_EXIT_CODE;
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use cli::Args;
 use inquire::validator::{ErrorMessage, Validation};
 use inquire::{Select, Text};
-use log::info;
+use log::{error, info, warn};
 use port_selector::is_free;
 use std::fs::File;
-use std::io::stdin;
+use std::io::{stdin, IsTerminal};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::{fs, io};
 
 #[macro_use]
@@ -27,14 +33,28 @@ i18n!("locales");
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    args.merge_file()?;
+
+    if let Some(Command::Status) = args.command {
+        return supervisor::print_status();
+    }
 
     let mut config = Config::new();
+    let started_with = (config.get_port(), config.get_web_options());
+
+    if let Some(locale) = args.locale.take() {
+        config.set_locale(locale);
+    }
+
+    // nobody would answer, e.g. when provisioned by a script
+    let unattended = args.yes || !stdin().is_terminal();
 
-    if config.get_interactive() || args.interactive {
+    if args.interactive || (config.get_interactive() && !unattended) {
         config.set_interactive(true);
         interactive_install(&mut config);
     } else {
+        config.set_interactive(false);
         rust_i18n::set_locale(config.get_locale().as_str());
     }
 
@@ -44,11 +64,92 @@ async fn main() -> Result<()> {
         config.set_port(Port::new(args.port.unwrap()));
     }
 
-    let latest_version = Config::get_latest_version(args.pre_version).await?;
-    info!("{}: {}", t!("latest_version"), latest_version);
-    config.set_version(latest_version);
+    if let Some(keep) = args.keep {
+        config.set_keep_versions(keep);
+    }
+
+    config.set_web_options(args.web_options());
+
+    let archive = match args.archive {
+        // kept absolute, the next run may start elsewhere
+        Some(archive) if !archive.is_empty() => Some(
+            fs::canonicalize(&archive)
+                .map_err(|_| anyhow!("{}", t!("archive_missing", path = archive)))?
+                .display()
+                .to_string(),
+        ),
+        archive => archive,
+    };
+    config.set_source(archive, args.mirror, args.version);
+
+    if let Some(no_version_check) = args.no_version_check {
+        config.set_no_version_check(no_version_check);
+    }
 
-    if !Path::new(config.web_bin_path().as_path().to_str().unwrap()).exists() {
+    if let Some(supervise) = args.supervise {
+        config.set_supervise(supervise);
+    }
+
+    // it would start the serverbee-web stopped here again
+    if let Some(pid) = supervisor::running_supervisor() {
+        return Err(anyhow!("{}", t!("supervisor_running", pid = pid)));
+    }
+
+    let mut started = None;
+
+    if let Some(Command::Rollback) = args.command {
+        upgrade::rollback(&mut config, &mut started).await?;
+        return supervise(&mut config, started).await;
+    }
+
+    if let Some(version) = config.get_pinned_version() {
+        info!("{}: {}", t!("pinned_version"), version);
+        config.set_version(version);
+    } else if config.get_archive().is_some() {
+        return Err(anyhow!("{}", t!("archive_needs_version")));
+    } else if config.get_no_version_check() {
+        let version = config
+            .get_active_version()
+            .unwrap_or_else(|| env!("CARGO_PKG_VERSION").into());
+        info!("{}: {}", t!("skip_version_check"), version);
+        config.set_version(version);
+    } else {
+        let latest_version = Config::get_latest_version(args.pre_version).await?;
+        info!("{}: {}", t!("latest_version"), latest_version);
+        config.set_version(latest_version);
+
+        if let Some(active) = config.get_active_version() {
+            if config.get_skip_version() == Some(config.get_version())
+                && Config::version_bin_path(&active).exists()
+            {
+                warn!(
+                    "{}",
+                    t!(
+                        "skip_rolled_back",
+                        version = config.get_version(),
+                        active = active
+                    )
+                );
+                config.set_version(active);
+            }
+        }
+    }
+
+    if let Some(public_key) = args.public_key {
+        config.set_public_key(public_key)?;
+    }
+
+    let web_bin_exists = Path::new(config.web_bin_path().as_path().to_str().unwrap()).exists();
+
+    if web_bin_exists && (args.skip_verify || config.is_bin_verified()) {
+        info!("{}", t!("skip_download"));
+    } else {
+        if web_bin_exists {
+            warn!(
+                "{}",
+                t!("unverified_bin", path = config.web_bin_path().display())
+            );
+        }
         let web_file_path = config.web_bin_zip_path();
 
         if let Some(p) = web_file_path.parent() {
@@ -57,25 +158,62 @@ async fn main() -> Result<()> {
             }
         }
 
-        config.download_bin().await?;
+        // fetched first, a release without checksums isn't downloaded at all
+        let manifest = if args.skip_verify {
+            warn!("{}", t!("skip_verify"));
+            None
+        } else {
+            Some(config.fetch_manifest().await?)
+        };
 
-        info!(
-            "{}",
-            t!("download_and_unzip", path = web_file_path.display())
-        );
+        match config.get_archive() {
+            Some(archive) if !archive.exists() => {
+                return Err(anyhow!(
+                    "{}",
+                    t!("archive_missing", path = archive.display())
+                ));
+            }
+            Some(_) => {}
+            None => config.download_bin().await?,
+        }
 
-        let tokio_file = tokio::fs::File::open(web_file_path).await?;
+        if let Some(manifest) = &manifest {
+            config.verify_bin_zip(manifest)?;
+        }
+
+        if config.get_archive().is_some() {
+            info!(
+                "{}",
+                t!("unzip_archive", path = config.bin_zip_path().display())
+            );
+        } else {
+            info!(
+                "{}",
+                t!("download_and_unzip", path = config.bin_zip_path().display())
+            );
+        }
+
+        let tokio_file = tokio::fs::File::open(config.bin_zip_path()).await?;
         let std_file = tokio_file.into_std().await;
 
         unzip(std_file, config.web_bin_dir());
         info!("{}", t!("unzip_success"));
-    } else {
-        info!("{}", t!("skip_download"));
+
+        if manifest.is_some() {
+            config.mark_bin_verified()?;
+        } else {
+            // verified again the next time it is run without --skip-verify
+            let _ = fs::remove_file(config.web_bin_checksum_path());
+        }
     }
 
     config.set_auto_launch(args.auto_launch.unwrap_or_else(|| config.get_auto_launch()));
 
-    start_process(config.web_bin_path().to_str().unwrap(), config.get_port());
+    // a running serverbee-web picks up changed options by restarting
+    // a supervisor owns the serverbee-web it started
+    let restart =
+        started_with != (config.get_port(), config.get_web_options()) || config.get_supervise();
+    upgrade::activate(&mut config, restart, &mut started).await?;
 
     if config.get_interactive() {
         config.set_interactive(false);
@@ -93,8 +231,14 @@ async fn main() -> Result<()> {
         println!("############################################");
 
         match current_exe() {
-            Ok(p) => println!("{}", daemon_content(p.display().to_string())),
-            Err(_) => println!("{}", daemon_content(t!("deploy_full_path"))),
+            Ok(p) => println!(
+                "{}",
+                daemon_content(p.display().to_string(), config.get_supervise())
+            ),
+            Err(_) => println!(
+                "{}",
+                daemon_content(t!("deploy_full_path"), config.get_supervise())
+            ),
         }
 
         println!("systemctl enable serverbee-deploy.service");
@@ -103,7 +247,22 @@ async fn main() -> Result<()> {
     }
     println!("{}", t!("install_finish"));
 
-    Ok(())
+    supervise(&mut config, started).await
+}
+
+/// Stay in the foreground with the started serverbee-web when supervising.
+async fn supervise(config: &mut Config, started: Option<std::process::Child>) -> Result<()> {
+    match started {
+        Some(child) if config.get_supervise() => match supervisor::supervise(config, child).await {
+            // a service manager must not start the deployer again
+            Err(e) if e.is::<supervisor::CrashLoop>() => {
+                error!("{}", e);
+                std::process::exit(CRASH_LOOP_EXIT_CODE);
+            }
+            result => result,
+        },
+        _ => Ok(()),
+    }
 }
 
 fn unzip(file: File, out_dir: PathBuf) {
@@ -159,32 +318,6 @@ fn unzip(file: File, out_dir: PathBuf) {
     }
 }
 
-#[cfg(windows)]
-fn start_process(bin_full_path: &str, port: u16) {
-    info!("{}: {}", t!("full_path"), bin_full_path);
-
-    Command::new("powershell")
-        .args(["/C", bin_full_path])
-        .args(["-p", port.to_string().as_str()])
-        .spawn()
-        .expect(t!("run_fail", path = "serverbee-web.exe").as_str());
-
-    info!("{}", t!("run_success"));
-}
-
-#[cfg(not(windows))]
-fn start_process(bin_full_path: &str, port: u16) {
-    info!("{}: {}", t!("full_path"), bin_full_path);
-
-    Command::new(bin_full_path)
-        .arg("-p")
-        .arg(port.to_string())
-        .spawn()
-        .unwrap_or_else(|_| panic!("{}", t!("run_fail", path = bin_full_path)));
-
-    info!("{}", t!("run_success"));
-}
-
 fn interactive_install(config: &mut Config) {
     let locales_options = vec!["English", "中文"];
 
@@ -268,8 +401,17 @@ fn interactive_install(config: &mut Config) {
     println!("==============================");
 }
 
+/// A supervising deployer keeps running, otherwise it exits after starting serverbee-web.
 #[cfg(target_os = "linux")]
-fn daemon_content(path: String) -> String {
+fn daemon_content(path: String, supervise: bool) -> String {
+    let service = if supervise {
+        format!(
+            "Type=simple\nRestart=on-failure\nRestartPreventExitStatus={}",
+            CRASH_LOOP_EXIT_CODE
+        )
+    } else {
+        "Type=oneshot\nRemainAfterExit=yes".to_string()
+    };
     format!(
         r#"
 cat > /etc/systemd/system/serverbee-deploy.service <<EOF
@@ -279,15 +421,14 @@ After=network-online.target systemd-resolved.service
 Wants=network-online.target systemd-resolved.service
 
 [Service]
-Type=oneshot
+{}
 ExecStart={}
-RemainAfterExit=yes
 Environment="RUST_LOG=info"
 
 [Install]
 WantedBy=multi-user.target
 EOF
 "#,
-        path
+        service, path
     )
 }
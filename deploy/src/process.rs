@@ -0,0 +1,201 @@
+use crate::config::Config;
+use crate::constant::{HEALTH_CHECK_TIMEOUT, STOP_TIMEOUT};
+use crate::web_options::WebOptions;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use port_selector::is_free;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where the output of serverbee-web goes, appended to the deploy log when `capture`.
+/// A file rather than a pipe, serverbee-web keeps running when the deployer exits.
+fn output(capture: bool) -> Result<Stdio> {
+    if !capture {
+        return Ok(Stdio::inherit());
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(Config::deploy_log_path())?;
+    Ok(Stdio::from(file))
+}
+
+#[cfg(windows)]
+pub fn start_process(
+    bin_full_path: &Path,
+    port: u16,
+    web: &WebOptions,
+    capture: bool,
+) -> Result<Child> {
+    info!("{}: {}", t!("full_path"), bin_full_path.display());
+
+    let child = Command::new("powershell")
+        .args(["/C", bin_full_path.to_str().unwrap()])
+        .args(["-p", port.to_string().as_str()])
+        .args(web.args())
+        .envs(web.envs())
+        .stdout(output(capture)?)
+        .stderr(output(capture)?)
+        .spawn()
+        .map_err(|_| anyhow!("{}", t!("run_fail", path = "serverbee-web.exe")))?;
+
+    Ok(child)
+}
+
+#[cfg(not(windows))]
+pub fn start_process(
+    bin_full_path: &Path,
+    port: u16,
+    web: &WebOptions,
+    capture: bool,
+) -> Result<Child> {
+    info!("{}: {}", t!("full_path"), bin_full_path.display());
+
+    let child = Command::new(bin_full_path)
+        .arg("-p")
+        .arg(port.to_string())
+        .args(web.args())
+        .envs(web.envs())
+        .stdout(output(capture)?)
+        .stderr(output(capture)?)
+        .spawn()
+        .map_err(|_| anyhow!("{}", t!("run_fail", path = bin_full_path.display())))?;
+
+    Ok(child)
+}
+
+#[cfg(not(windows))]
+fn has_name(pid: u32, name: &str) -> bool {
+    match Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+    {
+        Ok(output) => {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).contains(name)
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+fn has_name(pid: u32, name: &str) -> bool {
+    match Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(name),
+        Err(_) => false,
+    }
+}
+
+/// Whether `pid` is a serverbee-web still running, a pid left from before a reboot may be reused.
+pub fn is_running(pid: u32) -> bool {
+    has_name(pid, "serverbee-web")
+}
+
+/// Whether `pid` is a serverbee-deploy still running, the name is cut to 15 characters on Linux.
+pub fn is_deployer_running(pid: u32) -> bool {
+    has_name(pid, "serverbee-dep")
+}
+
+fn wait_until(timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while !done() {
+        if start.elapsed() > timeout {
+            return false;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    true
+}
+
+/// Stop the serverbee-web running on `port`. The one started last is asked to stop
+/// gracefully first, whatever still holds the port is killed.
+pub fn stop_process(pid: Option<u32>, port: u16) -> Result<()> {
+    if let Some(pid) = pid.filter(|pid| is_running(*pid)) {
+        info!("{}", t!("stopping_process", pid = pid));
+        #[cfg(not(windows))]
+        {
+            let _ = Command::new("kill")
+                .args(["-TERM", &pid.to_string()])
+                .status();
+            if wait_until(STOP_TIMEOUT, || !is_running(pid)) {
+                return Ok(());
+            }
+            warn!("{}", t!("stop_timeout", pid = pid));
+        }
+        port_killer::kill_by_pids(&[pid])?;
+        wait_until(STOP_TIMEOUT, || !is_running(pid));
+    }
+    if !is_free(port) {
+        warn!("{}", t!("kill_port", port = port));
+        port_killer::kill(port)?;
+        if !wait_until(STOP_TIMEOUT, || is_free(port)) {
+            return Err(anyhow!("{}", t!("port_in_use", port = port)));
+        }
+    }
+    Ok(())
+}
+
+/// Wait for `/version` to answer with `version`, plain or over TLS with any certificate.
+pub async fn health_check(child: &mut Child, port: u16, version: &str) -> Result<()> {
+    let version = version.trim_start_matches('v');
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .danger_accept_invalid_certs(true)
+        .build()?;
+    let start = Instant::now();
+    let mut last_error = String::new();
+    while start.elapsed() < HEALTH_CHECK_TIMEOUT {
+        if let Some(status) = child.try_wait()? {
+            return Err(anyhow!("{}", t!("process_exited", status = status)));
+        }
+        for scheme in ["http", "https"] {
+            match client
+                .get(format!("{}://127.0.0.1:{}/version", scheme, port))
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    let text = response.text().await.unwrap_or_default();
+                    if text.trim() == version {
+                        return Ok(());
+                    }
+                    last_error = format!("/version answered {}", text.trim());
+                }
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(anyhow!(
+        "{}",
+        t!("health_check_failed", port = port, error = last_error)
+    ))
+}
+
+/// Stop a serverbee-web started by this process, it is reaped here and not left a zombie.
+pub fn stop_child(child: &mut Child) {
+    info!("{}", t!("stopping_process", pid = child.id()));
+    #[cfg(not(windows))]
+    {
+        let _ = Command::new("kill")
+            .args(["-TERM", &child.id().to_string()])
+            .status();
+        if wait_until(STOP_TIMEOUT, || !matches!(child.try_wait(), Ok(None))) {
+            return;
+        }
+        warn!("{}", t!("stop_timeout", pid = child.id()));
+    }
+    kill_child(child);
+}
+
+/// Kill a process started by this run, it failed its health check.
+pub fn kill_child(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
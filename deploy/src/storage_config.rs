@@ -1,8 +1,10 @@
 use crate::cli::Port;
 use crate::config::Config;
+use crate::constant::DEFAULT_KEEP_VERSIONS;
+use crate::web_options::WebOptions;
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StorageConfig {
@@ -10,6 +12,36 @@ pub struct StorageConfig {
     pub is_auto_launch: Option<bool>,
     pub locale: Option<String>,
     interactive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    /// Version serverbee-web runs, Ex v0.0.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_version: Option<String>,
+    /// Versions run before the active one, the latest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    previous_versions: Vec<String>,
+    /// Version rolled back from, it isn't installed again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    skip_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keep_versions: Option<usize>,
+    /// Local zip installed instead of a download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    archive: Option<String>,
+    /// Artifact server used instead of BASE_URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mirror: Option<String>,
+    /// Version installed instead of the latest one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    no_version_check: Option<bool>,
+    #[serde(default, skip_serializing_if = "WebOptions::is_empty")]
+    web: WebOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    supervise: Option<bool>,
 }
 
 impl StorageConfig {
@@ -22,6 +54,18 @@ impl StorageConfig {
                 locale: None,
                 is_auto_launch: None,
                 interactive: None,
+                public_key: None,
+                active_version: None,
+                previous_versions: vec![],
+                skip_version: None,
+                pid: None,
+                keep_versions: None,
+                archive: None,
+                mirror: None,
+                version: None,
+                no_version_check: None,
+                web: WebOptions::default(),
+                supervise: None,
             }
         }
     }
@@ -48,6 +92,129 @@ impl StorageConfig {
         self.interactive
     }
 
+    pub fn get_public_key(&self) -> Option<String> {
+        self.public_key.clone()
+    }
+
+    pub fn set_public_key(&mut self, public_key: String) {
+        if self.public_key.as_ref() != Some(&public_key) {
+            self.public_key = Some(public_key);
+            self.save_config();
+        }
+    }
+
+    pub fn get_active_version(&self) -> Option<String> {
+        self.active_version.clone()
+    }
+
+    pub fn get_previous_versions(&self) -> Vec<String> {
+        self.previous_versions.clone()
+    }
+
+    pub fn get_skip_version(&self) -> Option<String> {
+        self.skip_version.clone()
+    }
+
+    pub fn get_pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Record what runs now, in one write.
+    pub fn set_active(
+        &mut self,
+        version: String,
+        previous_versions: Vec<String>,
+        skip_version: Option<String>,
+        pid: u32,
+    ) {
+        self.active_version = Some(version);
+        self.previous_versions = previous_versions;
+        self.skip_version = skip_version;
+        self.pid = Some(pid);
+        self.save_config();
+    }
+
+    pub fn get_keep_versions(&self) -> usize {
+        self.keep_versions.unwrap_or(DEFAULT_KEEP_VERSIONS)
+    }
+
+    pub fn set_keep_versions(&mut self, keep_versions: usize) {
+        if self.keep_versions != Some(keep_versions) {
+            self.keep_versions = Some(keep_versions);
+            self.save_config();
+        }
+    }
+
+    pub fn get_archive(&self) -> Option<String> {
+        self.archive.clone()
+    }
+
+    pub fn get_mirror(&self) -> Option<String> {
+        self.mirror.clone()
+    }
+
+    pub fn get_version(&self) -> Option<String> {
+        self.version.clone()
+    }
+
+    /// Set the archive, mirror and pinned version, an empty value clears one.
+    pub fn set_source(
+        &mut self,
+        archive: Option<String>,
+        mirror: Option<String>,
+        version: Option<String>,
+    ) {
+        let merge = |current: &Option<String>, new: Option<String>| match new {
+            Some(new) if new.is_empty() => None,
+            Some(new) => Some(new),
+            None => current.clone(),
+        };
+        let archive = merge(&self.archive, archive);
+        let mirror = merge(&self.mirror, mirror);
+        let version = merge(&self.version, version);
+        if archive != self.archive || mirror != self.mirror || version != self.version {
+            self.archive = archive;
+            self.mirror = mirror;
+            self.version = version;
+            self.save_config();
+        }
+    }
+
+    pub fn get_no_version_check(&self) -> bool {
+        self.no_version_check.unwrap_or(false)
+    }
+
+    pub fn set_no_version_check(&mut self, no_version_check: bool) {
+        if self.no_version_check != Some(no_version_check) {
+            self.no_version_check = Some(no_version_check);
+            self.save_config();
+        }
+    }
+
+    pub fn get_web_options(&self) -> WebOptions {
+        self.web.clone()
+    }
+
+    pub fn set_web_options(&mut self, web: WebOptions) {
+        let mut merged = self.web.clone();
+        merged.merge(web);
+        if merged != self.web {
+            self.web = merged;
+            self.save_config();
+        }
+    }
+
+    pub fn get_supervise(&self) -> bool {
+        self.supervise.unwrap_or(false)
+    }
+
+    pub fn set_supervise(&mut self, supervise: bool) {
+        if self.supervise != Some(supervise) {
+            self.supervise = Some(supervise);
+            self.save_config();
+        }
+    }
+
     pub fn set_auto_launch(&mut self, is_auto_launch: bool) {
         if self.is_auto_launch.is_none() || self.is_auto_launch.unwrap() != is_auto_launch {
             self.is_auto_launch = Some(is_auto_launch);
@@ -73,11 +240,7 @@ impl StorageConfig {
     }
 
     pub fn save_config(&self) {
-        let config_file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(StorageConfig::deploy_config_path())
-            .unwrap();
+        let config_file = open_private(&StorageConfig::deploy_config_path()).unwrap();
         serde_yaml::to_writer(config_file, self).unwrap();
     }
 
@@ -87,3 +250,20 @@ impl StorageConfig {
         path
     }
 }
+
+/// Open `path` for writing it over, it may hold the tokens of serverbee-web.
+/// On unix it is created readable by the owner only and an older file is narrowed to that.
+pub fn open_private(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let file = options.open(path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+    #[cfg(not(unix))]
+    options.open(path)
+}
@@ -0,0 +1,237 @@
+use crate::config::Config;
+use crate::constant::{CRASH_LOOP_LIMIT, CRASH_LOOP_WINDOW, RESTART_BACKOFF, RESTART_BACKOFF_MAX};
+use crate::process::{is_deployer_running, stop_child};
+use crate::upgrade::start_checked;
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Crashes of the supervised serverbee-web within `CRASH_LOOP_WINDOW`.
+#[derive(Debug, Default)]
+pub struct Crashes {
+    times: VecDeque<Instant>,
+}
+
+impl Crashes {
+    /// Record a crash at `now`, the delay before starting again or `None` when it crash-loops.
+    pub fn crash(&mut self, now: Instant) -> Option<Duration> {
+        while let Some(first) = self.times.front() {
+            if now.duration_since(*first) < CRASH_LOOP_WINDOW {
+                break;
+            }
+            self.times.pop_front();
+        }
+        self.times.push_back(now);
+        if self.times.len() >= CRASH_LOOP_LIMIT {
+            return None;
+        }
+        let delay = RESTART_BACKOFF.saturating_mul(1 << (self.times.len() - 1));
+        Some(delay.min(RESTART_BACKOFF_MAX))
+    }
+}
+
+/// Returned by `supervise` when serverbee-web crash-loops.
+#[derive(Debug)]
+pub struct CrashLoop;
+
+impl fmt::Display for CrashLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            t!(
+                "crash_loop",
+                count = CRASH_LOOP_LIMIT,
+                seconds = CRASH_LOOP_WINDOW.as_secs()
+            )
+        )
+    }
+}
+
+impl std::error::Error for CrashLoop {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Running,
+    Restarting,
+    Stopped,
+    CrashLoop,
+}
+
+/// Written to `supervisor-status.json` whenever the state changes, times in unix seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Status {
+    pub state: State,
+    pub supervisor_pid: u32,
+    pub version: String,
+    pub pid: Option<u32>,
+    pub started_at: u64,
+    pub restarts: u32,
+    pub last_exit: Option<String>,
+    pub updated_at: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Status {
+    pub fn read() -> Option<Status> {
+        let text = fs::read_to_string(Config::supervisor_status_path()).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Whether the supervisor that wrote this is still at work.
+    pub fn is_alive(&self) -> bool {
+        matches!(self.state, State::Running | State::Restarting)
+            && is_deployer_running(self.supervisor_pid)
+    }
+
+    fn set(&mut self, state: State) {
+        self.state = state;
+        self.updated_at = now();
+        // renamed into place, a reader never sees half of it
+        let path = Config::supervisor_status_path();
+        let tmp = path.with_extension("json.tmp");
+        let written = serde_json::to_vec_pretty(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(&tmp, json)?))
+            .and_then(|_| Ok(fs::rename(&tmp, &path)?));
+        if let Err(e) = written {
+            warn!("{}", t!("status_write_failed", error = e));
+        }
+    }
+}
+
+/// A supervisor other than this process that is still running.
+pub fn running_supervisor() -> Option<u32> {
+    Status::read()
+        .filter(|status| status.supervisor_pid != std::process::id() && status.is_alive())
+        .map(|status| status.supervisor_pid)
+}
+
+/// Print the last status written by a supervisor.
+pub fn print_status() -> Result<()> {
+    let status = Status::read().ok_or_else(|| anyhow!("{}", t!("no_supervisor_status")))?;
+    println!("{}", serde_json::to_string_pretty(&status)?);
+    if matches!(status.state, State::Running | State::Restarting) && !status.is_alive() {
+        println!("{}", t!("supervisor_gone", pid = status.supervisor_pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            tokio::select! {
+                _ = terminate.recv() => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(windows)]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+async fn wait_exit(child: &mut Child) -> Result<ExitStatus> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Keep the started serverbee-web running until the deployer is told to stop. It is
+/// started again after a crash, later each time, and given up on when it crash-loops.
+/// Exiting with success counts as being stopped on purpose. Gives up with `CrashLoop`.
+pub async fn supervise(config: &mut Config, mut child: Child) -> Result<()> {
+    let version = config
+        .get_active_version()
+        .unwrap_or_else(|| config.get_version());
+    let mut crashes = Crashes::default();
+    let mut status = Status {
+        state: State::Running,
+        supervisor_pid: std::process::id(),
+        version: version.clone(),
+        pid: Some(child.id()),
+        started_at: now(),
+        restarts: 0,
+        last_exit: None,
+        updated_at: 0,
+    };
+    status.set(State::Running);
+    info!("{}", t!("supervising", pid = child.id()));
+
+    loop {
+        let exit = tokio::select! {
+            exit = wait_exit(&mut child) => exit?,
+            _ = shutdown_signal() => {
+                info!("{}", t!("supervisor_stopping"));
+                stop_child(&mut child);
+                status.pid = None;
+                status.set(State::Stopped);
+                return Ok(());
+            }
+        };
+        status.pid = None;
+        status.last_exit = Some(exit.to_string());
+        if exit.success() {
+            info!("{}", t!("process_stopped", status = exit));
+            status.set(State::Stopped);
+            return Ok(());
+        }
+        warn!("{}", t!("process_crashed", status = exit));
+
+        child = loop {
+            let delay = match crashes.crash(Instant::now()) {
+                Some(delay) => delay,
+                None => {
+                    status.set(State::CrashLoop);
+                    return Err(CrashLoop.into());
+                }
+            };
+            status.set(State::Restarting);
+            info!("{}", t!("restarting", seconds = delay.as_secs()));
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown_signal() => {
+                    status.set(State::Stopped);
+                    return Ok(());
+                }
+            }
+            match start_checked(config, &version).await {
+                Ok(child) => break child,
+                Err(e) => error!("{}", t!("start_failed", version = version, error = e)),
+            }
+        };
+        status.restarts += 1;
+        status.pid = Some(child.id());
+        config.set_active(
+            version.clone(),
+            config.get_previous_versions(),
+            config.get_skip_version(),
+            child.id(),
+        );
+        status.set(State::Running);
+    }
+}
@@ -0,0 +1,8 @@
+#[cfg(test)]
+mod supervisor_test;
+#[cfg(test)]
+mod upgrade_test;
+#[cfg(test)]
+mod verify_test;
+#[cfg(test)]
+mod web_options_test;
@@ -0,0 +1,58 @@
+use crate::constant::{
+    CRASH_LOOP_EXIT_CODE, CRASH_LOOP_LIMIT, CRASH_LOOP_WINDOW, RESTART_BACKOFF_MAX,
+};
+use crate::supervisor::{Crashes, State, Status};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_restart_backoff_doubles() {
+    let mut crashes = Crashes::default();
+    let now = Instant::now();
+    let delays: Vec<_> = (1..CRASH_LOOP_LIMIT)
+        .map(|i| crashes.crash(now + Duration::from_secs(i as u64)).unwrap())
+        .collect();
+    for pair in delays.windows(2) {
+        assert_eq!(pair[1], (pair[0] * 2).min(RESTART_BACKOFF_MAX));
+    }
+    assert_eq!(crashes.crash(now + Duration::from_secs(60)), None);
+}
+
+#[test]
+fn test_old_crashes_are_forgotten() {
+    let mut crashes = Crashes::default();
+    let mut now = Instant::now();
+    let first = crashes.crash(now).unwrap();
+    for _ in 0..CRASH_LOOP_LIMIT * 2 {
+        now += CRASH_LOOP_WINDOW;
+        assert_eq!(crashes.crash(now), Some(first));
+    }
+}
+
+#[test]
+fn test_status_round_trip() {
+    let status = Status {
+        state: State::CrashLoop,
+        supervisor_pid: 42,
+        version: "v2.3.0".into(),
+        pid: None,
+        started_at: 1,
+        restarts: 4,
+        last_exit: Some("exit status: 1".into()),
+        updated_at: 2,
+    };
+    let json = serde_json::to_string(&status).unwrap();
+    assert!(json.contains(r#""state":"crash_loop""#));
+    assert_eq!(serde_json::from_str::<Status>(&json).unwrap(), status);
+}
+
+#[test]
+fn test_unit_keeps_crash_loop_stopped() {
+    let unit = crate::daemon_content("/opt/serverbee-deploy".into(), true);
+    assert!(unit.contains("Restart=on-failure"));
+    assert!(unit.contains(&format!(
+        "RestartPreventExitStatus={}",
+        CRASH_LOOP_EXIT_CODE
+    )));
+    let unit = crate::daemon_content("/opt/serverbee-deploy".into(), false);
+    assert!(!unit.contains("Restart"));
+}
@@ -0,0 +1,40 @@
+use crate::upgrade::previous_versions;
+
+fn versions(versions: &[&str]) -> Vec<String> {
+    versions.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_upgrade_keeps_latest_versions() {
+    let (kept, removed) = previous_versions(
+        versions(&["v1.1.0", "v1.0.0"]),
+        Some("v1.2.0".into()),
+        "v1.3.0",
+        2,
+    );
+    assert_eq!(kept, versions(&["v1.2.0", "v1.1.0"]));
+    assert_eq!(removed, versions(&["v1.0.0"]));
+
+    let (kept, removed) = previous_versions(kept, Some("v1.3.0".into()), "v1.4.0", 0);
+    assert!(kept.is_empty());
+    assert_eq!(removed, versions(&["v1.3.0", "v1.2.0", "v1.1.0"]));
+}
+
+#[test]
+fn test_reinstall_does_not_keep_itself() {
+    // going forward again after a rollback
+    let (kept, removed) = previous_versions(
+        versions(&["v1.3.0", "v1.1.0"]),
+        Some("v1.2.0".into()),
+        "v1.3.0",
+        2,
+    );
+    assert_eq!(kept, versions(&["v1.2.0", "v1.1.0"]));
+    assert!(removed.is_empty());
+
+    let (kept, _) = previous_versions(versions(&["v1.1.0"]), Some("v1.2.0".into()), "v1.2.0", 2);
+    assert_eq!(kept, versions(&["v1.1.0"]));
+
+    let (kept, _) = previous_versions(vec![], None, "v1.0.0", 2);
+    assert!(kept.is_empty());
+}
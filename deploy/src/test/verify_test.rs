@@ -0,0 +1,78 @@
+use crate::verify::{sha256_file, Manifest, PublicKey};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use std::env::temp_dir;
+use std::fs;
+
+const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+fn key_pair(seed: u8) -> Ed25519KeyPair {
+    Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap()
+}
+
+fn public_key(pair: &Ed25519KeyPair) -> String {
+    let key = [b"Ed".as_slice(), &KEY_ID, pair.public_key().as_ref()].concat();
+    format!(
+        "untrusted comment: minisign public key\n{}\n",
+        BASE64.encode(key)
+    )
+}
+
+fn minisig(pair: &Ed25519KeyPair, data: &[u8], comment: &str) -> String {
+    let signature = pair.sign(data);
+    let global = pair.sign(&[signature.as_ref(), comment.as_bytes()].concat());
+    format!(
+        "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+        BASE64.encode([b"Ed".as_slice(), &KEY_ID, signature.as_ref()].concat()),
+        comment,
+        BASE64.encode(global)
+    )
+}
+
+#[test]
+fn test_manifest_verifies_archive() {
+    let path = temp_dir().join("serverbee-verify-test.zip");
+    fs::write(&path, b"archive").unwrap();
+    let checksum = sha256_file(&path).unwrap();
+
+    let manifest = Manifest::parse(&format!(
+        "{}  serverbee-web.zip\n{} *other.zip\n",
+        checksum,
+        "0".repeat(64)
+    ))
+    .unwrap();
+    assert_eq!(
+        manifest.checksum("other.zip"),
+        Some("0".repeat(64).as_str())
+    );
+    assert!(manifest.verify("serverbee-web.zip", &path).is_ok());
+
+    let e = manifest.verify("other.zip", &path).unwrap_err().to_string();
+    assert!(e.contains("checksum mismatch"), "{}", e);
+    let e = manifest
+        .verify("missing.zip", &path)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("not listed"), "{}", e);
+
+    assert!(Manifest::parse("").is_err());
+    assert!(Manifest::parse("abc  serverbee-web.zip").is_err());
+}
+
+#[test]
+fn test_signature_is_checked() {
+    let pair = key_pair(1);
+    let key = PublicKey::parse(&public_key(&pair)).unwrap();
+    let manifest = b"checksums";
+    let signature = minisig(&pair, manifest, "timestamp:1\tfile:SHA256SUMS");
+
+    assert!(key.verify(manifest, &signature).is_ok());
+    assert!(key.verify(b"tampered", &signature).is_err());
+
+    let tampered_comment = signature.replace("file:SHA256SUMS", "file:other");
+    assert!(key.verify(manifest, &tampered_comment).is_err());
+
+    let other = PublicKey::parse(&public_key(&key_pair(2))).unwrap();
+    assert!(other.verify(manifest, &signature).is_err());
+}
@@ -0,0 +1,90 @@
+use crate::cli::Args;
+use crate::web_options::WebOptions;
+use clap::Parser;
+use std::env::temp_dir;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_merge_keeps_and_clears() {
+    let mut web = WebOptions::default();
+    web.merge(WebOptions {
+        app_token: Some("app".into()),
+        server_host: Some("hub.example.com".into()),
+        disable_ssl: Some(true),
+        data_dir: Some("data".into()),
+        ..Default::default()
+    });
+    assert!(Path::new(web.data_dir.as_ref().unwrap()).is_absolute());
+
+    web.merge(WebOptions {
+        server_host: Some("".into()),
+        disable_ssl: Some(false),
+        ..Default::default()
+    });
+    assert_eq!(web.app_token.as_deref(), Some("app"));
+    assert_eq!(web.server_host, None);
+    assert_eq!(web.disable_ssl, Some(false));
+
+    assert_eq!(
+        web.args(),
+        vec!["-d".to_string(), web.data_dir.clone().unwrap()]
+    );
+    assert_eq!(
+        web.envs(),
+        vec![
+            ("SERVERBEE_APP_TOKEN", "app".to_string()),
+            ("SERVERBEE_SERVER_DISABLE_SSL", "false".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_config_file_fills_missing_args() {
+    let path = temp_dir().join("serverbee-deploy-args-test.yml");
+    fs::write(
+        &path,
+        "port: 8081\nyes: true\napp_token: from-file\nserver_token: from-file\n",
+    )
+    .unwrap();
+    let path = path.display().to_string();
+
+    let mut args = Args::parse_from([
+        "serverbee-deploy",
+        "--config",
+        &path,
+        "--app-token",
+        "from-args",
+    ]);
+    args.merge_file().unwrap();
+    assert_eq!(args.port, Some(8081));
+    assert!(args.yes);
+    let web = args.web_options();
+    assert_eq!(web.app_token.as_deref(), Some("from-args"));
+    assert_eq!(web.server_token.as_deref(), Some("from-file"));
+
+    fs::write(&path, "prot: 8081\n").unwrap();
+    let mut args = Args::parse_from(["serverbee-deploy", "--config", &path]);
+    assert!(args.merge_file().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_file_is_private() {
+    use crate::storage_config::open_private;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_dir().join(format!("deploy-config-{}.yml", std::process::id()));
+    fs::write(&path, "old").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    open_private(&path).unwrap().write_all(b"new").unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    fs::remove_file(&path).unwrap();
+    open_private(&path).unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,165 @@
+use crate::config::Config;
+use crate::process::{health_check, is_running, kill_child, start_process, stop_process};
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use std::fs;
+use std::process::Child;
+
+/// Versions kept after `version` replaces `active`, the latest first, and the ones to remove.
+pub fn previous_versions(
+    mut previous: Vec<String>,
+    active: Option<String>,
+    version: &str,
+    keep: usize,
+) -> (Vec<String>, Vec<String>) {
+    previous.retain(|v| v != version);
+    if let Some(active) = active.filter(|active| active != version) {
+        previous.retain(|v| v != &active);
+        previous.insert(0, active);
+    }
+    let removed = previous.split_off(keep.min(previous.len()));
+    (previous, removed)
+}
+
+/// Start serverbee-web and wait until it answers, it is killed when it doesn't.
+/// Its output goes to the deploy log when supervised.
+pub async fn start_checked(config: &Config, version: &str) -> Result<Child> {
+    let port = config.get_port();
+    let mut child = start_process(
+        &Config::version_bin_path(version),
+        port,
+        &config.get_web_options(),
+        config.get_supervise(),
+    )?;
+    if let Err(e) = health_check(&mut child, port, version).await {
+        kill_child(&mut child);
+        return Err(e);
+    }
+    info!("{}", t!("run_success"));
+    Ok(child)
+}
+
+/// Record the started process, kept in `started` for a supervisor.
+fn started_pid(started: &mut Option<Child>, child: Child) -> u32 {
+    let pid = child.id();
+    *started = Some(child);
+    pid
+}
+
+/// Run the version of `config`. The previous serverbee-web is stopped first, the
+/// database is only opened by one process at a time, and is started again when the
+/// new one fails its health check. The running one is restarted when `restart`.
+pub async fn activate(
+    config: &mut Config,
+    restart: bool,
+    started: &mut Option<Child>,
+) -> Result<()> {
+    let version = config.get_version();
+    let active = config.get_active_version();
+    let port = config.get_port();
+
+    if active.as_deref() == Some(version.as_str()) && !restart {
+        if let Some(pid) = config.get_pid().filter(|pid| is_running(*pid)) {
+            info!("{}", t!("already_running", version = version, pid = pid));
+            return Ok(());
+        }
+    }
+
+    stop_process(config.get_pid(), port)?;
+
+    let e = match start_checked(config, &version).await {
+        Ok(child) => {
+            let pid = started_pid(started, child);
+            let (previous, removed) = previous_versions(
+                config.get_previous_versions(),
+                active,
+                &version,
+                config.get_keep_versions(),
+            );
+            config.set_active(version, previous, config.get_skip_version(), pid);
+            remove_versions(&removed);
+            return Ok(());
+        }
+        Err(e) => e,
+    };
+
+    error!("{}", t!("start_failed", version = version, error = e));
+    match active.filter(|active| active != &version && Config::version_bin_path(active).exists()) {
+        Some(active) => {
+            let pid = started_pid(started, start_checked(config, &active).await?);
+            // not installed again by the next run
+            config.set_active(
+                active.clone(),
+                config.get_previous_versions(),
+                Some(version.clone()),
+                pid,
+            );
+            Err(anyhow!(
+                "{}",
+                t!("upgrade_rolled_back", version = version, previous = active)
+            ))
+        }
+        None => Err(e),
+    }
+}
+
+/// Run the version that ran before the active one again.
+pub async fn rollback(config: &mut Config, started: &mut Option<Child>) -> Result<()> {
+    let active = config
+        .get_active_version()
+        .ok_or_else(|| anyhow!("{}", t!("no_active_version")))?;
+    let mut previous = config.get_previous_versions();
+    let index = previous
+        .iter()
+        .position(|v| Config::version_bin_path(v).exists())
+        .ok_or_else(|| anyhow!("{}", t!("no_previous_version")))?;
+    let target = previous.remove(index);
+    let port = config.get_port();
+
+    info!(
+        "{}",
+        t!("rolling_back", version = active, previous = target)
+    );
+    stop_process(config.get_pid(), port)?;
+
+    match start_checked(config, &target).await {
+        Ok(child) => {
+            let pid = started_pid(started, child);
+            // kept to go forward again, but not installed again by the next run
+            previous.insert(0, active.clone());
+            config.set_active(target, previous, Some(active), pid);
+            Ok(())
+        }
+        Err(e) => {
+            error!("{}", t!("start_failed", version = target, error = e));
+            let pid = started_pid(started, start_checked(config, &active).await?);
+            config.set_active(
+                active,
+                config.get_previous_versions(),
+                config.get_skip_version(),
+                pid,
+            );
+            Err(anyhow!("{}", t!("rollback_failed", previous = target)))
+        }
+    }
+}
+
+fn remove_versions(versions: &[String]) {
+    for version in versions {
+        // a stored value never points outside the deploy directory
+        if version.is_empty() || version.starts_with('.') || version.contains(['/', '\\']) {
+            continue;
+        }
+        let dir = Config::version_dir(version);
+        if !dir.exists() {
+            continue;
+        }
+        match fs::remove_dir_all(&dir) {
+            Ok(_) => info!("{}", t!("version_removed", path = dir.display())),
+            Err(e) => warn!(
+                "{}",
+                t!("version_remove_failed", path = dir.display(), error = e)
+            ),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Name of the checksum manifest published next to the archives of a release.
+pub const MANIFEST_NAME: &str = "SHA256SUMS";
+
+/// Name of the minisign signature of the manifest.
+pub const SIGNATURE_NAME: &str = "SHA256SUMS.minisig";
+
+const TRUSTED_COMMENT: &str = "trusted comment: ";
+
+/// Checksums of a release in `sha256sum` format, `<hex>  <file name>` per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    checksums: HashMap<String, String>,
+}
+
+impl Manifest {
+    pub fn parse(text: &str) -> Result<Manifest> {
+        let mut checksums = HashMap::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (checksum, name) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow!("malformed manifest line: {}", line))?;
+            // `*` marks binary mode in `sha256sum` output
+            let name = name.trim_start().trim_start_matches('*');
+            if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!("malformed checksum for {}: {}", name, checksum));
+            }
+            checksums.insert(name.to_string(), checksum.to_ascii_lowercase());
+        }
+        if checksums.is_empty() {
+            return Err(anyhow!("manifest is empty"));
+        }
+        Ok(Manifest { checksums })
+    }
+
+    pub fn checksum(&self, name: &str) -> Option<&str> {
+        self.checksums.get(name).map(String::as_str)
+    }
+
+    /// Check the file against the checksum listed for `name`.
+    pub fn verify(&self, name: &str, path: &Path) -> Result<()> {
+        let expected = self
+            .checksum(name)
+            .ok_or_else(|| anyhow!("{} is not listed in {}", name, MANIFEST_NAME))?;
+        let actual = sha256_file(path)?;
+        if actual != expected {
+            return Err(anyhow!(
+                "checksum mismatch for {}, expected {} but got {}",
+                name,
+                expected,
+                actual
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 of a file.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Decode the first non-comment line of a minisign file.
+fn decode_line(text: &str, len: usize, what: &str) -> Result<Vec<u8>> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+        .ok_or_else(|| anyhow!("{} is empty", what))?;
+    let bytes = BASE64
+        .decode(line)
+        .map_err(|e| anyhow!("malformed {}: {}", what, e))?;
+    if bytes.len() != len {
+        return Err(anyhow!("malformed {}: {} bytes", what, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// A minisign public key, as printed by `minisign -G` or read from its `.pub` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl PublicKey {
+    pub fn parse(text: &str) -> Result<PublicKey> {
+        let bytes = decode_line(text, 42, "public key")?;
+        if &bytes[..2] != b"Ed" {
+            return Err(anyhow!("public key is not an Ed25519 minisign key"));
+        }
+        Ok(PublicKey {
+            key_id: bytes[2..10].try_into()?,
+            key: bytes[10..].try_into()?,
+        })
+    }
+
+    /// Check a minisign signature of `data`, made with `minisign -S -l`, and its trusted comment.
+    pub fn verify(&self, data: &[u8], minisig: &str) -> Result<()> {
+        let signature = decode_line(minisig, 74, "signature")?;
+        match &signature[..2] {
+            b"Ed" => {}
+            // prehashed with BLAKE2b, the default of minisign 0.10
+            b"ED" => {
+                return Err(anyhow!(
+                    "prehashed signatures are not supported, sign with `minisign -S -l`"
+                ))
+            }
+            _ => return Err(anyhow!("unknown signature algorithm")),
+        }
+        if signature[2..10] != self.key_id {
+            return Err(anyhow!("signature was made with another key"));
+        }
+        let key = UnparsedPublicKey::new(&ED25519, &self.key);
+        key.verify(data, &signature[10..])
+            .map_err(|_| anyhow!("signature does not match"))?;
+
+        let mut lines = minisig.lines().map(str::trim);
+        let comment = lines
+            .find_map(|l| l.strip_prefix(TRUSTED_COMMENT))
+            .ok_or_else(|| anyhow!("signature has no trusted comment"))?;
+        let global = BASE64
+            .decode(lines.find(|l| !l.is_empty()).unwrap_or_default())
+            .map_err(|e| anyhow!("malformed trusted comment signature: {}", e))?;
+        key.verify(&[&signature[10..], comment.as_bytes()].concat(), &global)
+            .map_err(|_| anyhow!("trusted comment signature does not match"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::path::{self, Path};
+
+/// Settings of serverbee-web passed when the deployer starts it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WebOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_ssl: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_dir: Option<String>,
+    /// Config file of serverbee-web
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+}
+
+/// Paths are kept absolute, serverbee-web may be started from another directory.
+fn absolute(value: String) -> String {
+    match path::absolute(Path::new(&value)) {
+        Ok(path) => path.display().to_string(),
+        Err(_) => value,
+    }
+}
+
+impl WebOptions {
+    pub fn is_empty(&self) -> bool {
+        self == &WebOptions::default()
+    }
+
+    /// Take the values set in `other`, an empty string clears one.
+    pub fn merge(&mut self, other: WebOptions) {
+        fn merge(current: &mut Option<String>, new: Option<String>, path: bool) {
+            match new {
+                Some(new) if new.is_empty() => *current = None,
+                Some(new) if path => *current = Some(absolute(new)),
+                Some(new) => *current = Some(new),
+                None => {}
+            }
+        }
+        merge(&mut self.app_token, other.app_token, false);
+        merge(&mut self.server_token, other.server_token, false);
+        merge(&mut self.server_host, other.server_host, false);
+        merge(&mut self.data_dir, other.data_dir, true);
+        merge(&mut self.log_dir, other.log_dir, true);
+        merge(&mut self.config, other.config, true);
+        if other.disable_ssl.is_some() {
+            self.disable_ssl = other.disable_ssl;
+        }
+    }
+
+    /// Arguments of serverbee-web.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        let options = [
+            ("-d", &self.data_dir),
+            ("-l", &self.log_dir),
+            ("--config", &self.config),
+        ];
+        for (name, value) in options {
+            if let Some(value) = value {
+                args.push(name.to_string());
+                args.push(value.clone());
+            }
+        }
+        args
+    }
+
+    /// Environment of serverbee-web. Tokens are passed here to keep them out of the process
+    /// list, `disable_ssl` so that `false` also overrides its config file.
+    pub fn envs(&self) -> Vec<(&'static str, String)> {
+        [
+            ("SERVERBEE_APP_TOKEN", self.app_token.clone()),
+            ("SERVERBEE_SERVER_TOKEN", self.server_token.clone()),
+            ("SERVERBEE_SERVER_HOST", self.server_host.clone()),
+            (
+                "SERVERBEE_SERVER_DISABLE_SSL",
+                self.disable_ssl.map(|disable| disable.to_string()),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+}
@@ -1,2 +1,3 @@
 export const kCommunicationToken = 'communication-token'
 export const kTerminalSettings = 'terminal-settings'
+export const kRefreshToken = 'refresh-token'
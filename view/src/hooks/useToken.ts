@@ -1,14 +1,14 @@
 import { useEffect } from 'react'
-import { kCommunicationToken } from '@/const'
 import { verifyToken } from '@/requests/token'
 import { useStore } from '@/store'
 import { kSetCommunicationToken, kSetIsVerified } from '@/store/token'
+import { getAccessToken } from '@/lib/session'
 
 export const useToken = () => {
     const { token, tokenDispatch } = useStore()
 
     useEffect(() => {
-        const token = localStorage.getItem(kCommunicationToken)
+        const token = getAccessToken()
         if (token) {
             tokenDispatch({ type: kSetCommunicationToken, payload: token })
         }
@@ -0,0 +1,32 @@
+import { kCommunicationToken, kRefreshToken } from '@/const'
+
+export interface SessionTokens {
+    access_token: string
+    expires_in: number
+    refresh_token: string
+    refresh_expires_in: number
+}
+
+// remembered sessions live in localStorage, the others only last for this tab
+const storages = [localStorage, sessionStorage]
+
+export const getAccessToken = (): string | null =>
+    localStorage.getItem(kCommunicationToken) ??
+    sessionStorage.getItem(kCommunicationToken)
+
+export const getRefreshToken = (): string | null =>
+    localStorage.getItem(kRefreshToken) ?? sessionStorage.getItem(kRefreshToken)
+
+export const clearSession = () =>
+    storages.forEach((storage) => {
+        storage.removeItem(kCommunicationToken)
+        storage.removeItem(kRefreshToken)
+    })
+
+export const saveSession = (tokens: SessionTokens, remember?: boolean) => {
+    const keep = remember ?? localStorage.getItem(kRefreshToken) !== null
+    clearSession()
+    const storage = keep ? localStorage : sessionStorage
+    storage.setItem(kCommunicationToken, tokens.access_token)
+    storage.setItem(kRefreshToken, tokens.refresh_token)
+}
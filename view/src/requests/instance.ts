@@ -1,24 +1,82 @@
-import axios from 'axios'
+import axios, { InternalAxiosRequestConfig } from 'axios'
 
+import { Result } from '@/types/result'
+import {
+    clearSession,
+    getAccessToken,
+    getRefreshToken,
+    saveSession,
+    SessionTokens,
+} from '@/lib/session'
 import { toast } from '@/components/ui/use-toast'
 
+const baseURL = process.env.NODE_ENV === 'development' ? '/api' : undefined
+
 const instance = axios.create({
-    baseURL: process.env.NODE_ENV === 'development' ? '/api' : undefined,
+    baseURL,
     withCredentials: true,
     timeout: 5000,
 })
 
+let refreshing: Promise<string | undefined> | null = null
+const retried = new WeakSet<InternalAxiosRequestConfig>()
+
+// trade the refresh token for a new access token, once for concurrent requests
+const refreshAccessToken = () => {
+    const refreshToken = getRefreshToken()
+    if (!refreshToken) return Promise.resolve(undefined)
+    if (!refreshing) {
+        refreshing = axios
+            .post<Result<SessionTokens>>(`${baseURL ?? ''}/auth/refresh`, {
+                refresh_token: refreshToken,
+            })
+            .then(({ data }) => {
+                if (data.success && data.data) {
+                    saveSession(data.data)
+                    return data.data.access_token
+                }
+                clearSession()
+                return undefined
+            })
+            .catch(() => undefined)
+            .finally(() => {
+                refreshing = null
+            })
+    }
+    return refreshing
+}
+
+instance.interceptors.request.use(function (config) {
+    const accessToken = getAccessToken()
+    if (accessToken) {
+        config.headers.Authorization = `Bearer ${accessToken}`
+    }
+    return config
+})
+
 instance.interceptors.response.use(
     function (response) {
         return response
     },
-    function (error) {
+    async function (error) {
+        const config = error.config as InternalAxiosRequestConfig | undefined
+        if (
+            error.response?.status === 401 &&
+            config &&
+            !retried.has(config) &&
+            !config.url?.startsWith('/auth/')
+        ) {
+            retried.add(config)
+            if (await refreshAccessToken()) {
+                return instance(config)
+            }
+        }
         toast({
             variant: 'destructive',
             title: 'Uh oh! Something went wrong.',
             description: error.toString(),
         })
-        if (error.response.status) {
+        if (error.response?.status) {
             if (error.response.status === 401) {
                 window.location.href = '/login'
             }
@@ -14,7 +14,7 @@ export const fetchSettings = async (
 ): Promise<Settings | undefined> => {
     const { data } = await instance.get<Result<Settings>>(url, {
         headers: {
-            Authorization: token,
+            Authorization: `Bearer ${token}`,
         },
     })
     if (!data.success)
@@ -32,7 +32,7 @@ export const updateGeneralSettings = async (
             settings,
             {
                 headers: {
-                    Authorization: token,
+                    Authorization: `Bearer ${token}`,
                 },
             }
         )
@@ -52,7 +52,7 @@ export const updateServerSettings = async (
             settings,
             {
                 headers: {
-                    Authorization: token,
+                    Authorization: `Bearer ${token}`,
                 },
             }
         )
@@ -69,7 +69,7 @@ export const updateSecuritySettings = async (
     try {
         const { data } = await instance.post<Result>('/config/app', settings, {
             headers: {
-                Authorization: token,
+                Authorization: `Bearer ${token}`,
             },
         })
         return data.success
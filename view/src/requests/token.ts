@@ -1,15 +1,42 @@
 import instance from '@/requests/instance'
 
-export const getToken = async () => {
-    const { data } = await instance.get<string>('/local/token/view')
-    return data
+import { Result } from '@/types/result'
+import { SessionTokens } from '@/lib/session'
+
+export const login = async (token: string) => {
+    try {
+        const { data } = await instance.post<Result<SessionTokens>>(
+            '/auth/login',
+            { token }
+        )
+        return data.success ? data.data : undefined
+    } catch {
+        return undefined
+    }
+}
+
+export const logout = async () => {
+    try {
+        await instance.post('/auth/logout')
+    } catch {
+        /* empty */
+    }
+}
+
+// one-time ticket for opening a WebSocket, so no token ends up in the url
+export const fetchTicket = async () => {
+    const { data } =
+        await instance.post<Result<{ ticket: string }>>('/auth/ticket')
+    if (!data.success || !data.data)
+        throw new Error(data.message ?? 'Failed to fetch ticket')
+    return data.data.ticket
 }
 
 export const verifyToken = async (token: string) => {
     try {
         await instance.get('/check', {
             headers: {
-                Authorization: token,
+                Authorization: `Bearer ${token}`,
             },
         })
         return true
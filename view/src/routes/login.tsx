@@ -1,11 +1,12 @@
 import { useState } from 'react'
-import { kCommunicationToken } from '@/const'
 import { zodResolver } from '@hookform/resolvers/zod'
 import { Eye, EyeOff } from 'lucide-react'
 import { useForm } from 'react-hook-form'
 import { Link, useNavigate } from 'react-router-dom'
 import * as z from 'zod'
 
+import { login } from '@/requests/token'
+import { saveSession } from '@/lib/session'
 import { useToken } from '@/hooks/useToken'
 import { Button } from '@/components/ui/button'
 import { Checkbox } from '@/components/ui/checkbox'
@@ -41,12 +42,12 @@ export default function LoginPage() {
     })
 
     const onSubmit = async (data: LoginFormValue) => {
-        const value = data.token
-        const res = await verify(value)
+        const tokens = await login(data.token)
+        if (tokens) {
+            saveSession(tokens, checked)
+        }
+        const res = tokens ? await verify(tokens.access_token) : false
         if (res) {
-            if (checked) {
-                localStorage.setItem(kCommunicationToken, value)
-            }
             toast({
                 title: 'Verify success',
             })
@@ -13,6 +13,7 @@ import SearchWidget from '@/routes/terminal/search.tsx'
 import { Cog } from 'lucide-react'
 
 import { wsBaseUrl } from '@/lib/utils.ts'
+import { fetchTicket } from '@/requests/token'
 import { useTerminalSettings } from '@/hooks/useTerminalSettings.tsx'
 import { Button } from '@/components/ui/button.tsx'
 import {
@@ -34,11 +35,8 @@ function TerminalPage() {
 
     useEffect(() => {
         if (!terminalDivRef.current) return
-        const url = terminalSettings.shell
-            ? `${wsBaseUrl()}/pty?shell=${terminalSettings.shell}`
-            : `${wsBaseUrl()}/pty`
-        const webSocket = new WebSocket(url)
-        webSocket.binaryType = 'arraybuffer'
+        let webSocket: WebSocket | undefined
+        let closed = false
         const terminal = new Terminal({
             cursorBlink: terminalSettings?.cursorBlink,
             cursorStyle: terminalSettings?.cursorStyle as
@@ -58,7 +56,7 @@ function TerminalPage() {
         terminalRef.current = terminal
 
         terminal.onResize(({ cols, rows }) => {
-            webSocket.send(
+            webSocket?.send(
                 new Uint8Array([
                     0x37,
                     0x37,
@@ -89,21 +87,36 @@ function TerminalPage() {
         const resizeFn = () => fitAddon.fit()
         window.addEventListener('resize', resizeFn)
 
-        webSocket.onopen = () => {
-            const attachAddon = new AttachAddon(webSocket)
-            terminal.loadAddon(attachAddon)
-            fitAddon.fit()
-        }
-
-        webSocket.onclose = (reason) => {
+        const onClose = (reason: unknown) => {
             terminalRef.current?.write(
                 '\r\nConnection closed. Please \x1b[33mPress Enter\x1b[0m or \x1b[33mRefresh the Page\x1b[0m to attempt reconnection. \r\n'
             )
             console.log('WebSocket closed', reason)
         }
 
+        fetchTicket()
+            .then((ticket) => {
+                if (closed) return
+                const shell = terminalSettings.shell
+                    ? `&shell=${terminalSettings.shell}`
+                    : ''
+                const ws = new WebSocket(
+                    `${wsBaseUrl()}/pty?ticket=${ticket}${shell}`
+                )
+                ws.binaryType = 'arraybuffer'
+                ws.onopen = () => {
+                    const attachAddon = new AttachAddon(ws)
+                    terminal.loadAddon(attachAddon)
+                    fitAddon.fit()
+                }
+                ws.onclose = onClose
+                webSocket = ws
+            })
+            .catch(onClose)
+
         return () => {
-            webSocket.close()
+            closed = true
+            webSocket?.close()
             terminal.dispose()
             window.removeEventListener('resize', resizeFn)
         }
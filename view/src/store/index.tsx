@@ -5,7 +5,7 @@ import {
     useEffect,
     useReducer,
 } from 'react'
-import { kCommunicationToken, kTerminalSettings } from '@/const'
+import { kTerminalSettings } from '@/const'
 import {
     FusionContext,
     fusionReducer,
@@ -28,7 +28,9 @@ import { kSetWs, kSetWsStatus, WsContext, wsReducer, WsState } from '@/store/ws'
 
 import { Fusion } from '@/types/fusion.ts'
 import { TerminalSettings } from '@/types/settings.ts'
+import { getAccessToken } from '@/lib/session'
 import { wsBaseUrl } from '@/lib/utils.ts'
+import { fetchTicket } from '@/requests/token'
 
 type StoreContextProps = FusionContext &
     TokenContext &
@@ -45,7 +47,7 @@ export const StoreProvider = ({ children }: { children: ReactNode }) => {
         {} as FusionState
     )
     const [token, tokenDispatch] = useReducer(tokenReducer, {
-        communicationToken: localStorage.getItem(kCommunicationToken) ?? '',
+        communicationToken: getAccessToken() ?? '',
         isVerified: false,
     })
     const [history, historyDispatch] = useReducer(historyReducer, {
@@ -77,34 +79,48 @@ export const StoreProvider = ({ children }: { children: ReactNode }) => {
         if (token.communicationToken) {
             wsDispatch({ type: kSetWsStatus, payload: WebSocket.CONNECTING })
 
-            const instance = new WebSocket(
-                `${wsBaseUrl()}/ws?token=${token.communicationToken}`
-            )
+            let instance: WebSocket | undefined
+            let closed = false
 
-            wsDispatch({ type: kSetWs, payload: instance })
+            fetchTicket()
+                .then((ticket) => {
+                    if (closed) return
+                    instance = new WebSocket(`${wsBaseUrl()}/ws?ticket=${ticket}`)
+                    const ws = instance
 
-            instance.onopen = () => {
-                console.log('Websocket Connected')
-                instance.send('/more')
-                wsDispatch({ type: kSetWsStatus, payload: WebSocket.OPEN })
-            }
-            instance.onmessage = (e) => {
-                const fusion = JSON.parse(e.data) as Fusion
-                fusionDispatch({ type: kSetFusion, payload: fusion })
-                historyDispatch({ type: kHistoryAdd, payload: fusion.overview })
-            }
-            instance.onclose = () => {
-                wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSED })
-                console.log('Disconnected')
-            }
-            instance.onerror = (e) => {
-                wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSED })
-                console.log('Websocket Error', e)
-            }
+                    wsDispatch({ type: kSetWs, payload: ws })
+
+                    ws.onopen = () => {
+                        console.log('Websocket Connected')
+                        ws.send('/more')
+                        wsDispatch({ type: kSetWsStatus, payload: WebSocket.OPEN })
+                    }
+                    ws.onmessage = (e) => {
+                        const fusion = JSON.parse(e.data) as Fusion
+                        fusionDispatch({ type: kSetFusion, payload: fusion })
+                        historyDispatch({
+                            type: kHistoryAdd,
+                            payload: fusion.overview,
+                        })
+                    }
+                    ws.onclose = () => {
+                        wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSED })
+                        console.log('Disconnected')
+                    }
+                    ws.onerror = (e) => {
+                        wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSED })
+                        console.log('Websocket Error', e)
+                    }
+                })
+                .catch((e) => {
+                    wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSED })
+                    console.log('Websocket Error', e)
+                })
             return () => {
                 console.log('Closing websocket')
+                closed = true
                 wsDispatch({ type: kSetWsStatus, payload: WebSocket.CLOSING })
-                instance.close()
+                instance?.close()
             }
         }
     }, [token.communicationToken])
@@ -0,0 +1,89 @@
+use crate::config::access::{AccessConfig, AccessGroup};
+use crate::config::config::Config;
+use actix_web::web::Data;
+use actix_web::HttpRequest;
+use log::warn;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+/// Headers a proxy adds, their presence means the socket peer is not the client.
+const FORWARDED_HEADERS: [&str; 3] = ["X-Forwarded-For", "Forwarded", "X-Real-IP"];
+
+fn access_config(req: &HttpRequest) -> AccessConfig {
+    req.app_data::<Data<Arc<RwLock<Config>>>>()
+        .and_then(|config| config.read().ok().map(|config| config.access_config()))
+        .unwrap_or_default()
+}
+
+fn peer_ip(req: &HttpRequest) -> Option<IpAddr> {
+    req.peer_addr().map(|addr| addr.ip().to_canonical())
+}
+
+/// The client behind a chain of trusted proxies, read from the right of X-Forwarded-For
+/// so entries the client made up itself are never reached.
+fn forwarded_client(req: &HttpRequest, access: &AccessConfig) -> Option<IpAddr> {
+    let hops: Vec<IpAddr> = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+        .map(|ip| ip.to_canonical())
+        .collect();
+    hops.iter()
+        .rev()
+        .find(|ip| !access.is_trusted_proxy(**ip))
+        .or_else(|| hops.first())
+        .copied()
+}
+
+pub fn resolve_client_ip(req: &HttpRequest, access: &AccessConfig) -> Option<IpAddr> {
+    let peer = peer_ip(req)?;
+    if access.is_trusted_proxy(peer) {
+        return forwarded_client(req, access).or(Some(peer));
+    }
+    Some(peer)
+}
+
+/// Address of the client, X-Forwarded-For is only believed from trusted proxies.
+pub fn client_ip(req: &HttpRequest) -> String {
+    resolve_client_ip(req, &access_config(req))
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Why the request is refused by the access rule of its route group, if it is.
+pub fn denied(req: &HttpRequest) -> Option<String> {
+    let reason = check(req, &access_config(req))?;
+    warn!(
+        "Access denied to {} {}: {}",
+        req.method(),
+        req.path(),
+        reason
+    );
+    Some(reason)
+}
+
+/// Match a request against `access`, local routes are judged by the socket address alone.
+pub fn check(req: &HttpRequest, access: &AccessConfig) -> Option<String> {
+    let group = AccessGroup::of(req.path());
+    let ip = if group == AccessGroup::Local {
+        if FORWARDED_HEADERS
+            .iter()
+            .any(|header| req.headers().contains_key(*header))
+        {
+            return Some("local routes can not be reached through a proxy".to_string());
+        }
+        peer_ip(req)
+    } else {
+        resolve_client_ip(req, access)
+    };
+    match ip {
+        Some(ip) if access.rule(group).permits(ip) => None,
+        Some(ip) => Some(format!("{} is not allowed to reach {} routes", ip, group)),
+        None => Some(format!(
+            "unknown address is not allowed to reach {} routes",
+            group
+        )),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::config::constant::{AUDIT_TREE, DEFAULT_AUDIT_QUERY_LIMIT, MAX_AUDIT_QUERY_LIMIT};
+use crate::db::db_wrapper::DbWrapper;
+use anyhow::{anyhow, Result};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sled::Tree;
+
+/// How the actor of an audit entry authenticated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    Account,
+    ApiToken,
+    Certificate,
+    /// A `/local` request from the machine itself.
+    Local,
+    Anonymous,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditResult {
+    Success,
+    Failure,
+}
+
+/// One privileged action, entries are never changed once written.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub timestamp: u64,
+    /// Account, API token or certificate name.
+    pub actor: String,
+    pub actor_kind: ActorKind,
+    /// API token id or certificate fingerprint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+    pub ip: String,
+    /// Such as `config.update` or `process.kill`.
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub result: AuditResult,
+    /// Why the action failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Conditions entries must all meet, read from the query string of `/audit`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    pub ip: Option<String>,
+    /// Matches the action itself and actions under it, `config` matches `config.update`.
+    pub action: Option<String>,
+    pub target: Option<String>,
+    pub result: Option<AuditResult>,
+    /// Earliest timestamp, inclusive.
+    pub since: Option<u64>,
+    /// Latest timestamp, inclusive.
+    pub until: Option<u64>,
+    /// Only entries with a smaller id, to page back from the last id seen.
+    pub before: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor
+            .as_ref()
+            .is_none_or(|actor| *actor == entry.actor)
+            && self.ip.as_ref().is_none_or(|ip| *ip == entry.ip)
+            && self.action.as_ref().is_none_or(|action| {
+                entry.action == *action || entry.action.starts_with(&format!("{}.", action))
+            })
+            && self
+                .target
+                .as_ref()
+                .is_none_or(|target| entry.target.as_ref() == Some(target))
+            && self.result.is_none_or(|result| result == entry.result)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+            && self.before.is_none_or(|before| entry.id < before)
+    }
+}
+
+/// Append-only audit trail in its own sled tree, keyed by big-endian id
+/// so iteration runs from the oldest entry to the newest.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    db: DbWrapper,
+    tree: Tree,
+}
+
+impl AuditLog {
+    pub fn new(db: DbWrapper) -> Result<Self> {
+        let tree = db.open_tree(AUDIT_TREE)?;
+        Ok(Self { db, tree })
+    }
+
+    /// Write an entry, its id is assigned here. The id generator starts over after the
+    /// database is compacted, ids are kept past the newest entry so none is overwritten.
+    pub fn append(&self, mut entry: AuditEntry) -> Result<AuditEntry> {
+        loop {
+            let last = match self.tree.last()? {
+                Some((key, _)) => key
+                    .as_ref()
+                    .try_into()
+                    .map(u64::from_be_bytes)
+                    .map_err(|_| anyhow!("invalid audit log key"))?,
+                None => 0,
+            };
+            entry.id = self.db.generate_id()?.max(last + 1);
+            let value = serde_json::to_vec(&entry)?;
+            if self
+                .tree
+                .compare_and_swap(entry.id.to_be_bytes(), None as Option<&[u8]>, Some(value))?
+                .is_ok()
+            {
+                return Ok(entry);
+            }
+        }
+    }
+
+    fn entries(&self) -> impl DoubleEndedIterator<Item = AuditEntry> {
+        self.tree.iter().filter_map(|item| match item {
+            Ok((_, value)) => serde_json::from_slice(&value).ok(),
+            Err(e) => {
+                error!("Error reading audit log: {}", e);
+                None
+            }
+        })
+    }
+
+    /// Matching entries, newest first.
+    pub fn query(&self, filter: &AuditFilter) -> Vec<AuditEntry> {
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_AUDIT_QUERY_LIMIT)
+            .min(MAX_AUDIT_QUERY_LIMIT);
+        self.entries()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(limit)
+            .collect()
+    }
+
+    /// Matching entries as JSON lines, oldest first, without a limit unless one is given.
+    pub fn export(&self, filter: &AuditFilter) -> String {
+        let mut entries: Vec<AuditEntry> = self
+            .entries()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect();
+        entries.reverse();
+        entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Remove entries older than `retention_days` and beyond the newest `max_entries`.
+    /// A limit of 0 disables that check. Returns how many entries were removed.
+    pub fn prune(&self, retention_days: u64, max_entries: usize, now: u64) -> usize {
+        let oldest_allowed = now.saturating_sub(retention_days * 24 * 60 * 60);
+        let mut overflow = match max_entries {
+            0 => 0,
+            max => self.count().saturating_sub(max),
+        };
+        let mut removed = 0;
+        for item in self.tree.iter() {
+            let (key, value) = match item {
+                Ok(item) => item,
+                Err(e) => {
+                    error!("Error reading audit log: {}", e);
+                    break;
+                }
+            };
+            let expired = retention_days > 0
+                && serde_json::from_slice::<AuditEntry>(&value)
+                    .map_or(true, |entry| entry.timestamp < oldest_allowed);
+            if !expired && overflow == 0 {
+                break;
+            }
+            if let Err(e) = self.tree.remove(key) {
+                error!("Error removing audit entry: {}", e);
+                break;
+            }
+            overflow = overflow.saturating_sub(1);
+            removed += 1;
+        }
+        if removed > 0 {
+            info!("{} audit entries removed by retention policy", removed);
+        }
+        removed
+    }
+}
@@ -0,0 +1,167 @@
+pub mod audit_log;
+
+use crate::access::client_ip;
+use crate::audit::audit_log::{ActorKind, AuditEntry, AuditLog, AuditResult};
+use crate::config::config::Config;
+use crate::config::constant::AUDIT_PRUNE_INTERVAL;
+use crate::token::communication_token::{Principal, PrincipalKind};
+use crate::traits::json_response::FailureMessage;
+use crate::utils::common_util::get_now_timestamp;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::Method;
+use actix_web::web::Data;
+use actix_web::{HttpMessage, HttpRequest};
+use log::error;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Target of the audited action when the path doesn't name it, set by the handler.
+#[derive(Debug, Clone)]
+pub struct AuditTarget(pub String);
+
+/// Name what a request acted on, for actions whose target is in the body.
+pub fn set_target(req: &HttpRequest, target: impl Into<String>) {
+    req.extensions_mut().insert(AuditTarget(target.into()));
+}
+
+/// The audited action of a request and the target named by its path,
+/// None for requests that aren't privileged.
+pub fn action_of(method: &Method, path: &str) -> Option<(&'static str, Option<String>)> {
+    let path = path.strip_prefix("/local").unwrap_or(path);
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let target = |index: usize| segments.get(index).map(|segment| segment.to_string());
+    let action = match (method.as_str(), segments.as_slice()) {
+        ("POST", ["config", "import"]) => "config.import",
+        ("GET", ["config", "export"]) => "config.export",
+        ("POST", ["config", _]) => "config.update",
+        ("POST", ["token", "rest"]) => "token.reset",
+        ("POST", ["kill"]) => "process.kill",
+        ("GET", ["pty"]) => "terminal.open",
+        ("DELETE", ["pty", "sessions", _]) => "terminal.close",
+        ("GET", ["pty", "recordings", _]) => "recording.download",
+        ("GET", ["pty", "recordings", _, "play"]) => "recording.play",
+        ("POST", ["exec"]) => "exec.run",
+        ("GET", ["exec", "ws"]) => "exec.open",
+        ("POST", ["users"]) => "account.save",
+        ("DELETE", ["users", _]) => "account.delete",
+        ("POST", ["tokens"]) => "api_token.create",
+        ("DELETE", ["tokens", _]) => "api_token.revoke",
+        ("DELETE", ["bans", _]) => "ban.lift",
+        ("POST", ["auth", "login"]) => "auth.login",
+        ("POST", ["auth", "logout"]) => "auth.logout",
+        _ => return None,
+    };
+    let target = match segments.as_slice() {
+        ["config", "import"] | ["config", "export"] => None,
+        ["config", _] | ["users", _] | ["tokens", _] | ["bans", _] => target(1),
+        ["pty", _, _] | ["pty", _, _, "play"] => target(2),
+        _ => None,
+    };
+    Some((action, target))
+}
+
+/// Who is acting and from where, captured from a request
+/// so actions can be recorded after it, e.g. by a WebSocket actor.
+#[derive(Debug, Clone)]
+pub struct Auditor {
+    log: AuditLog,
+    actor: String,
+    actor_kind: ActorKind,
+    actor_id: Option<String>,
+    ip: String,
+}
+
+impl Auditor {
+    pub fn of(req: &HttpRequest) -> Option<Auditor> {
+        let log = req
+            .app_data::<Data<Arc<RwLock<Config>>>>()?
+            .read()
+            .ok()?
+            .audit_log();
+        let principal = req.extensions().get::<Principal>().cloned();
+        let (actor, actor_kind, actor_id) = match principal {
+            Some(principal) => match principal.kind {
+                PrincipalKind::Account(_) => (principal.name, ActorKind::Account, None),
+                PrincipalKind::ApiToken(id) => (principal.name, ActorKind::ApiToken, Some(id)),
+                PrincipalKind::Certificate(fingerprint) => {
+                    (principal.name, ActorKind::Certificate, Some(fingerprint))
+                }
+            },
+            None if req.path().starts_with("/local/") => {
+                ("local".to_string(), ActorKind::Local, None)
+            }
+            None => ("anonymous".to_string(), ActorKind::Anonymous, None),
+        };
+        Some(Auditor {
+            log,
+            actor,
+            actor_kind,
+            actor_id,
+            ip: client_ip(req),
+        })
+    }
+
+    /// Append an entry, `result` carries why the action failed.
+    pub fn record(&self, action: &str, target: Option<String>, result: Result<(), String>) {
+        let entry = AuditEntry {
+            id: 0,
+            timestamp: get_now_timestamp(),
+            actor: self.actor.clone(),
+            actor_kind: self.actor_kind,
+            actor_id: self.actor_id.clone(),
+            ip: self.ip.clone(),
+            action: action.to_string(),
+            target,
+            result: match result {
+                Ok(_) => AuditResult::Success,
+                Err(_) => AuditResult::Failure,
+            },
+            detail: result.err(),
+        };
+        if let Err(e) = self.log.append(entry) {
+            error!("Error writing audit log: {}", e);
+        }
+    }
+}
+
+/// Record the outcome of a privileged request, failed when it was refused
+/// or its handler answered with `success: false`.
+pub fn record_response<B>(res: &ServiceResponse<B>, action: &str, target: Option<String>) {
+    let req = res.request();
+    let auditor = match Auditor::of(req) {
+        Some(auditor) => auditor,
+        None => return,
+    };
+    let target = req
+        .extensions()
+        .get::<AuditTarget>()
+        .map(|target| target.0.clone())
+        .or(target);
+    let status = res.status();
+    let result = if status.is_client_error() || status.is_server_error() {
+        Err(status.to_string())
+    } else {
+        match res.response().extensions().get::<FailureMessage>() {
+            Some(failure) => Err(failure.0.clone()),
+            None => Ok(()),
+        }
+    };
+    auditor.record(action, target, result);
+}
+
+/// Remove entries past the retention policy, once at start and then periodically.
+pub async fn run_pruner(config: Arc<RwLock<Config>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(AUDIT_PRUNE_INTERVAL));
+    loop {
+        interval.tick().await;
+        let (audit_log, audit) = {
+            let config = config.read().unwrap();
+            (config.audit_log(), config.audit_config())
+        };
+        audit_log.prune(
+            audit.retention_days(),
+            audit.max_entries(),
+            get_now_timestamp(),
+        );
+    }
+}
@@ -1,10 +1,18 @@
-use clap::Parser;
+use crate::config::document::ConfigFormat;
+use crate::service::unit::{DEFAULT_SERVICE_NAME, DEFAULT_SERVICE_USER};
+use crate::token::scope::Scope;
+use clap::{Parser, Subcommand};
 
 /// ServerBee 的后端配置项
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 #[clap(disable_help_flag = true)]
 pub struct Args {
+    /// YAML or TOML config file, overridden by SERVERBEE_* environment variables and arguments.
+    /// YAML 或 TOML 配置文件, 可被 SERVERBEE_* 环境变量和命令行参数覆盖
+    #[clap(long)]
+    pub config: Option<String>,
+
     /// Log directory, defaults to the current directory where the program is located.
     /// 日志目录, 默认为程序所在当前目录
     #[clap(short, long)]
@@ -40,8 +48,198 @@ pub struct Args {
     #[clap(long)]
     pub disable_ssl: bool,
 
+    /// Certificate chain PEM file, serves HTTPS with --tls-key.
+    /// 证书链 PEM 文件, 与 --tls-key 一起启用 HTTPS
+    #[clap(long)]
+    pub tls_cert: Option<String>,
+
+    /// Private key PEM file of --tls-cert.
+    /// --tls-cert 对应的私钥 PEM 文件
+    #[clap(long)]
+    pub tls_key: Option<String>,
+
+    /// Serve HTTPS with a self-signed certificate generated on first start.
+    /// 使用首次启动时生成的自签名证书启用 HTTPS
+    #[clap(long)]
+    pub tls_self_signed: bool,
+
+    /// Redirect plain HTTP on this port to HTTPS.
+    /// 将此端口上的 HTTP 请求重定向到 HTTPS
+    #[clap(long)]
+    pub tls_redirect_port: Option<u16>,
+
+    /// File with the base64 key that encrypts tokens in the database, defaults to secret.key in the data directory.
+    /// 加密数据库中 Token 的 base64 密钥文件, 默认为数据目录下的 secret.key
+    #[clap(long)]
+    pub secret_key_file: Option<String>,
+
     /// Print help information.
     /// 打印帮助信息
     #[arg(long, action = clap::ArgAction::Help)]
     help: Option<bool>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Run a terminal command as another user, used internally by terminal profiles.
+    /// 以其他用户身份运行终端命令, 供终端配置内部使用
+    #[command(hide = true)]
+    PtyExec {
+        #[clap(long)]
+        uid: Option<u32>,
+
+        #[clap(long)]
+        gid: Option<u32>,
+
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Manage API tokens, run it while serverbee-web is stopped.
+    /// 管理 API Token, 需在 serverbee-web 停止时运行
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+
+    /// Export or import the configuration, run it while serverbee-web is stopped.
+    /// 导出或导入配置, 需在 serverbee-web 停止时运行
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Inspect or compact the database, run it while serverbee-web is stopped.
+    /// 检查或压缩数据库, 需在 serverbee-web 停止时运行
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+
+    /// Install serverbee-web as a systemd service, run it as root.
+    /// 将 serverbee-web 安装为 systemd 服务, 需以 root 运行
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ServiceAction {
+    /// Write the unit with the data and log directories given by -d and -l, then start it.
+    /// 写入 systemd 服务并启动, 使用 -d 和 -l 指定的数据和日志目录
+    Install {
+        /// Name of the service.
+        /// 服务名
+        #[clap(long, default_value = DEFAULT_SERVICE_NAME)]
+        name: String,
+
+        /// User the service runs as, created when missing.
+        /// 运行服务的用户, 不存在时自动创建
+        #[clap(long, default_value = DEFAULT_SERVICE_USER)]
+        user: String,
+
+        /// Print the unit and the steps without changing anything.
+        /// 仅输出服务文件和执行步骤, 不做修改
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Stop the service and remove the unit, the data and user are kept.
+    /// 停止服务并删除服务文件, 保留数据和用户
+    Uninstall {
+        /// Name of the service.
+        /// 服务名
+        #[clap(long, default_value = DEFAULT_SERVICE_NAME)]
+        name: String,
+
+        /// Print the steps without changing anything.
+        /// 仅输出执行步骤, 不做修改
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Show whether the service is enabled and running.
+    /// 查看服务是否启用和运行
+    Status {
+        /// Name of the service.
+        /// 服务名
+        #[clap(long, default_value = DEFAULT_SERVICE_NAME)]
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DbAction {
+    /// Report the schema version, size and integrity of the database.
+    /// 检查数据库的版本, 大小和完整性
+    Check {
+        /// Remove values that can not be read, they fall back to their defaults.
+        /// 删除无法读取的值, 恢复为默认值
+        #[clap(long)]
+        fix: bool,
+    },
+
+    /// Rewrite the database to reclaim the space of old values.
+    /// 重写数据库以回收空间
+    Compact,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Print the configuration as a versioned document, or write it to --output.
+    /// 导出配置为带版本号的文档
+    Export {
+        /// File to write instead of stdout.
+        /// 写入的文件, 默认输出到标准输出
+        #[clap(short, long)]
+        output: Option<String>,
+
+        /// Document format.
+        /// 文档格式
+        #[clap(long, value_enum, default_value = "yaml")]
+        format: ConfigFormat,
+
+        /// Leave the tokens out.
+        /// 不导出 Token
+        #[clap(long)]
+        redact: bool,
+    },
+
+    /// Validate a document written by `config export` and store it in the database, `-` reads stdin.
+    /// 校验并导入 `config export` 导出的文档
+    Import { file: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TokenAction {
+    /// List API tokens.
+    /// 列出 API Token
+    List,
+
+    /// Create an API token and print it.
+    /// 创建 API Token 并输出
+    Create {
+        /// Token name.
+        /// Token 名称
+        #[clap(long)]
+        name: String,
+
+        /// Comma separated scopes: metrics, process, exec, terminal, config, accounts.
+        /// 权限范围, 以逗号分隔
+        #[clap(long, value_delimiter = ',', required = true)]
+        scopes: Vec<Scope>,
+
+        /// Seconds until the token expires, never by default.
+        /// 有效期秒数, 默认永不过期
+        #[clap(long)]
+        ttl: Option<u64>,
+    },
+
+    /// Revoke an API token by id.
+    /// 撤销 API Token
+    Revoke { id: String },
 }
@@ -0,0 +1,161 @@
+use crate::traits::json_response::JsonResponder;
+use anyhow::{anyhow, Result};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+
+/// Routes that share one access rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessGroup {
+    /// The dashboard, `/ws`, `/check`, `/kill`, `/auth` and anything not listed below.
+    Metrics,
+    /// `/config`, `/users`, `/token` and `/audit`.
+    Config,
+    /// `/pty` and `/exec`.
+    Terminal,
+    /// `/local`.
+    Local,
+}
+
+impl AccessGroup {
+    pub fn of(path: &str) -> AccessGroup {
+        let under = |prefix: &str| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        if under("/local") {
+            AccessGroup::Local
+        } else if under("/pty") || under("/exec") {
+            AccessGroup::Terminal
+        } else if under("/config") || under("/users") || under("/token") || under("/audit") {
+            AccessGroup::Config
+        } else {
+            AccessGroup::Metrics
+        }
+    }
+}
+
+impl Display for AccessGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AccessGroup::Metrics => "metrics",
+            AccessGroup::Config => "config",
+            AccessGroup::Terminal => "terminal",
+            AccessGroup::Local => "local",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parse a CIDR such as `10.0.0.0/8`, a bare address is a single host.
+pub fn parse_cidr(value: &str) -> Result<IpNet> {
+    let value = value.trim();
+    value
+        .parse::<IpNet>()
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| anyhow!("invalid CIDR: {}", value))
+}
+
+fn matches(list: &[String], ip: IpAddr) -> bool {
+    list.iter()
+        .filter_map(|cidr| parse_cidr(cidr).ok())
+        .any(|net| net.contains(&ip))
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct AccessRule {
+    /// When not empty, only these networks are let in.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Networks that are always refused, even when allowed.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl AccessRule {
+    pub fn permits(&self, ip: IpAddr) -> bool {
+        !matches(&self.deny, ip) && (self.allow.is_empty() || matches(&self.allow, ip))
+    }
+
+    fn validate(&self) -> Result<()> {
+        for cidr in self.allow.iter().chain(self.deny.iter()) {
+            parse_cidr(cidr)?;
+        }
+        Ok(())
+    }
+}
+
+fn default_local_rule() -> AccessRule {
+    AccessRule {
+        allow: vec!["127.0.0.0/8".to_string(), "::1/128".to_string()],
+        deny: vec![],
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AccessConfig {
+    /// Proxies whose X-Forwarded-For header is believed.
+    #[serde(default)]
+    trusted_proxies: Vec<String>,
+    #[serde(default)]
+    metrics: AccessRule,
+    #[serde(default)]
+    config: AccessRule,
+    #[serde(default)]
+    terminal: AccessRule,
+    /// Checked against the socket address, proxied requests are never local.
+    #[serde(default = "default_local_rule")]
+    local: AccessRule,
+}
+
+impl AccessConfig {
+    /// Merge the other AccessConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: AccessConfig) -> bool {
+        let changed = *self != other;
+        *self = other;
+        changed
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for cidr in &self.trusted_proxies {
+            parse_cidr(cidr)?;
+        }
+        self.metrics.validate()?;
+        self.config.validate()?;
+        self.terminal.validate()?;
+        self.local.validate()
+    }
+
+    pub fn trusted_proxies(&self) -> Vec<String> {
+        self.trusted_proxies.clone()
+    }
+
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        matches(&self.trusted_proxies, ip)
+    }
+
+    pub fn rule(&self, group: AccessGroup) -> &AccessRule {
+        match group {
+            AccessGroup::Metrics => &self.metrics,
+            AccessGroup::Config => &self.config,
+            AccessGroup::Terminal => &self.terminal,
+            AccessGroup::Local => &self.local,
+        }
+    }
+}
+
+impl JsonResponder for AccessConfig {}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        AccessConfig {
+            trusted_proxies: vec![],
+            metrics: AccessRule::default(),
+            config: AccessRule::default(),
+            terminal: AccessRule::default(),
+            local: default_local_rule(),
+        }
+    }
+}
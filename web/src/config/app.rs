@@ -1,3 +1,4 @@
+use crate::db::db_wrapper::Sealed;
 use crate::traits::json_response::JsonResponder;
 use serde::{Deserialize, Serialize};
 
@@ -23,10 +24,12 @@ impl AppConfig {
     pub fn token(&self) -> Option<String> {
         self.token.clone()
     }
-
-    pub fn set_token(&mut self, token: Option<String>) {
-        self.token = token
-    }
 }
 
 impl JsonResponder for AppConfig {}
+
+impl Sealed for AppConfig {
+    fn secrets(&mut self) -> Vec<&mut Option<String>> {
+        vec![&mut self.token]
+    }
+}
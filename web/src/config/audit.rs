@@ -0,0 +1,50 @@
+use crate::config::constant::{DEFAULT_AUDIT_MAX_ENTRIES, DEFAULT_AUDIT_RETENTION_DAYS};
+use crate::traits::json_response::JsonResponder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AuditConfig {
+    /// Days audit entries are kept, 0 keeps them forever.
+    #[serde(default = "default_retention_days")]
+    retention_days: u64,
+    /// Entries kept at most, the oldest are removed first, 0 is unlimited.
+    #[serde(default = "default_max_entries")]
+    max_entries: usize,
+}
+
+fn default_retention_days() -> u64 {
+    DEFAULT_AUDIT_RETENTION_DAYS
+}
+
+fn default_max_entries() -> usize {
+    DEFAULT_AUDIT_MAX_ENTRIES
+}
+
+impl AuditConfig {
+    /// Merge the other AuditConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: AuditConfig) -> bool {
+        let changed = *self != other;
+        *self = other;
+        changed
+    }
+
+    pub fn retention_days(&self) -> u64 {
+        self.retention_days
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+}
+
+impl JsonResponder for AuditConfig {}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        AuditConfig {
+            retention_days: DEFAULT_AUDIT_RETENTION_DAYS,
+            max_entries: DEFAULT_AUDIT_MAX_ENTRIES,
+        }
+    }
+}
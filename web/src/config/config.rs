@@ -1,14 +1,33 @@
+use crate::audit::audit_log::AuditLog;
 use crate::cli::Args;
+use crate::config::access::AccessConfig;
 use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
 use crate::config::constant::{
-    APP_CONFIG, DEFAULT_PORT, LAST_LOGIN, LOG_DIR, SERVER_CONFIG, WEB_SERVER_CONFIG,
+    ACCESS_CONFIG, ACCOUNTS, API_TOKENS, AUDIT_CONFIG, DEFAULT_ACCOUNT, EXEC_CONFIG, LAST_LOGIN,
+    RATE_LIMIT_CONFIG, SERVER_CONFIG, TERMINAL_CONFIG, TLS_CONFIG, WEB_SERVER_CONFIG,
 };
+use crate::config::document::{ConfigDocument, ConfigSections, Credentials};
+use crate::config::exec::ExecConfig;
+use crate::config::layers::{
+    database_layer, get_path, set_path, ConfigLayers, Overlays, ValueSource, TOP_LEVEL_KEYS,
+};
+use crate::config::rate_limit::RateLimitConfig;
 use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
 use crate::config::web_server::WebServerConfig;
+use crate::db::cipher::{load_key, Cipher, KeySource};
 use crate::db::db_wrapper::DbWrapper;
+use crate::db::migration::migrate;
+use crate::limiter::lockout::Lockout;
+use crate::limiter::rate_limiter::RateLimiter;
+use crate::token::account::{put_account, Account, AccountStore, Role};
+use crate::token::api_token::ApiTokenStore;
+use crate::token::session::SessionStore;
 use crate::utils::common_util::get_now_timestamp;
-use anyhow::Result;
-use log::{info, LevelFilter};
+use anyhow::{anyhow, Result};
+use log::{info, warn, LevelFilter};
 use log4rs::append::console::ConsoleAppender;
 use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
 use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
@@ -16,67 +35,125 @@ use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
 use log4rs::append::rolling_file::RollingFileAppender;
 use log4rs::config::{Appender, Root};
 use log4rs::encode::pattern::PatternEncoder;
+use serde::Serialize;
+use serde_json::{to_value, Value};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::File;
 use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+/// Settings as served by `/config/effective`.
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub file: Option<PathBuf>,
+    pub settings: BTreeMap<String, (Value, ValueSource)>,
+}
+
+/// Handle to swap the log appenders, which reopens the log file.
+static LOG_HANDLE: OnceLock<log4rs::Handle> = OnceLock::new();
+
+/// The sections the running server keeps.
+#[derive(Clone, Debug)]
+struct Staged {
+    web_server: WebServerConfig,
+    server: ServerConfig,
+    terminal: TerminalConfig,
+    exec: ExecConfig,
+    tls: TlsConfig,
+    rate_limit: RateLimitConfig,
+    access: AccessConfig,
+    audit: AuditConfig,
+}
+
+/// An import checked against the running settings, see `Config::stage_import`.
+#[derive(Debug)]
+pub struct ConfigImport {
+    staged: Staged,
+    values: Vec<(&'static str, Option<Value>)>,
+    previous: Vec<(&'static str, Option<Value>)>,
+    revoke: Vec<String>,
+    /// The web server to move to first, when it changes.
+    pub rebind: Option<WebServerConfig>,
+}
 
 #[derive(Clone, Debug)]
 pub struct Config {
     db: DbWrapper,
+    accounts: AccountStore,
+    api_tokens: ApiTokenStore,
+    sessions: SessionStore,
+    lockout: Lockout,
+    rate_limiter: RateLimiter,
+    audit_log: AuditLog,
+    data_dir: PathBuf,
     log_dir: PathBuf,
     web_server: WebServerConfig,
     server: ServerConfig,
     app: AppConfig,
+    terminal: TerminalConfig,
+    exec: ExecConfig,
+    tls: TlsConfig,
+    rate_limit: RateLimitConfig,
+    access: AccessConfig,
+    audit: AuditConfig,
     last_login: u64,
+    layers: Arc<ConfigLayers>,
 }
 
 impl Config {
     pub fn new(args: Args) -> Self {
-        let db = DbWrapper::new(args.data_dir.map(|d| PathBuf::from(d)));
-
-        // merge web server config
-        let mut web_server = match db.get(WEB_SERVER_CONFIG) {
-            Ok(Some(v)) => v,
-            _ => WebServerConfig::new(DEFAULT_PORT),
+        let overlays = match Overlays::load(&args) {
+            Ok(overlays) => overlays,
+            Err(e) => {
+                eprintln!("Error reading config file {}", e);
+                std::process::exit(1);
+            }
         };
 
-        args.port.is_some().then(|| {
-            web_server.set_port(args.port.unwrap());
-            db.set(WEB_SERVER_CONFIG, &web_server);
-        });
+        // relative to the working directory like the db when not given
+        let data_dir = overlays.top_level("data_dir").map(PathBuf::from);
 
-        // merge server config
-        let mut server = match db.get::<ServerConfig>(SERVER_CONFIG) {
-            Ok(Some(v)) => v,
-            _ => Default::default(),
+        let (db, key_source) = match Config::open_database(&overlays) {
+            Ok(opened) => opened,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
         };
 
-        server
-            .merge(ServerConfig::new(
-                args.server_token,
-                args.server_host,
-                args.disable_ssl,
-            ))
-            .then(|| {
-                db.set(SERVER_CONFIG, &server);
-            });
-
-        // merge app config
-        let mut app = match db.get::<AppConfig>(APP_CONFIG) {
-            Ok(Some(v)) => v,
-            _ => Default::default(),
+        // defaults < database < config file < environment < command line
+        let database = match database_layer(&db) {
+            Ok(layer) => layer,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        let mut layers = ConfigLayers::new(database, overlays);
+
+        let sections = match ConfigSections::from_layers(&layers) {
+            Ok(sections) => sections,
+            Err(e) => {
+                eprintln!("Invalid configuration:");
+                for error in e.to_string().lines() {
+                    eprintln!("  {}", error);
+                }
+                std::process::exit(1);
+            }
         };
+        let app = sections.app.unwrap_or_default();
 
-        app.merge(AppConfig::new(args.app_token)).then(|| {
-            db.set(APP_CONFIG, &app);
-        });
+        // the app token is only an input now, it is kept hashed as the default admin account
+        let accounts = AccountStore::new(db.clone());
+        if let Some(token) = app.token() {
+            if let Err(e) = accounts.upsert(DEFAULT_ACCOUNT, Role::Admin, Some(token)) {
+                eprintln!("Error migrating app token: {}", e);
+            }
+        }
 
-        let log_dir = args
-            .log_dir
-            .unwrap_or_else(|| match db.get::<String>(LOG_DIR) {
-                Ok(Some(v)) => v,
-                _ => Config::current_dir().to_str().unwrap().to_string(),
-            });
+        let log_dir = layers
+            .top_level("log_dir")
+            .unwrap_or_else(|| Config::current_dir().to_str().unwrap().to_string());
 
         let last_login = match db.get::<u64>(LAST_LOGIN) {
             Ok(Some(v)) => v,
@@ -87,18 +164,306 @@ impl Config {
             }
         };
 
-        let config = Config {
+        let mut config = Config {
+            api_tokens: ApiTokenStore::new(db.clone()),
+            sessions: SessionStore::new(db.clone()),
+            lockout: Lockout::new(),
+            rate_limiter: RateLimiter::new(),
+            audit_log: AuditLog::new(db.clone()).expect("Failed to open audit log"),
             db,
+            accounts,
+            data_dir: data_dir.unwrap_or_default(),
             log_dir: PathBuf::from(log_dir),
-            web_server,
-            server,
-            app,
+            web_server: sections.web_server.unwrap_or_default(),
+            server: sections.server.unwrap_or_default(),
+            app: AppConfig::default(),
+            terminal: sections.terminal.unwrap_or_default(),
+            exec: sections.exec.unwrap_or_default(),
+            tls: sections.tls.unwrap_or_default(),
+            rate_limit: sections.rate_limit.unwrap_or_default(),
+            access: sections.access.unwrap_or_default(),
+            audit: sections.audit.unwrap_or_default(),
             last_login,
+            layers: Arc::new(layers.clone()),
         };
+        layers.settle(config.settings());
+        config.layers = Arc::new(layers);
         config.init_logging();
+        if let Some(file) = config.layers.file() {
+            info!("Read config file {}", file.display());
+        }
+        info!(
+            "Secrets in the database are encrypted with the key from {}",
+            key_source
+        );
         config
     }
 
+    /// Open the database in the data directory with the secret key of `overlays`.
+    pub fn open_database(overlays: &Overlays) -> Result<(DbWrapper, KeySource)> {
+        let data_dir = overlays.top_level("data_dir").map(PathBuf::from);
+        let secret_key_file = overlays.top_level("secret_key_file").map(PathBuf::from);
+        let (cipher, key_source) = load_key(
+            secret_key_file.as_deref(),
+            &data_dir.clone().unwrap_or_default(),
+        )
+        .and_then(|(key, source)| Ok((Cipher::new(&key)?, source)))
+        .map_err(|e| anyhow!("Error loading secret key: {}", e))?;
+        let db = DbWrapper::new(data_dir)?.with_cipher(cipher);
+        for migration in migrate(&db)? {
+            eprintln!(
+                "Migrated database to schema {}: {}",
+                migration.version, migration.description
+            );
+        }
+        Ok((db, key_source))
+    }
+
+    /// Current settings keyed like the config file, the top-level ones as they were at start.
+    fn settings(&self) -> Value {
+        let mut settings = self.layers.value();
+        let sections = [
+            ("web_server", to_value(&self.web_server)),
+            ("server", to_value(&self.server)),
+            ("terminal", to_value(&self.terminal)),
+            ("exec", to_value(&self.exec)),
+            ("tls", to_value(&self.tls)),
+            ("rate_limit", to_value(&self.rate_limit)),
+            ("access", to_value(&self.access)),
+            ("audit", to_value(&self.audit)),
+        ];
+        for (section, value) in sections {
+            set_path(&mut settings, section, value.unwrap_or_default());
+        }
+        settings
+    }
+
+    /// Every setting with the layer it came from, tokens masked.
+    pub fn effective_config(&self) -> EffectiveConfig {
+        EffectiveConfig {
+            file: self.layers.file(),
+            settings: self.layers.effective(&self.settings()),
+        }
+    }
+
+    fn sections(&self) -> ConfigSections {
+        ConfigSections {
+            web_server: Some(self.web_server_config()),
+            server: Some(self.server_config()),
+            app: Some(self.app_config()),
+            terminal: Some(self.terminal_config()),
+            exec: Some(self.exec_config()),
+            tls: Some(self.tls_config()),
+            rate_limit: Some(self.rate_limit_config()),
+            access: Some(self.access_config()),
+            audit: Some(self.audit_config()),
+        }
+    }
+
+    pub fn export_config(&self, redact: bool) -> ConfigDocument {
+        ConfigDocument::new(&self.sections(), Credentials::read(&self.db), redact)
+    }
+
+    /// Merge imported sections and credentials into the running settings and check the
+    /// result, nothing is stored yet. The values to store are kept with the ones they replace.
+    pub fn stage_import(
+        &self,
+        sections: ConfigSections,
+        credentials: Option<Credentials>,
+    ) -> Result<ConfigImport> {
+        fn put<T: Serialize + PartialEq>(
+            values: &mut Vec<(&'static str, Option<Value>)>,
+            key: &'static str,
+            current: &T,
+            merged: &T,
+        ) -> Result<()> {
+            if current != merged {
+                values.push((key, Some(to_value(merged)?)));
+            }
+            Ok(())
+        }
+        fn merged<T: Clone>(current: &T, section: Option<T>, merge: fn(&mut T, T) -> bool) -> T {
+            let mut merged = current.clone();
+            if let Some(section) = section {
+                merge(&mut merged, section);
+            }
+            merged
+        }
+
+        let staged = Staged {
+            web_server: merged(
+                &self.web_server,
+                sections.web_server,
+                WebServerConfig::merge,
+            ),
+            server: merged(&self.server, sections.server, ServerConfig::merge),
+            terminal: merged(&self.terminal, sections.terminal, TerminalConfig::merge),
+            exec: merged(&self.exec, sections.exec, ExecConfig::merge),
+            tls: merged(&self.tls, sections.tls, TlsConfig::merge),
+            rate_limit: merged(
+                &self.rate_limit,
+                sections.rate_limit,
+                RateLimitConfig::merge,
+            ),
+            access: merged(&self.access, sections.access, AccessConfig::merge),
+            audit: merged(&self.audit, sections.audit, AuditConfig::merge),
+        };
+        staged
+            .web_server
+            .validate()
+            .map_err(|e| anyhow!("web_server: {}", e))?;
+        staged.exec.validate().map_err(|e| anyhow!("exec: {}", e))?;
+        staged.tls.validate().map_err(|e| anyhow!("tls: {}", e))?;
+        staged
+            .access
+            .validate()
+            .map_err(|e| anyhow!("access: {}", e))?;
+
+        let mut values = vec![];
+        put(
+            &mut values,
+            WEB_SERVER_CONFIG,
+            &self.web_server,
+            &staged.web_server,
+        )?;
+        if self.server != staged.server {
+            values.push((SERVER_CONFIG, Some(to_value(self.db.seal(&staged.server))?)));
+        }
+        put(
+            &mut values,
+            TERMINAL_CONFIG,
+            &self.terminal,
+            &staged.terminal,
+        )?;
+        put(&mut values, EXEC_CONFIG, &self.exec, &staged.exec)?;
+        put(&mut values, TLS_CONFIG, &self.tls, &staged.tls)?;
+        put(
+            &mut values,
+            RATE_LIMIT_CONFIG,
+            &self.rate_limit,
+            &staged.rate_limit,
+        )?;
+        put(&mut values, ACCESS_CONFIG, &self.access, &staged.access)?;
+        put(&mut values, AUDIT_CONFIG, &self.audit, &staged.audit)?;
+
+        // sessions of the accounts replaced here are ended once it is applied
+        let mut revoke = vec![];
+        let mut accounts = self.accounts.list();
+        let before = accounts.clone();
+        if let Some(credentials) = &credentials {
+            let mut api_tokens = self.api_tokens.list();
+            let before = api_tokens.clone();
+            credentials.merge(&mut accounts, &mut api_tokens)?;
+            put(&mut values, API_TOKENS, &before, &api_tokens)?;
+            revoke.extend(credentials.accounts.iter().map(|a| a.name.clone()));
+        }
+        if let Some(token) = sections
+            .app
+            .and_then(|app| app.token())
+            .filter(|token| !token.is_empty())
+        {
+            put_account(&mut accounts, Account::admin(&token))?;
+            revoke.push(DEFAULT_ACCOUNT.to_string());
+        }
+        put(&mut values, ACCOUNTS, &before, &accounts)?;
+
+        let previous = values
+            .iter()
+            .map(|(key, _)| Ok((*key, self.db.get::<Value>(key)?)))
+            .collect::<Result<_>>()?;
+        let rebind = (staged.web_server != self.web_server).then(|| staged.web_server.clone());
+        Ok(ConfigImport {
+            staged,
+            values,
+            previous,
+            revoke,
+            rebind,
+        })
+    }
+
+    /// Store a staged import in a single write.
+    pub fn store_import(&self, import: &ConfigImport) -> Result<()> {
+        self.db.set_batch(&import.values)
+    }
+
+    /// Put back what a stored import replaced, when it can't be applied after all.
+    pub fn undo_import(&self, import: &ConfigImport) -> Result<()> {
+        self.db.set_batch(&import.previous)
+    }
+
+    /// Run on a stored import, the web server must already run on the imported one.
+    pub fn apply_import(&mut self, import: ConfigImport) {
+        for account in &import.revoke {
+            self.sessions.revoke_account(account);
+        }
+        let staged = import.staged;
+        self.web_server = staged.web_server;
+        self.server = staged.server;
+        self.terminal = staged.terminal;
+        self.exec = staged.exec;
+        self.tls = staged.tls;
+        self.rate_limit = staged.rate_limit;
+        self.access = staged.access;
+        self.audit = staged.audit;
+        info!("Imported configuration");
+    }
+
+    /// Read the config file and environment again over the database.
+    /// Fails without changing anything when they are invalid.
+    pub fn reload_layers(&self, args: &Args) -> Result<(ConfigLayers, ConfigSections)> {
+        let overlays = Overlays::load(args)?;
+        let layers = ConfigLayers::new(database_layer(&self.db)?, overlays);
+        let sections = ConfigSections::from_layers(&layers)?;
+        for key in TOP_LEVEL_KEYS {
+            if layers.top_level(key) != self.layers.top_level(key) {
+                warn!("{} changed, restart serverbee-web to apply it", key);
+            }
+        }
+        Ok((layers, sections))
+    }
+
+    /// Run on reloaded settings, the web server must already run on the reloaded one.
+    /// They are not stored, like the config file and environment at start.
+    pub fn apply_layers(
+        &mut self,
+        mut layers: ConfigLayers,
+        sections: ConfigSections,
+    ) -> Result<()> {
+        if let Some(token) = sections.app.and_then(|app| app.token()) {
+            self.accounts
+                .upsert(DEFAULT_ACCOUNT, Role::Admin, Some(token))?;
+        }
+        // the top-level settings stay as they were at start
+        let mut value = layers.value();
+        let current = self.layers.value();
+        for key in TOP_LEVEL_KEYS {
+            set_path(
+                &mut value,
+                key,
+                get_path(&current, key).cloned().unwrap_or(Value::Null),
+            );
+        }
+        layers.settle(value);
+        self.layers = Arc::new(layers.clone());
+        self.web_server = sections.web_server.unwrap_or_default();
+        self.server = sections.server.unwrap_or_default();
+        self.terminal = sections.terminal.unwrap_or_default();
+        self.exec = sections.exec.unwrap_or_default();
+        self.tls = sections.tls.unwrap_or_default();
+        self.rate_limit = sections.rate_limit.unwrap_or_default();
+        self.access = sections.access.unwrap_or_default();
+        self.audit = sections.audit.unwrap_or_default();
+        layers.settle(self.settings());
+        self.layers = Arc::new(layers);
+        info!("Reloaded configuration");
+        Ok(())
+    }
+
+    /// Write everything pending in the database to disk.
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()
+    }
+
     pub fn web_server_config(&self) -> WebServerConfig {
         self.web_server.clone()
     }
@@ -111,12 +476,56 @@ impl Config {
         self.app.clone()
     }
 
+    pub fn terminal_config(&self) -> TerminalConfig {
+        self.terminal.clone()
+    }
+
+    pub fn exec_config(&self) -> ExecConfig {
+        self.exec.clone()
+    }
+
+    pub fn tls_config(&self) -> TlsConfig {
+        self.tls.clone()
+    }
+
+    pub fn rate_limit_config(&self) -> RateLimitConfig {
+        self.rate_limit.clone()
+    }
+
+    pub fn audit_log(&self) -> AuditLog {
+        self.audit_log.clone()
+    }
+
+    pub fn audit_config(&self) -> AuditConfig {
+        self.audit.clone()
+    }
+
+    pub fn access_config(&self) -> AccessConfig {
+        self.access.clone()
+    }
+
     pub fn server_port(&self) -> u16 {
         self.web_server.port()
     }
 
-    pub fn app_token(&self) -> Option<String> {
-        self.app.token()
+    pub fn accounts(&self) -> AccountStore {
+        self.accounts.clone()
+    }
+
+    pub fn api_tokens(&self) -> ApiTokenStore {
+        self.api_tokens.clone()
+    }
+
+    pub fn sessions(&self) -> SessionStore {
+        self.sessions.clone()
+    }
+
+    pub fn lockout(&self) -> Lockout {
+        self.lockout.clone()
+    }
+
+    pub fn rate_limiter(&self) -> RateLimiter {
+        self.rate_limiter.clone()
     }
 
     pub fn server_token(&self) -> Option<String> {
@@ -132,9 +541,22 @@ impl Config {
     }
 
     fn init_logging(&self) {
+        info!("日志文件路径: {:?}", self.log_file());
+        let handle = log4rs::init_config(self.log_config()).unwrap();
+        let _ = LOG_HANDLE.set(handle);
+    }
+
+    /// Open the log file again, e.g. after logrotate moved it away.
+    pub fn reopen_logs(&self) {
+        if let Some(handle) = LOG_HANDLE.get() {
+            handle.set_config(self.log_config());
+            info!("Reopened log file {:?}", self.log_file());
+        }
+    }
+
+    fn log_config(&self) -> log4rs::config::Config {
         let log_dir = self.log_dir();
         let log_file = self.log_file();
-        info!("日志文件路径: {:?}", log_file);
 
         // init logging
         let stdout: ConsoleAppender = ConsoleAppender::builder()
@@ -160,7 +582,7 @@ impl Config {
             .build(log_file, Box::new(compound_policy))
             .unwrap();
 
-        let log_config = log4rs::config::Config::builder()
+        log4rs::config::Config::builder()
             .appender(Appender::builder().build("stdout", Box::new(stdout)))
             .appender(Appender::builder().build("logfile", Box::new(rolling_logfile)))
             .build(
@@ -169,37 +591,24 @@ impl Config {
                     .appender("logfile")
                     .build(LevelFilter::Info),
             )
-            .unwrap();
-
-        log4rs::init_config(log_config).unwrap();
+            .unwrap()
     }
 
-    fn get_config_yml() -> Result<WebServerConfig> {
-        let mut config_path = PathBuf::from("config.yml");
+    fn current_dir() -> PathBuf {
         if let Ok(current_exe) = env::current_exe() {
             if let Some(parent) = current_exe.parent() {
-                config_path = parent.join("config.yml");
+                return parent.to_path_buf();
             }
         }
-
-        let config_file = File::open(config_path)?;
-        Ok(serde_yaml::from_reader::<File, WebServerConfig>(
-            config_file,
-        )?)
+        env::current_dir().expect("获取当前目录失败, 权限不足或当前目录不存在")
     }
 
-    pub fn get_server_port() -> u16 {
-        let d = Config::get_config_yml().unwrap_or_default();
-        d.port()
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone()
     }
 
-    fn current_dir() -> PathBuf {
-        if let Ok(current_exe) = env::current_exe() {
-            if let Some(parent) = current_exe.parent() {
-                return parent.to_path_buf();
-            }
-        }
-        env::current_dir().expect("获取当前目录失败, 权限不足或当前目录不存在")
+    pub fn recording_dir(&self) -> PathBuf {
+        self.data_dir().join("recordings")
     }
 
     pub fn log_dir(&self) -> PathBuf {
@@ -219,34 +628,96 @@ impl Config {
         Ok(())
     }
 
+    /// Set or remove the token of the default admin account.
     pub fn set_app_token(&mut self, token: Option<String>) -> Result<()> {
-        self.app.set_token(token);
-        self.db.set::<AppConfig>(APP_CONFIG, &self.app);
+        match token {
+            Some(token) => {
+                self.accounts
+                    .upsert(DEFAULT_ACCOUNT, Role::Admin, Some(token))?;
+            }
+            None => {
+                warn!("Default account {} removed", DEFAULT_ACCOUNT);
+                self.accounts.remove(DEFAULT_ACCOUNT);
+            }
+        }
+        self.sessions.revoke_account(DEFAULT_ACCOUNT);
         Ok(())
     }
 
     pub fn set_app_config(&mut self, config: AppConfig) -> Result<()> {
-        self.app.merge(config).then(|| {
-            self.db.set::<AppConfig>(APP_CONFIG, &self.app);
-        });
-        Ok(())
+        match config.token() {
+            Some(token) => self.set_app_token(Some(token)),
+            None => Ok(()),
+        }
     }
 
     pub fn set_server_token(&mut self, token: &str) -> Result<()> {
         self.server.set_token(Some(token.to_string()));
-        self.db.set::<ServerConfig>(SERVER_CONFIG, &self.server);
+        self.db.set_sealed(SERVER_CONFIG, &self.server);
         Ok(())
     }
 
     pub fn set_server_host(&mut self, host: &str) -> Result<()> {
         self.server.set_host(Some(host.to_string()));
-        self.db.set::<ServerConfig>(SERVER_CONFIG, &self.server);
+        self.db.set_sealed(SERVER_CONFIG, &self.server);
         Ok(())
     }
 
     pub fn set_server_config(&mut self, config: ServerConfig) -> Result<()> {
         self.server.merge(config).then(|| {
-            self.db.set::<ServerConfig>(SERVER_CONFIG, &self.server);
+            self.db.set_sealed(SERVER_CONFIG, &self.server);
+        });
+        Ok(())
+    }
+
+    pub fn set_terminal_config(&mut self, config: TerminalConfig) -> Result<()> {
+        info!("Terminal config change: {:?}", config);
+        self.terminal.merge(config).then(|| {
+            self.db
+                .set::<TerminalConfig>(TERMINAL_CONFIG, &self.terminal);
+        });
+        Ok(())
+    }
+
+    pub fn set_exec_config(&mut self, config: ExecConfig) -> Result<()> {
+        config.validate()?;
+        info!("Exec config change: {:?}", config);
+        self.exec.merge(config).then(|| {
+            self.db.set::<ExecConfig>(EXEC_CONFIG, &self.exec);
+        });
+        Ok(())
+    }
+
+    pub fn set_tls_config(&mut self, config: TlsConfig) -> Result<()> {
+        info!("TLS config change, applied after a restart: {:?}", config);
+        self.tls.merge(config).then(|| {
+            self.db.set::<TlsConfig>(TLS_CONFIG, &self.tls);
+        });
+        Ok(())
+    }
+
+    pub fn set_rate_limit_config(&mut self, config: RateLimitConfig) -> Result<()> {
+        info!("Rate limit config change: {:?}", config);
+        self.rate_limit.merge(config).then(|| {
+            self.db
+                .set::<RateLimitConfig>(RATE_LIMIT_CONFIG, &self.rate_limit);
+        });
+        Ok(())
+    }
+
+    pub fn set_access_config(&mut self, config: AccessConfig) -> Result<()> {
+        config.validate()?;
+        info!("Access config change: {:?}", config);
+        self.access.merge(config).then(|| {
+            self.db.set::<AccessConfig>(ACCESS_CONFIG, &self.access);
+        });
+        Ok(())
+    }
+
+    pub fn set_audit_config(&mut self, config: AuditConfig) -> Result<()> {
+        info!("Audit config change: {:?}", config);
+        self.audit.merge(config).then(|| {
+            self.db.set::<AuditConfig>(AUDIT_CONFIG, &self.audit);
         });
         Ok(())
     }
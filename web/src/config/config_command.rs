@@ -0,0 +1,55 @@
+use crate::cli::{Args, ConfigAction};
+use crate::config::config::Config;
+use crate::config::document::{ConfigDocument, ConfigSections, Credentials};
+use crate::config::layers::{database_layer, ConfigLayers, Overlays};
+use anyhow::{anyhow, Result};
+use std::fs::{read_to_string, OpenOptions};
+use std::io::{Read, Write};
+
+/// `serverbee-web config ...`, works on the db directly so the server must be stopped.
+pub fn run_config_command(args: &Args, action: ConfigAction) -> Result<()> {
+    let overlays = Overlays::load(args)?;
+    let (db, _) = Config::open_database(&overlays)?;
+    match action {
+        ConfigAction::Export {
+            output,
+            format,
+            redact,
+        } => {
+            let layers = ConfigLayers::new(database_layer(&db)?, overlays);
+            let sections = ConfigSections::from_layers(&layers)?;
+            let content =
+                ConfigDocument::new(&sections, Credentials::read(&db), redact).format(format)?;
+            match output {
+                Some(path) => {
+                    // holds the tokens unless redacted
+                    let mut options = OpenOptions::new();
+                    options.write(true).create(true).truncate(true);
+                    #[cfg(unix)]
+                    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                    options.open(&path)?.write_all(content.as_bytes())?;
+                    eprintln!("Exported config to {}", path);
+                }
+                None => print!("{}", content),
+            }
+        }
+        ConfigAction::Import { file } => {
+            let content = match file.as_str() {
+                "-" => {
+                    let mut content = String::new();
+                    std::io::stdin().read_to_string(&mut content)?;
+                    content
+                }
+                _ => read_to_string(&file).map_err(|e| anyhow!("{}: {}", file, e))?,
+            };
+            let document = ConfigDocument::parse(&content)?;
+            let sections = document.sections()?;
+            sections.store(&db, document.credentials.as_ref())?;
+            eprintln!(
+                "Imported {} into the database, restart serverbee-web to apply",
+                sections.names().join(", ")
+            );
+        }
+    }
+    Ok(())
+}
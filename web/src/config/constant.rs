@@ -2,14 +2,95 @@ pub(crate) const LOG_DIR: &str = "log_dir";
 
 pub(crate) const LAST_LOGIN: &str = "last_login";
 
+/// Version of the layout of the keys below, see `db::migration`
+pub(crate) const SCHEMA_VERSION: &str = "schema_version";
+
 pub(crate) const WEB_SERVER_CONFIG: &str = "web_server_config";
 
 pub(crate) const APP_CONFIG: &str = "app_config";
 
 pub(crate) const SERVER_CONFIG: &str = "server_config";
 
+pub(crate) const TERMINAL_CONFIG: &str = "terminal_config";
+
+pub(crate) const EXEC_CONFIG: &str = "exec_config";
+
+pub(crate) const TLS_CONFIG: &str = "tls_config";
+
+pub(crate) const RATE_LIMIT_CONFIG: &str = "rate_limit_config";
+
+pub(crate) const ACCESS_CONFIG: &str = "access_config";
+
+pub(crate) const AUDIT_CONFIG: &str = "audit_config";
+/// sled tree the audit trail is appended to
+pub(crate) const AUDIT_TREE: &str = "audit";
+
+pub(crate) const ACCOUNTS: &str = "accounts";
+
+pub(crate) const API_TOKENS: &str = "api_tokens";
+
+pub(crate) const AUTH_SESSIONS: &str = "auth_sessions";
+
+pub(crate) const SESSION_SECRET: &str = "session_secret";
+
+/// Account the legacy app token is migrated to
+pub(crate) const DEFAULT_ACCOUNT: &str = "admin";
+
 pub(crate) const PORT: &str = "port";
 pub(crate) const APP_TOKEN: &str = "app_token";
 pub(crate) const SERVER_TOKEN: &str = "server_token";
 pub(crate) const SERVER_HOST: &str = "server_host";
 pub(crate) const DEFAULT_PORT: u16 = 9527;
+/// Worker threads of the HTTP server
+pub(crate) const DEFAULT_WORKERS: usize = 2;
+pub(crate) const MAX_WORKERS: usize = 256;
+/// Largest JSON request body in bytes
+pub(crate) const DEFAULT_JSON_LIMIT: usize = 4096;
+/// Seconds the server has to stop gracefully before it is stopped anyway
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 10;
+pub(crate) const MAX_SHUTDOWN_TIMEOUT: u64 = 300;
+pub(crate) const DEFAULT_SESSION_IDLE_TIMEOUT: u64 = 300;
+pub(crate) const DEFAULT_SCROLLBACK_SIZE: usize = 64 * 1024;
+pub(crate) const DEFAULT_RECORDING_RETENTION_DAYS: u64 = 30;
+pub(crate) const DEFAULT_RECORDING_MAX_FILES: usize = 100;
+pub(crate) const DEFAULT_EXEC_TIMEOUT: u64 = 60;
+pub(crate) const MIN_EXEC_TIMEOUT: u64 = 1;
+pub(crate) const MAX_EXEC_TIMEOUT: u64 = 24 * 60 * 60;
+pub(crate) const DEFAULT_EXEC_MAX_OUTPUT: usize = 1024 * 1024;
+/// Seconds between writes of an API token's last used time
+pub(crate) const API_TOKEN_TOUCH_INTERVAL: u64 = 60;
+/// Seconds a login access token is valid
+pub(crate) const SESSION_ACCESS_TTL: u64 = 15 * 60;
+/// Seconds a login refresh token is valid
+pub(crate) const SESSION_REFRESH_TTL: u64 = 7 * 24 * 60 * 60;
+/// Seconds a WebSocket ticket can be redeemed
+pub(crate) const WS_TICKET_TTL: u64 = 30;
+/// Seconds between checks of the TLS certificate files for renewals
+pub(crate) const DEFAULT_TLS_RELOAD_INTERVAL: u64 = 60;
+pub(crate) const DEFAULT_MAX_AUTH_FAILURES: u32 = 5;
+/// Seconds failed authentications are counted over
+pub(crate) const DEFAULT_FAILURE_WINDOW: u64 = 5 * 60;
+/// Seconds of a first ban, later bans double it
+pub(crate) const DEFAULT_BAN_DURATION: u64 = 60;
+pub(crate) const DEFAULT_MAX_BAN_DURATION: u64 = 24 * 60 * 60;
+pub(crate) const DEFAULT_HEAVY_REQUESTS_PER_MINUTE: u32 = 30;
+pub(crate) const DEFAULT_AUDIT_RETENTION_DAYS: u64 = 90;
+pub(crate) const DEFAULT_AUDIT_MAX_ENTRIES: usize = 100_000;
+/// Seconds between removals of audit entries past retention
+pub(crate) const AUDIT_PRUNE_INTERVAL: u64 = 60 * 60;
+/// Entries returned by `/audit` when no limit is given
+pub(crate) const DEFAULT_AUDIT_QUERY_LIMIT: usize = 100;
+pub(crate) const MAX_AUDIT_QUERY_LIMIT: usize = 1000;
+/// Base64 secret key that encrypts tokens in the db, overrides the key file
+pub(crate) const SECRET_KEY_ENV: &str = "SERVERBEE_SECRET_KEY";
+pub(crate) const SECRET_KEY_FILE: &str = "secret.key";
+#[cfg(feature = "keyring")]
+pub(crate) const SECRET_KEY_KEYRING_SERVICE: &str = "serverbee";
+#[cfg(feature = "keyring")]
+pub(crate) const SECRET_KEY_KEYRING_USER: &str = "secret-key";
+/// Config file read when `--config` isn't given
+pub(crate) const CONFIG_FILE_ENV: &str = "SERVERBEE_CONFIG";
+/// Prefix of the environment variables overriding config values, e.g. `SERVERBEE_WEB_SERVER_PORT`
+pub(crate) const CONFIG_ENV_PREFIX: &str = "SERVERBEE_";
+/// Version of the documents written by `config export`
+pub(crate) const CONFIG_DOCUMENT_VERSION: u32 = 1;
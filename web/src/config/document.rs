@@ -0,0 +1,351 @@
+use crate::config::access::AccessConfig;
+use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
+use crate::config::constant::{
+    ACCESS_CONFIG, ACCOUNTS, API_TOKENS, AUDIT_CONFIG, CONFIG_DOCUMENT_VERSION, EXEC_CONFIG,
+    RATE_LIMIT_CONFIG, SERVER_CONFIG, TERMINAL_CONFIG, TLS_CONFIG, WEB_SERVER_CONFIG,
+};
+use crate::config::exec::ExecConfig;
+use crate::config::layers::{
+    get_path, set_path, unknown_settings, ConfigLayers, SECRET_PATHS, TOP_LEVEL_KEYS,
+};
+use crate::config::rate_limit::RateLimitConfig;
+use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
+use crate::config::web_server::WebServerConfig;
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{put_account, Account, AccountStore};
+use crate::token::api_token::{ApiToken, ApiTokenStore};
+use crate::utils::common_util::get_now_timestamp;
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Format of an exported config document.
+#[derive(clap::ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "application/yaml",
+            ConfigFormat::Json => "application/json",
+        }
+    }
+}
+
+/// The configuration sections, those left out of an import stay as they are.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSections {
+    pub web_server: Option<WebServerConfig>,
+    pub server: Option<ServerConfig>,
+    pub app: Option<AppConfig>,
+    pub terminal: Option<TerminalConfig>,
+    pub exec: Option<ExecConfig>,
+    pub tls: Option<TlsConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    pub access: Option<AccessConfig>,
+    pub audit: Option<AuditConfig>,
+}
+
+fn section<T: DeserializeOwned>(config: &Value, name: &str, errors: &mut Vec<String>) -> Option<T> {
+    let value = config.get(name)?;
+    match serde_json::from_value(value.clone()) {
+        Ok(section) => Some(section),
+        Err(e) => {
+            errors.push(format!("{}: {}", name, e));
+            None
+        }
+    }
+}
+
+impl ConfigSections {
+    /// Sections that fail their checks, with the error.
+    fn invalid(&self) -> Vec<(&'static str, anyhow::Error)> {
+        let checks = [
+            ("web_server", self.web_server.as_ref().map(|c| c.validate())),
+            ("exec", self.exec.as_ref().map(|c| c.validate())),
+            ("tls", self.tls.as_ref().map(|c| c.validate())),
+            ("access", self.access.as_ref().map(|c| c.validate())),
+        ];
+        checks
+            .into_iter()
+            .filter_map(|(name, result)| match result {
+                Some(Err(e)) => Some((name, e)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every section as the layers merged them, an invalid one reported with the layer
+    /// that set it, one per line.
+    pub fn from_layers(layers: &ConfigLayers) -> Result<ConfigSections> {
+        let mut errors = vec![];
+        let sections = ConfigSections {
+            web_server: Some(layers.section("web_server", &mut errors)),
+            server: Some(layers.section("server", &mut errors)),
+            app: Some(layers.section("app", &mut errors)),
+            terminal: Some(layers.section("terminal", &mut errors)),
+            exec: Some(layers.section("exec", &mut errors)),
+            tls: Some(layers.section("tls", &mut errors)),
+            rate_limit: Some(layers.section("rate_limit", &mut errors)),
+            access: Some(layers.section("access", &mut errors)),
+            audit: Some(layers.section("audit", &mut errors)),
+        };
+        for (name, e) in sections.invalid() {
+            errors.push(format!(
+                "{}: {} (set by {})",
+                name,
+                e,
+                layers.describe(name)
+            ));
+        }
+        match errors.is_empty() {
+            true => Ok(sections),
+            false => Err(anyhow!(errors.join("\n"))),
+        }
+    }
+
+    /// Read the sections of `config`, reporting every unknown setting and invalid section.
+    pub fn from_value(config: &Value) -> Result<ConfigSections> {
+        if !config.is_object() {
+            return Err(anyhow!("config must be a mapping of sections"));
+        }
+        let mut errors = vec![];
+        for key in TOP_LEVEL_KEYS {
+            if config.get(key).is_some() {
+                errors.push(format!(
+                    "{}: can only be set by the config file, environment or command line",
+                    key
+                ));
+            }
+        }
+        let unknown = unknown_settings(config);
+        if !unknown.is_empty() {
+            errors.push(format!("unknown settings: {}", unknown.join(", ")));
+        }
+        let sections = ConfigSections {
+            web_server: section(config, "web_server", &mut errors),
+            server: section(config, "server", &mut errors),
+            app: section(config, "app", &mut errors),
+            terminal: section(config, "terminal", &mut errors),
+            exec: section(config, "exec", &mut errors),
+            tls: section(config, "tls", &mut errors),
+            rate_limit: section(config, "rate_limit", &mut errors),
+            access: section(config, "access", &mut errors),
+            audit: section(config, "audit", &mut errors),
+        };
+        for (name, e) in sections.invalid() {
+            errors.push(format!("{}: {}", name, e));
+        }
+        match errors.is_empty() {
+            true => Ok(sections),
+            false => Err(anyhow!(errors.join("\n"))),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        fn insert<T: Serialize>(map: &mut Map<String, Value>, name: &str, section: &Option<T>) {
+            if let Some(section) = section {
+                map.insert(
+                    name.to_string(),
+                    serde_json::to_value(section).expect("config serializes to JSON"),
+                );
+            }
+        }
+        let mut map = Map::new();
+        insert(&mut map, "web_server", &self.web_server);
+        insert(&mut map, "server", &self.server);
+        insert(&mut map, "app", &self.app);
+        insert(&mut map, "terminal", &self.terminal);
+        insert(&mut map, "exec", &self.exec);
+        insert(&mut map, "tls", &self.tls);
+        insert(&mut map, "rate_limit", &self.rate_limit);
+        insert(&mut map, "access", &self.access);
+        insert(&mut map, "audit", &self.audit);
+        Value::Object(map)
+    }
+
+    /// Names of the sections present.
+    pub fn names(&self) -> Vec<String> {
+        match self.to_value() {
+            Value::Object(map) => map.keys().cloned().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Write the sections and credentials to the database of a stopped server, in one go.
+    /// The server token is kept when left out, an app token becomes the admin account.
+    pub fn store(&self, db: &DbWrapper, credentials: Option<&Credentials>) -> Result<()> {
+        fn put<T: Serialize>(
+            values: &mut Vec<(&'static str, Option<Value>)>,
+            key: &'static str,
+            section: &Option<T>,
+        ) -> Result<()> {
+            if let Some(section) = section {
+                values.push((key, Some(serde_json::to_value(section)?)));
+            }
+            Ok(())
+        }
+        let mut values = vec![];
+        put(&mut values, WEB_SERVER_CONFIG, &self.web_server)?;
+        if let Some(server) = &self.server {
+            let mut stored = db
+                .get_sealed::<ServerConfig>(SERVER_CONFIG)?
+                .unwrap_or_default();
+            stored.merge(server.clone());
+            put(&mut values, SERVER_CONFIG, &Some(db.seal(&stored)))?;
+        }
+        put(&mut values, TERMINAL_CONFIG, &self.terminal)?;
+        put(&mut values, EXEC_CONFIG, &self.exec)?;
+        put(&mut values, TLS_CONFIG, &self.tls)?;
+        put(&mut values, RATE_LIMIT_CONFIG, &self.rate_limit)?;
+        put(&mut values, ACCESS_CONFIG, &self.access)?;
+        put(&mut values, AUDIT_CONFIG, &self.audit)?;
+
+        let mut accounts = AccountStore::new(db.clone()).list();
+        let mut api_tokens = ApiTokenStore::new(db.clone()).list();
+        let token = self
+            .app
+            .as_ref()
+            .and_then(|app| app.token())
+            .filter(|token| !token.is_empty());
+        if let Some(credentials) = credentials {
+            credentials.merge(&mut accounts, &mut api_tokens)?;
+            put(&mut values, API_TOKENS, &Some(api_tokens))?;
+        }
+        if let Some(token) = &token {
+            put_account(&mut accounts, Account::admin(token))?;
+        }
+        if credentials.is_some() || token.is_some() {
+            put(&mut values, ACCOUNTS, &Some(accounts))?;
+        }
+        db.set_batch(&values)
+    }
+}
+
+fn is_token_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Accounts and API tokens. Only the SHA-256 of their tokens is kept,
+/// which keeps working on another host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Credentials {
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    #[serde(default)]
+    pub api_tokens: Vec<ApiToken>,
+}
+
+impl Credentials {
+    pub fn read(db: &DbWrapper) -> Credentials {
+        Credentials {
+            accounts: AccountStore::new(db.clone()).list(),
+            api_tokens: ApiTokenStore::new(db.clone()).list(),
+        }
+    }
+
+    /// Put these in place of the accounts with the same name and the API tokens with
+    /// the same id, the others stay.
+    pub fn merge(&self, accounts: &mut Vec<Account>, api_tokens: &mut Vec<ApiToken>) -> Result<()> {
+        for account in &self.accounts {
+            if !is_token_hash(&account.token_hash) {
+                return Err(anyhow!("account {}: malformed token_hash", account.name));
+            }
+            put_account(accounts, account.clone())
+                .map_err(|e| anyhow!("account {}: {}", account.name, e))?;
+        }
+        for token in &self.api_tokens {
+            if token.id.is_empty() || !is_token_hash(&token.token_hash) {
+                return Err(anyhow!(
+                    "api token {}: malformed id or token_hash",
+                    token.id
+                ));
+            }
+            match api_tokens.iter_mut().find(|t| t.id == token.id) {
+                Some(existing) => *existing = token.clone(),
+                None => api_tokens.push(token.clone()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A versioned dump of the configuration, for moving it to another host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfigDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: u64,
+    /// Tokens were left out, importing keeps the ones in place.
+    #[serde(default)]
+    pub redacted: bool,
+    pub config: Value,
+    /// Left out when redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<Credentials>,
+}
+
+impl ConfigDocument {
+    pub fn new(
+        sections: &ConfigSections,
+        credentials: Credentials,
+        redact: bool,
+    ) -> ConfigDocument {
+        let mut config = sections.to_value();
+        if redact {
+            for path in SECRET_PATHS {
+                if get_path(&config, path).is_some() {
+                    set_path(&mut config, path, Value::Null);
+                }
+            }
+        }
+        ConfigDocument {
+            version: CONFIG_DOCUMENT_VERSION,
+            exported_at: get_now_timestamp(),
+            redacted: redact,
+            config,
+            credentials: (!redact).then_some(credentials),
+        }
+    }
+
+    /// Read a YAML or JSON document written by `format`.
+    pub fn parse(content: &str) -> Result<ConfigDocument> {
+        let document: ConfigDocument =
+            serde_yaml::from_str(content).map_err(|e| anyhow!("not a config document: {}", e))?;
+        if document.version == 0 || document.version > CONFIG_DOCUMENT_VERSION {
+            return Err(anyhow!(
+                "unsupported config document version {}, expected {}",
+                document.version,
+                CONFIG_DOCUMENT_VERSION
+            ));
+        }
+        Ok(document)
+    }
+
+    pub fn sections(&self) -> Result<ConfigSections> {
+        ConfigSections::from_value(&self.config)
+    }
+
+    pub fn format(&self, format: ConfigFormat) -> Result<String> {
+        Ok(match format {
+            ConfigFormat::Yaml => serde_yaml::to_string(self)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use crate::config::constant::{
+    DEFAULT_EXEC_MAX_OUTPUT, DEFAULT_EXEC_TIMEOUT, MAX_EXEC_TIMEOUT, MIN_EXEC_TIMEOUT,
+};
+use crate::traits::json_response::JsonResponder;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An allowlisted command that `/exec` can run by name.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ExecScript {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ExecConfig {
+    #[serde(default)]
+    scripts: Vec<ExecScript>,
+    /// Allow `/exec` to run arbitrary shell input, off by default.
+    #[serde(default)]
+    allow_free_form: bool,
+    /// Seconds a command may run when the request doesn't say.
+    #[serde(default = "default_timeout")]
+    default_timeout: u64,
+    /// Bytes of stdout and stderr kept each in a non-streaming response.
+    #[serde(default = "default_max_output")]
+    max_output: usize,
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_EXEC_TIMEOUT
+}
+
+fn default_max_output() -> usize {
+    DEFAULT_EXEC_MAX_OUTPUT
+}
+
+impl ExecConfig {
+    /// Merge the other ExecConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: ExecConfig) -> bool {
+        let mut merged = false;
+        if other.scripts != self.scripts {
+            self.scripts = other.scripts;
+            merged = true;
+        }
+        if other.allow_free_form != self.allow_free_form {
+            self.allow_free_form = other.allow_free_form;
+            merged = true;
+        }
+        if other.default_timeout != self.default_timeout {
+            self.default_timeout = other.default_timeout;
+            merged = true;
+        }
+        if other.max_output != self.max_output {
+            self.max_output = other.max_output;
+            merged = true;
+        }
+        merged
+    }
+
+    pub fn scripts(&self) -> Vec<ExecScript> {
+        self.scripts.clone()
+    }
+
+    pub fn script(&self, name: &str) -> Option<ExecScript> {
+        self.scripts.iter().find(|s| s.name == name).cloned()
+    }
+
+    pub fn allow_free_form(&self) -> bool {
+        self.allow_free_form
+    }
+
+    pub fn default_timeout(&self) -> u64 {
+        self.default_timeout
+    }
+
+    /// The timeout to apply for a requested one, kept between `MIN_EXEC_TIMEOUT` and `MAX_EXEC_TIMEOUT`.
+    pub fn timeout(&self, requested: Option<u64>) -> u64 {
+        requested
+            .unwrap_or(self.default_timeout)
+            .clamp(MIN_EXEC_TIMEOUT, MAX_EXEC_TIMEOUT)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(MIN_EXEC_TIMEOUT..=MAX_EXEC_TIMEOUT).contains(&self.default_timeout) {
+            return Err(anyhow!(
+                "default_timeout must be between {} and {}",
+                MIN_EXEC_TIMEOUT,
+                MAX_EXEC_TIMEOUT
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn max_output(&self) -> usize {
+        self.max_output
+    }
+}
+
+impl JsonResponder for ExecConfig {}
+
+impl Default for ExecConfig {
+    fn default() -> Self {
+        ExecConfig {
+            scripts: vec![],
+            allow_free_form: false,
+            default_timeout: DEFAULT_EXEC_TIMEOUT,
+            max_output: DEFAULT_EXEC_MAX_OUTPUT,
+        }
+    }
+}
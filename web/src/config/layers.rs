@@ -0,0 +1,519 @@
+use crate::cli::Args;
+use crate::config::access::AccessConfig;
+use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
+use crate::config::constant::{
+    ACCESS_CONFIG, AUDIT_CONFIG, CONFIG_ENV_PREFIX, CONFIG_FILE_ENV, EXEC_CONFIG, LOG_DIR,
+    RATE_LIMIT_CONFIG, SERVER_CONFIG, TERMINAL_CONFIG, TLS_CONFIG, WEB_SERVER_CONFIG,
+};
+use crate::config::exec::ExecConfig;
+use crate::config::rate_limit::RateLimitConfig;
+use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
+use crate::config::web_server::WebServerConfig;
+use crate::db::db_wrapper::DbWrapper;
+use crate::utils::common_util::mask_token;
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Settings outside of the sections, they locate the database so can't be stored in it.
+pub const TOP_LEVEL_KEYS: [&str; 3] = ["data_dir", "log_dir", "secret_key_file"];
+
+/// Tokens, masked by `/config/effective` and left out of redacted exports.
+pub const SECRET_PATHS: [&str; 2] = ["server.token", "app.token"];
+
+/// Where a config value came from, each layer overrides the ones before it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    Default,
+    Database,
+    File,
+    Env,
+    Cli,
+}
+
+impl Display for ValueSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueSource::Default => write!(f, "the defaults"),
+            ValueSource::Database => write!(f, "the database"),
+            ValueSource::File => write!(f, "the config file"),
+            ValueSource::Env => write!(f, "the environment"),
+            ValueSource::Cli => write!(f, "the command line"),
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("config serializes to JSON")
+}
+
+/// Every setting with its default value, sections keyed like the config file.
+pub fn defaults() -> Value {
+    let mut root = Map::new();
+    for key in TOP_LEVEL_KEYS {
+        root.insert(key.to_string(), Value::Null);
+    }
+    root.insert("web_server".into(), to_value(&WebServerConfig::default()));
+    root.insert("server".into(), to_value(&ServerConfig::default()));
+    root.insert("app".into(), to_value(&AppConfig::default()));
+    root.insert("terminal".into(), to_value(&TerminalConfig::default()));
+    root.insert("exec".into(), to_value(&ExecConfig::default()));
+    root.insert("tls".into(), to_value(&TlsConfig::default()));
+    root.insert("rate_limit".into(), to_value(&RateLimitConfig::default()));
+    root.insert("access".into(), to_value(&AccessConfig::default()));
+    root.insert("audit".into(), to_value(&AuditConfig::default()));
+    Value::Object(root)
+}
+
+/// Leaves of a config tree by dotted path, arrays and empty objects are leaves.
+pub fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, child) in map {
+                    let path = match prefix {
+                        "" => key.clone(),
+                        _ => format!("{}.{}", prefix, key),
+                    };
+                    walk(&path, child, out);
+                }
+            }
+            _ => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    if let Value::Object(map) = value {
+        if !map.is_empty() {
+            walk("", value, &mut out);
+        }
+    }
+    out
+}
+
+pub fn get_path<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(root, |value, key| value.get(key))
+}
+
+/// Set a value by dotted path, creating the objects on the way.
+pub fn set_path(root: &mut Value, path: &str, value: Value) {
+    let mut node = root;
+    for key in path.split('.') {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = node
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    *node = value;
+}
+
+/// Set the leaves of `value` that differ from `base` under `section`.
+fn set_changed<T: Serialize>(layer: &mut Value, section: &str, base: &T, value: &T) {
+    let base = flatten(&to_value(base));
+    for (path, leaf) in flatten(&to_value(value)) {
+        if base.get(&path) != Some(&leaf) {
+            set_path(layer, &format!("{}.{}", section, path), leaf);
+        }
+    }
+}
+
+/// Paths in `value` that aren't settings, nested sections are checked key by key.
+fn unknown_keys(defaults: &Value, value: &Value, prefix: &str, out: &mut Vec<String>) {
+    let (Value::Object(defaults), Value::Object(value)) = (defaults, value) else {
+        return;
+    };
+    for (key, child) in value {
+        let path = match prefix {
+            "" => key.clone(),
+            _ => format!("{}.{}", prefix, key),
+        };
+        match defaults.get(key) {
+            None => out.push(path),
+            Some(default) => unknown_keys(default, child, &path, out),
+        }
+    }
+}
+
+/// Paths in `value` that aren't settings.
+pub fn unknown_settings(value: &Value) -> Vec<String> {
+    let mut unknown = vec![];
+    unknown_keys(&defaults(), value, "", &mut unknown);
+    unknown
+}
+
+/// Parse a config file, TOML for `.toml` files and YAML otherwise.
+pub fn parse_file(path: &Path, content: &str) -> Result<Value> {
+    let value: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(content)?,
+        _ => serde_yaml::from_str(content)?,
+    };
+    let value = match value {
+        Value::Null => Value::Object(Map::new()),
+        Value::Object(_) => value,
+        _ => return Err(anyhow!("expected a mapping of settings")),
+    };
+    let unknown = unknown_settings(&value);
+    if !unknown.is_empty() {
+        return Err(anyhow!("unknown settings: {}", unknown.join(", ")));
+    }
+    Ok(value)
+}
+
+/// The environment variable overriding a setting, e.g. `SERVERBEE_WEB_SERVER_PORT`.
+pub fn env_name(path: &str) -> String {
+    format!(
+        "{}{}",
+        CONFIG_ENV_PREFIX,
+        path.replace('.', "_").to_uppercase()
+    )
+}
+
+/// Settings from the environment, values are typed after the default they replace.
+/// Unset settings may be strings or not, those read as JSON are kept in the second
+/// map in case the section only accepts a string there.
+pub fn env_layer(lookup: impl Fn(&str) -> Option<String>) -> (Value, BTreeMap<String, String>) {
+    let mut layer = Value::Object(Map::new());
+    let mut ambiguous = BTreeMap::new();
+    for (path, default) in flatten(&defaults()) {
+        let raw = match lookup(&env_name(&path)) {
+            Some(raw) => raw,
+            None => continue,
+        };
+        let value = match default {
+            Value::String(_) => Value::String(raw),
+            Value::Null => match serde_json::from_str::<Value>(&raw) {
+                Ok(value) if !value.is_string() && !value.is_null() => {
+                    ambiguous.insert(path.clone(), raw);
+                    value
+                }
+                _ => Value::String(raw),
+            },
+            Value::Array(_) => serde_json::from_str(&raw).unwrap_or_else(|_| {
+                Value::Array(
+                    raw.split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Value::String(item.to_string()))
+                        .collect(),
+                )
+            }),
+            _ => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+        };
+        set_path(&mut layer, &path, value);
+    }
+    (layer, ambiguous)
+}
+
+/// Settings given as command line arguments.
+pub fn cli_layer(args: &Args) -> Value {
+    let mut layer = Value::Object(Map::new());
+    let top_level = [
+        ("data_dir", &args.data_dir),
+        ("log_dir", &args.log_dir),
+        ("secret_key_file", &args.secret_key_file),
+    ];
+    for (key, value) in top_level {
+        if let Some(value) = value {
+            set_path(&mut layer, key, Value::String(value.clone()));
+        }
+    }
+    if let Some(port) = args.port {
+        let web_server = WebServerConfig::new(port);
+        set_changed(
+            &mut layer,
+            "web_server",
+            &WebServerConfig::default(),
+            &web_server,
+        );
+    }
+    let app = AppConfig::new(args.app_token.clone());
+    set_changed(&mut layer, "app", &AppConfig::default(), &app);
+    let server = ServerConfig::new(
+        args.server_token.clone(),
+        args.server_host.clone(),
+        args.disable_ssl,
+    );
+    set_changed(&mut layer, "server", &ServerConfig::default(), &server);
+    let mut tls = TlsConfig::default();
+    tls.merge_args(
+        args.tls_cert.clone(),
+        args.tls_key.clone(),
+        args.tls_self_signed,
+        args.tls_redirect_port,
+    );
+    set_changed(&mut layer, "tls", &TlsConfig::default(), &tls);
+    layer
+}
+
+/// Settings stored in the database, only those that differ from the defaults.
+pub fn database_layer(db: &DbWrapper) -> Result<Value> {
+    fn stored<T: DeserializeOwned + Serialize + Default>(
+        db: &DbWrapper,
+        layer: &mut Value,
+        section: &str,
+        key: &str,
+        errors: &mut Vec<String>,
+    ) {
+        match db.get::<T>(key) {
+            Ok(Some(value)) => set_changed(layer, section, &T::default(), &value),
+            Ok(None) => {}
+            Err(e) => errors.push(format!("{}: {}", key, e)),
+        }
+    }
+    let mut layer = Value::Object(Map::new());
+    let mut errors = vec![];
+    match db.get::<String>(LOG_DIR) {
+        Ok(Some(log_dir)) => set_path(&mut layer, "log_dir", log_dir.into()),
+        Ok(None) => {}
+        Err(e) => errors.push(format!("{}: {}", LOG_DIR, e)),
+    }
+    stored::<WebServerConfig>(db, &mut layer, "web_server", WEB_SERVER_CONFIG, &mut errors);
+    match db.get_sealed::<ServerConfig>(SERVER_CONFIG) {
+        Ok(Some(server)) => set_changed(&mut layer, "server", &ServerConfig::default(), &server),
+        Ok(None) => {}
+        Err(e) => errors.push(format!("{}: {}", SERVER_CONFIG, e)),
+    }
+    stored::<TerminalConfig>(db, &mut layer, "terminal", TERMINAL_CONFIG, &mut errors);
+    stored::<ExecConfig>(db, &mut layer, "exec", EXEC_CONFIG, &mut errors);
+    stored::<TlsConfig>(db, &mut layer, "tls", TLS_CONFIG, &mut errors);
+    stored::<RateLimitConfig>(db, &mut layer, "rate_limit", RATE_LIMIT_CONFIG, &mut errors);
+    stored::<AccessConfig>(db, &mut layer, "access", ACCESS_CONFIG, &mut errors);
+    stored::<AuditConfig>(db, &mut layer, "audit", AUDIT_CONFIG, &mut errors);
+    match errors.is_empty() {
+        true => Ok(layer),
+        false => Err(anyhow!(
+            "Malformed values in the database, `serverbee-web db check --fix` removes them:\n  {}",
+            errors.join("\n  ")
+        )),
+    }
+}
+
+/// The config file, environment and command line layers,
+/// read before the database is open since they say where it is.
+#[derive(Debug, Clone, Default)]
+pub struct Overlays {
+    file: Option<PathBuf>,
+    file_layer: Value,
+    env_layer: Value,
+    ambiguous: BTreeMap<String, String>,
+    cli_layer: Value,
+}
+
+impl Overlays {
+    /// Read the file given by `--config` or `SERVERBEE_CONFIG`, the environment and `args`.
+    pub fn load(args: &Args) -> Result<Overlays> {
+        let file = args
+            .config
+            .clone()
+            .or_else(|| std::env::var(CONFIG_FILE_ENV).ok())
+            .map(PathBuf::from);
+        let file_layer = match &file {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| parse_file(path, &content))
+                .map_err(|e| anyhow!("{}: {}", path.display(), e))?,
+            None => Value::Object(Map::new()),
+        };
+        let (env_layer, ambiguous) = env_layer(|name| std::env::var(name).ok());
+        Ok(Overlays::new(
+            file,
+            file_layer,
+            env_layer,
+            ambiguous,
+            cli_layer(args),
+        ))
+    }
+
+    pub fn new(
+        file: Option<PathBuf>,
+        file_layer: Value,
+        env_layer: Value,
+        ambiguous: BTreeMap<String, String>,
+        cli_layer: Value,
+    ) -> Self {
+        Overlays {
+            file,
+            file_layer,
+            env_layer,
+            ambiguous,
+            cli_layer,
+        }
+    }
+
+    /// A top-level setting from the highest layer setting it.
+    pub fn top_level(&self, key: &str) -> Option<String> {
+        [&self.cli_layer, &self.env_layer, &self.file_layer]
+            .into_iter()
+            .find_map(|layer| layer_string(layer, key))
+    }
+}
+
+fn layer_string(layer: &Value, path: &str) -> Option<String> {
+    match get_path(layer, path)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// The settings merged from every layer, and which layer each one came from.
+#[derive(Debug, Clone)]
+pub struct ConfigLayers {
+    file: Option<PathBuf>,
+    value: Value,
+    sources: BTreeMap<String, ValueSource>,
+    ambiguous: BTreeMap<String, String>,
+}
+
+impl ConfigLayers {
+    pub fn new(database: Value, overlays: Overlays) -> Self {
+        let mut value = defaults();
+        let mut sources: BTreeMap<String, ValueSource> = flatten(&value)
+            .into_keys()
+            .map(|path| (path, ValueSource::Default))
+            .collect();
+        let layers = [
+            (ValueSource::Database, database),
+            (ValueSource::File, overlays.file_layer),
+            (ValueSource::Env, overlays.env_layer),
+            (ValueSource::Cli, overlays.cli_layer),
+        ];
+        for (source, layer) in layers {
+            for (path, leaf) in flatten(&layer) {
+                let nested = format!("{}.", path);
+                sources.retain(|other, _| !other.starts_with(&nested));
+                set_path(&mut value, &path, leaf);
+                sources.insert(path, source);
+            }
+        }
+        ConfigLayers {
+            file: overlays.file,
+            value,
+            sources,
+            ambiguous: overlays.ambiguous,
+        }
+    }
+
+    /// Replace the merged settings with the ones the sections were read as,
+    /// so values filled in by serde compare equal later.
+    pub fn settle(&mut self, value: Value) {
+        self.value = value;
+    }
+
+    pub fn file(&self) -> Option<PathBuf> {
+        self.file.clone()
+    }
+
+    pub fn value(&self) -> Value {
+        self.value.clone()
+    }
+
+    pub fn top_level(&self, key: &str) -> Option<String> {
+        layer_string(&self.value, key)
+    }
+
+    /// Layer a setting came from, a setting inside a list comes with the list.
+    pub fn source(&self, path: &str) -> ValueSource {
+        let mut path = path;
+        loop {
+            if let Some(source) = self.sources.get(path) {
+                return *source;
+            }
+            match path.rsplit_once('.') {
+                Some((parent, _)) => path = parent,
+                None => return ValueSource::Default,
+            }
+        }
+    }
+
+    /// Deserialize a section, recording a readable error and falling back to the defaults.
+    pub fn section<T: DeserializeOwned + Default>(
+        &self,
+        name: &str,
+        errors: &mut Vec<String>,
+    ) -> T {
+        let value = get_path(&self.value, name).cloned().unwrap_or(Value::Null);
+        let error = match serde_json::from_value::<T>(value.clone()) {
+            Ok(section) => return section,
+            Err(e) => e,
+        };
+        // environment values read as JSON may have been meant as strings
+        let prefix = format!("{}.", name);
+        let mut retry = value;
+        let mut changed = false;
+        for (path, raw) in &self.ambiguous {
+            if let Some(rest) = path.strip_prefix(&prefix) {
+                set_path(&mut retry, rest, Value::String(raw.clone()));
+                changed = true;
+            }
+        }
+        if changed {
+            if let Ok(section) = serde_json::from_value::<T>(retry) {
+                return section;
+            }
+        }
+        errors.push(format!(
+            "{}: {} (set by {})",
+            name,
+            error,
+            self.describe(name)
+        ));
+        T::default()
+    }
+
+    /// The layers that set anything in a section, for error messages.
+    pub fn describe(&self, section: &str) -> String {
+        let prefix = format!("{}.", section);
+        let mut sources: Vec<ValueSource> = self
+            .sources
+            .iter()
+            .filter(|(path, _)| path.as_str() == section || path.starts_with(&prefix))
+            .map(|(_, source)| *source)
+            .filter(|source| *source != ValueSource::Default)
+            .collect();
+        sources.sort();
+        sources.dedup();
+        let describe = |source: &ValueSource| match (source, &self.file) {
+            (ValueSource::File, Some(file)) => format!("{} {}", source, file.display()),
+            _ => source.to_string(),
+        };
+        match sources.as_slice() {
+            [] => ValueSource::Default.to_string(),
+            sources => sources.iter().map(describe).collect::<Vec<_>>().join(", "),
+        }
+    }
+
+    /// Every setting of `current` with where it came from, secrets masked.
+    /// Settings that no longer match the merged layers were changed through the API,
+    /// which stores them in the database.
+    pub fn effective(&self, current: &Value) -> BTreeMap<String, (Value, ValueSource)> {
+        let merged = flatten(&self.value);
+        flatten(current)
+            .into_iter()
+            .map(|(path, value)| {
+                let source = match merged.get(&path) {
+                    Some(merged) if *merged == value => self.source(&path),
+                    _ => ValueSource::Database,
+                };
+                let value = match (&value, SECRET_PATHS.contains(&path.as_str())) {
+                    (Value::String(secret), true) => Value::String(mask_token(secret)),
+                    _ => value,
+                };
+                (path, (value, source))
+            })
+            .collect()
+    }
+}
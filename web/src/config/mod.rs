@@ -1,5 +1,14 @@
+pub mod access;
 pub mod app;
+pub mod audit;
 pub mod config;
+pub mod config_command;
 pub mod constant;
+pub mod document;
+pub mod exec;
+pub mod layers;
+pub mod rate_limit;
 pub mod server;
+pub mod terminal;
+pub mod tls;
 pub mod web_server;
@@ -0,0 +1,96 @@
+use crate::config::constant::{
+    DEFAULT_BAN_DURATION, DEFAULT_FAILURE_WINDOW, DEFAULT_HEAVY_REQUESTS_PER_MINUTE,
+    DEFAULT_MAX_AUTH_FAILURES, DEFAULT_MAX_BAN_DURATION,
+};
+use crate::traits::json_response::JsonResponder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Failed authentications from one IP within `failure_window` before it is banned, 0 never bans.
+    #[serde(default = "default_max_auth_failures")]
+    max_auth_failures: u32,
+    /// Seconds failures are counted over.
+    #[serde(default = "default_failure_window")]
+    failure_window: u64,
+    /// Seconds of the first ban, doubled for every ban that follows.
+    #[serde(default = "default_ban_duration")]
+    ban_duration: u64,
+    #[serde(default = "default_max_ban_duration")]
+    max_ban_duration: u64,
+    /// Requests per minute and IP to `/ws`, `/pty`, `/exec` and `/ws` process mode, 0 is unlimited.
+    #[serde(default = "default_heavy_requests_per_minute")]
+    heavy_requests_per_minute: u32,
+}
+
+fn default_max_auth_failures() -> u32 {
+    DEFAULT_MAX_AUTH_FAILURES
+}
+
+fn default_failure_window() -> u64 {
+    DEFAULT_FAILURE_WINDOW
+}
+
+fn default_ban_duration() -> u64 {
+    DEFAULT_BAN_DURATION
+}
+
+fn default_max_ban_duration() -> u64 {
+    DEFAULT_MAX_BAN_DURATION
+}
+
+fn default_heavy_requests_per_minute() -> u32 {
+    DEFAULT_HEAVY_REQUESTS_PER_MINUTE
+}
+
+impl RateLimitConfig {
+    /// Merge the other RateLimitConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: RateLimitConfig) -> bool {
+        let changed = *self != other;
+        *self = other;
+        changed
+    }
+
+    pub fn max_auth_failures(&self) -> u32 {
+        self.max_auth_failures
+    }
+
+    pub fn failure_window(&self) -> u64 {
+        self.failure_window
+    }
+
+    pub fn ban_duration(&self) -> u64 {
+        self.ban_duration
+    }
+
+    pub fn max_ban_duration(&self) -> u64 {
+        self.max_ban_duration
+    }
+
+    pub fn heavy_requests_per_minute(&self) -> u32 {
+        self.heavy_requests_per_minute
+    }
+
+    /// Length of the nth ban of an IP, starting at 1.
+    pub fn ban_duration_for(&self, nth: u32) -> u64 {
+        let factor = 1u64.checked_shl(nth.saturating_sub(1)).unwrap_or(u64::MAX);
+        self.ban_duration
+            .saturating_mul(factor)
+            .min(self.max_ban_duration.max(self.ban_duration))
+    }
+}
+
+impl JsonResponder for RateLimitConfig {}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            max_auth_failures: DEFAULT_MAX_AUTH_FAILURES,
+            failure_window: DEFAULT_FAILURE_WINDOW,
+            ban_duration: DEFAULT_BAN_DURATION,
+            max_ban_duration: DEFAULT_MAX_BAN_DURATION,
+            heavy_requests_per_minute: DEFAULT_HEAVY_REQUESTS_PER_MINUTE,
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::db::db_wrapper::Sealed;
 use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::mask_token;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
@@ -21,7 +23,9 @@ impl ServerConfig {
     /// Returns true if any of the fields were changed.
     pub fn merge(&mut self, other: ServerConfig) -> bool {
         let mut merged = false;
-        if other.token.is_some() && other.token != self.token {
+        // a masked token read back from the API leaves the token as it is
+        let masked = self.token.as_deref().map(mask_token);
+        if other.token.is_some() && other.token != self.token && other.token != masked {
             self.token = other.token.clone();
             merged = true;
         }
@@ -62,3 +66,9 @@ impl ServerConfig {
 }
 
 impl JsonResponder for ServerConfig {}
+
+impl Sealed for ServerConfig {
+    fn secrets(&mut self) -> Vec<&mut Option<String>> {
+        vec![&mut self.token]
+    }
+}
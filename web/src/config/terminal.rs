@@ -0,0 +1,184 @@
+use crate::config::constant::{
+    DEFAULT_RECORDING_MAX_FILES, DEFAULT_RECORDING_RETENTION_DAYS, DEFAULT_SCROLLBACK_SIZE,
+    DEFAULT_SESSION_IDLE_TIMEOUT,
+};
+use crate::traits::json_response::JsonResponder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A named way to start a terminal, selected with the `shell` query parameter of `/pty`.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct TerminalProfile {
+    pub name: String,
+    /// Executable name or path.
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory, defaults to the home of `uid` when it is set.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// User to run the shell as, only honored on unix when serverbee-web runs as root.
+    #[serde(default)]
+    pub uid: Option<u32>,
+    /// Group to run the shell as, defaults to the primary group of `uid`.
+    #[serde(default)]
+    pub gid: Option<u32>,
+}
+
+impl TerminalProfile {
+    pub fn new(name: &str, command: &str) -> Self {
+        TerminalProfile {
+            name: name.to_string(),
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TerminalConfig {
+    /// Seconds a detached session is kept alive, 0 closes it as soon as the last viewer leaves.
+    #[serde(default = "default_session_idle_timeout")]
+    session_idle_timeout: u64,
+    /// Bytes of output kept per session and replayed on reattach.
+    #[serde(default = "default_scrollback_size")]
+    scrollback_size: usize,
+    /// Record every session to disk in asciicast v2 format.
+    #[serde(default)]
+    record: bool,
+    /// Also record what viewers type, off by default since it captures passwords.
+    #[serde(default)]
+    record_input: bool,
+    /// Recordings older than this many days are deleted, 0 keeps them forever.
+    #[serde(default = "default_recording_retention_days")]
+    recording_retention_days: u64,
+    /// Only the newest recordings up to this count are kept, 0 means unlimited.
+    #[serde(default = "default_recording_max_files")]
+    recording_max_files: usize,
+    #[serde(default)]
+    profiles: Vec<TerminalProfile>,
+    /// Profile used when `/pty` is opened without a `shell` parameter.
+    #[serde(default)]
+    default_profile: Option<String>,
+}
+
+fn default_session_idle_timeout() -> u64 {
+    DEFAULT_SESSION_IDLE_TIMEOUT
+}
+
+fn default_scrollback_size() -> usize {
+    DEFAULT_SCROLLBACK_SIZE
+}
+
+fn default_recording_retention_days() -> u64 {
+    DEFAULT_RECORDING_RETENTION_DAYS
+}
+
+fn default_recording_max_files() -> usize {
+    DEFAULT_RECORDING_MAX_FILES
+}
+
+impl TerminalConfig {
+    /// Merge the other TerminalConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: TerminalConfig) -> bool {
+        let mut merged = false;
+        if other.session_idle_timeout != self.session_idle_timeout {
+            self.session_idle_timeout = other.session_idle_timeout;
+            merged = true;
+        }
+        if other.scrollback_size != self.scrollback_size {
+            self.scrollback_size = other.scrollback_size;
+            merged = true;
+        }
+        if other.record != self.record {
+            self.record = other.record;
+            merged = true;
+        }
+        if other.record_input != self.record_input {
+            self.record_input = other.record_input;
+            merged = true;
+        }
+        if other.recording_retention_days != self.recording_retention_days {
+            self.recording_retention_days = other.recording_retention_days;
+            merged = true;
+        }
+        if other.recording_max_files != self.recording_max_files {
+            self.recording_max_files = other.recording_max_files;
+            merged = true;
+        }
+        if other.profiles != self.profiles {
+            self.profiles = other.profiles;
+            merged = true;
+        }
+        if other.default_profile != self.default_profile {
+            self.default_profile = other.default_profile;
+            merged = true;
+        }
+        merged
+    }
+
+    pub fn session_idle_timeout(&self) -> u64 {
+        self.session_idle_timeout
+    }
+
+    pub fn scrollback_size(&self) -> usize {
+        self.scrollback_size
+    }
+
+    pub fn record(&self) -> bool {
+        self.record
+    }
+
+    pub fn record_input(&self) -> bool {
+        self.record_input
+    }
+
+    pub fn recording_retention_days(&self) -> u64 {
+        self.recording_retention_days
+    }
+
+    pub fn recording_max_files(&self) -> usize {
+        self.recording_max_files
+    }
+
+    pub fn profiles(&self) -> Vec<TerminalProfile> {
+        self.profiles.clone()
+    }
+
+    pub fn profile(&self, name: &str) -> Option<TerminalProfile> {
+        self.profiles.iter().find(|p| p.name == name).cloned()
+    }
+
+    pub fn default_profile(&self) -> Option<String> {
+        self.default_profile.clone()
+    }
+
+    /// Names of the profiles that switch user or group.
+    pub fn privileged_profiles(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .filter(|p| p.uid.is_some() || p.gid.is_some())
+            .map(|p| p.name.clone())
+            .collect()
+    }
+}
+
+impl JsonResponder for TerminalConfig {}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        TerminalConfig {
+            session_idle_timeout: DEFAULT_SESSION_IDLE_TIMEOUT,
+            scrollback_size: DEFAULT_SCROLLBACK_SIZE,
+            record: false,
+            record_input: false,
+            recording_retention_days: DEFAULT_RECORDING_RETENTION_DAYS,
+            recording_max_files: DEFAULT_RECORDING_MAX_FILES,
+            profiles: vec![],
+            default_profile: None,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::config::constant::DEFAULT_TLS_RELOAD_INTERVAL;
+use crate::token::account::Role;
+use crate::token::scope::Scope;
+use crate::traits::json_response::JsonResponder;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Grants a client certificate the scopes of a role, plus any extra scopes.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ClientCertRule {
+    /// Common name of the certificate subject, `*` matches any certificate signed by the CA.
+    pub subject: String,
+    #[serde(default)]
+    pub role: Option<Role>,
+    #[serde(default)]
+    pub scopes: Vec<Scope>,
+}
+
+impl ClientCertRule {
+    pub fn scopes(&self) -> Vec<Scope> {
+        let mut scopes = self.role.map(|role| role.scopes()).unwrap_or_default();
+        for scope in &self.scopes {
+            if !scopes.contains(scope) {
+                scopes.push(*scope);
+            }
+        }
+        scopes
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TlsConfig {
+    /// Serve HTTPS instead of HTTP, changes apply after a restart.
+    #[serde(default)]
+    enabled: bool,
+    /// PEM file with the certificate chain, leaf first.
+    #[serde(default)]
+    cert: Option<String>,
+    /// PEM file with the private key, PKCS#8, PKCS#1 or SEC1.
+    #[serde(default)]
+    key: Option<String>,
+    /// Generate a self-signed certificate in the data directory when no cert and key are set.
+    #[serde(default)]
+    self_signed: bool,
+    /// Also listen for plain HTTP on this port and redirect it to HTTPS.
+    #[serde(default)]
+    redirect_port: Option<u16>,
+    /// Seconds between checks of the cert and key files for renewals, 0 disables reloading.
+    #[serde(default = "default_reload_interval")]
+    reload_interval: u64,
+    /// PEM file with the CAs client certificates are verified against, enables mutual TLS.
+    #[serde(default)]
+    client_ca: Option<String>,
+    /// Refuse connections without a client certificate, otherwise tokens still work.
+    #[serde(default)]
+    require_client_cert: bool,
+    #[serde(default)]
+    client_certs: Vec<ClientCertRule>,
+}
+
+fn default_reload_interval() -> u64 {
+    DEFAULT_TLS_RELOAD_INTERVAL
+}
+
+impl TlsConfig {
+    /// Apply the TLS command line arguments, any of them turns TLS on.
+    /// Returns true if any of the fields were changed.
+    pub fn merge_args(
+        &mut self,
+        cert: Option<String>,
+        key: Option<String>,
+        self_signed: bool,
+        redirect_port: Option<u16>,
+    ) -> bool {
+        let before = self.clone();
+        if cert.is_some() {
+            self.cert = cert;
+            self.enabled = true;
+        }
+        if key.is_some() {
+            self.key = key;
+            self.enabled = true;
+        }
+        if self_signed {
+            self.self_signed = true;
+            self.enabled = true;
+        }
+        if redirect_port.is_some() {
+            self.redirect_port = redirect_port;
+        }
+        *self != before
+    }
+
+    /// Merge the other TlsConfig into self.
+    /// Returns true if any of the fields were changed.
+    pub fn merge(&mut self, other: TlsConfig) -> bool {
+        let changed = *self != other;
+        *self = other;
+        changed
+    }
+
+    /// Enabled TLS needs a certificate and key, or a self-signed one.
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && !self.self_signed && (self.cert.is_none() || self.key.is_none()) {
+            return Err(anyhow!(
+                "cert and key are required when enabled without self_signed"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn cert(&self) -> Option<String> {
+        self.cert.clone()
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.key.clone()
+    }
+
+    pub fn self_signed(&self) -> bool {
+        self.self_signed
+    }
+
+    pub fn redirect_port(&self) -> Option<u16> {
+        self.redirect_port
+    }
+
+    pub fn reload_interval(&self) -> u64 {
+        self.reload_interval
+    }
+
+    pub fn client_ca(&self) -> Option<String> {
+        self.client_ca.clone()
+    }
+
+    pub fn require_client_cert(&self) -> bool {
+        self.require_client_cert
+    }
+
+    pub fn client_certs(&self) -> Vec<ClientCertRule> {
+        self.client_certs.clone()
+    }
+
+    /// Scopes of a verified client certificate, an exact subject wins over `*`.
+    pub fn client_cert_scopes(&self, subject: &str) -> Option<Vec<Scope>> {
+        self.client_certs
+            .iter()
+            .find(|rule| rule.subject == subject)
+            .or_else(|| self.client_certs.iter().find(|rule| rule.subject == "*"))
+            .map(|rule| rule.scopes())
+    }
+}
+
+impl JsonResponder for TlsConfig {}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig {
+            enabled: false,
+            cert: None,
+            key: None,
+            self_signed: false,
+            redirect_port: None,
+            reload_interval: DEFAULT_TLS_RELOAD_INTERVAL,
+            client_ca: None,
+            require_client_cert: false,
+            client_certs: vec![],
+        }
+    }
+}
@@ -1,32 +1,128 @@
-use crate::config::constant::DEFAULT_PORT;
+use crate::config::constant::{
+    DEFAULT_JSON_LIMIT, DEFAULT_PORT, DEFAULT_SHUTDOWN_TIMEOUT, DEFAULT_WORKERS,
+    MAX_SHUTDOWN_TIMEOUT, MAX_WORKERS,
+};
 use crate::traits::json_response::JsonResponder;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+/// Interfaces listened on when no bind address is given.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IpMode {
+    /// IPv4 and IPv6 on one socket, IPv4 only when the system has no IPv6.
+    #[default]
+    Dual,
+    Ipv4,
+    Ipv6,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct WebServerConfig {
+    #[serde(default = "default_port")]
     port: u16,
+    /// IPs to listen on, e.g. `127.0.0.1` or `::1`, all interfaces of `ip_mode` when empty.
+    #[serde(default)]
+    bind: Vec<String>,
+    #[serde(default)]
+    ip_mode: IpMode,
+    /// Worker threads handling requests.
+    #[serde(default = "default_workers")]
+    workers: usize,
+    /// Largest JSON request body in bytes.
+    #[serde(default = "default_json_limit")]
+    json_limit: usize,
+    /// Seconds to finish requests and close connections when stopping.
+    #[serde(default = "default_shutdown_timeout")]
+    shutdown_timeout: u64,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_workers() -> usize {
+    DEFAULT_WORKERS
+}
+
+fn default_json_limit() -> usize {
+    DEFAULT_JSON_LIMIT
+}
+
+fn default_shutdown_timeout() -> u64 {
+    DEFAULT_SHUTDOWN_TIMEOUT
 }
 
 impl WebServerConfig {
     pub fn new(port: u16) -> Self {
-        WebServerConfig { port }
+        WebServerConfig {
+            port,
+            ..Default::default()
+        }
     }
 
+    /// Merge the other WebServerConfig into self.
+    /// Returns true if any of the fields were changed.
     pub fn merge(&mut self, other: WebServerConfig) -> bool {
-        let mut merged = false;
-        if other.port != self.port {
-            self.port = other.port;
-            merged = true;
+        let changed = *self != other;
+        *self = other;
+        changed
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.port == 0 {
+            return Err(anyhow!("port must not be 0"));
+        }
+        self.bind_ips()?;
+        if self.workers == 0 || self.workers > MAX_WORKERS {
+            return Err(anyhow!("workers must be between 1 and {}", MAX_WORKERS));
         }
-        merged
+        if self.json_limit == 0 {
+            return Err(anyhow!("json_limit must not be 0"));
+        }
+        if self.shutdown_timeout == 0 || self.shutdown_timeout > MAX_SHUTDOWN_TIMEOUT {
+            return Err(anyhow!(
+                "shutdown_timeout must be between 1 and {}",
+                MAX_SHUTDOWN_TIMEOUT
+            ));
+        }
+        Ok(())
     }
 
     pub fn port(&self) -> u16 {
         self.port
     }
 
-    pub fn set_port(&mut self, port: u16) {
-        self.port = port;
+    pub fn bind(&self) -> Vec<String> {
+        self.bind.clone()
+    }
+
+    pub fn bind_ips(&self) -> Result<Vec<IpAddr>> {
+        self.bind
+            .iter()
+            .map(|ip| {
+                ip.trim()
+                    .parse()
+                    .map_err(|_| anyhow!("invalid bind address: {}", ip))
+            })
+            .collect()
+    }
+
+    pub fn ip_mode(&self) -> IpMode {
+        self.ip_mode
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn json_limit(&self) -> usize {
+        self.json_limit
+    }
+
+    pub fn shutdown_timeout(&self) -> u64 {
+        self.shutdown_timeout
     }
 }
 
@@ -34,6 +130,13 @@ impl JsonResponder for WebServerConfig {}
 
 impl Default for WebServerConfig {
     fn default() -> Self {
-        WebServerConfig { port: DEFAULT_PORT }
+        WebServerConfig {
+            port: DEFAULT_PORT,
+            bind: vec![],
+            ip_mode: IpMode::Dual,
+            workers: DEFAULT_WORKERS,
+            json_limit: DEFAULT_JSON_LIMIT,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        }
     }
 }
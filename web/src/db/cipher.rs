@@ -0,0 +1,175 @@
+use crate::config::constant::{SECRET_KEY_ENV, SECRET_KEY_FILE};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Marks a value encrypted by `Cipher`, followed by base64 of nonce and ciphertext.
+const SEALED_PREFIX: &str = "enc:v1:";
+
+const KEY_LEN: usize = 32;
+
+/// Encrypts secrets stored in sled with ChaCha20-Poly1305.
+#[derive(Clone)]
+pub struct Cipher {
+    key: Arc<LessSafeKey>,
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cipher").finish_non_exhaustive()
+    }
+}
+
+impl Cipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() != KEY_LEN {
+            return Err(anyhow!("secret key must be {} bytes", KEY_LEN));
+        }
+        let key =
+            UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| anyhow!("invalid secret key"))?;
+        Ok(Self {
+            key: Arc::new(LessSafeKey::new(key)),
+        })
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX)
+    }
+
+    pub fn seal(&self, plain: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut data = plain.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .expect("ChaCha20-Poly1305 encryption can't fail");
+        let mut sealed = nonce.to_vec();
+        sealed.extend(data);
+        format!("{}{}", SEALED_PREFIX, BASE64.encode(sealed))
+    }
+
+    pub fn open(&self, value: &str) -> Result<String> {
+        let sealed = value
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or_else(|| anyhow!("value is not encrypted"))?;
+        let mut data = BASE64.decode(sealed)?;
+        if data.len() < NONCE_LEN {
+            return Err(anyhow!("encrypted value is truncated"));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[..NONCE_LEN]);
+        let plain = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut data[NONCE_LEN..],
+            )
+            .map_err(|_| anyhow!("can not decrypt value, is the secret key right?"))?;
+        Ok(String::from_utf8(plain.to_vec())?)
+    }
+}
+
+/// Where the secret key was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeySource {
+    Env,
+    File(PathBuf),
+    #[cfg(feature = "keyring")]
+    Keyring,
+}
+
+impl Display for KeySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Env => write!(f, "${}", SECRET_KEY_ENV),
+            KeySource::File(path) => write!(f, "{}", path.display()),
+            #[cfg(feature = "keyring")]
+            KeySource::Keyring => write!(f, "OS keyring"),
+        }
+    }
+}
+
+fn decode_key(encoded: &str) -> Result<Vec<u8>> {
+    let key = BASE64
+        .decode(encoded.trim())
+        .map_err(|_| anyhow!("secret key must be base64"))?;
+    if key.len() != KEY_LEN {
+        return Err(anyhow!("secret key must be {} bytes", KEY_LEN));
+    }
+    Ok(key)
+}
+
+pub fn generate_key() -> String {
+    let mut key = [0u8; KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut key);
+    BASE64.encode(key)
+}
+
+fn write_key_file(path: &Path, key: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(key.as_bytes())?;
+    Ok(())
+}
+
+/// Read the key from `path`, generating it when the file doesn't exist.
+fn file_key(path: &Path) -> Result<Vec<u8>> {
+    if !path.exists() {
+        write_key_file(path, &generate_key())?;
+        eprintln!(
+            "Generated secret key at {}, back it up with the database",
+            path.display()
+        );
+    }
+    decode_key(&read_to_string(path)?).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_key() -> Result<Vec<u8>> {
+    use crate::config::constant::{SECRET_KEY_KEYRING_SERVICE, SECRET_KEY_KEYRING_USER};
+    let entry = keyring::Entry::new(SECRET_KEY_KEYRING_SERVICE, SECRET_KEY_KEYRING_USER)?;
+    match entry.get_password() {
+        Ok(key) => decode_key(&key),
+        Err(keyring::Error::NoEntry) => {
+            let key = generate_key();
+            entry.set_password(&key)?;
+            eprintln!("Generated secret key in the OS keyring");
+            decode_key(&key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Find the secret key: `SERVERBEE_SECRET_KEY`, then `key_file`, then the OS keyring
+/// when built with the `keyring` feature and no key file exists yet in `data_dir`,
+/// and finally the key file in `data_dir`, generated on first start.
+pub fn load_key(key_file: Option<&Path>, data_dir: &Path) -> Result<(Vec<u8>, KeySource)> {
+    if let Ok(key) = std::env::var(SECRET_KEY_ENV) {
+        return Ok((
+            decode_key(&key).map_err(|e| anyhow!("${}: {}", SECRET_KEY_ENV, e))?,
+            KeySource::Env,
+        ));
+    }
+    if let Some(path) = key_file {
+        return Ok((file_key(path)?, KeySource::File(path.to_path_buf())));
+    }
+    let path = data_dir.join(SECRET_KEY_FILE);
+    #[cfg(feature = "keyring")]
+    if !path.exists() {
+        return Ok((keyring_key()?, KeySource::Keyring));
+    }
+    Ok((file_key(&path)?, KeySource::File(path)))
+}
@@ -0,0 +1,129 @@
+use crate::cli::{Args, DbAction};
+use crate::config::access::AccessConfig;
+use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
+use crate::config::constant::{
+    ACCESS_CONFIG, ACCOUNTS, API_TOKENS, APP_CONFIG, AUDIT_CONFIG, EXEC_CONFIG, LAST_LOGIN,
+    LOG_DIR, RATE_LIMIT_CONFIG, SCHEMA_VERSION, SERVER_CONFIG, TERMINAL_CONFIG, TLS_CONFIG,
+    WEB_SERVER_CONFIG,
+};
+use crate::config::exec::ExecConfig;
+use crate::config::layers::Overlays;
+use crate::config::rate_limit::RateLimitConfig;
+use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
+use crate::config::web_server::WebServerConfig;
+use crate::db::db_wrapper::{db_path, DbWrapper};
+use crate::db::migration::{latest_version, pending, schema_version};
+use crate::token::account::Account;
+use crate::token::api_token::ApiToken;
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+fn shape<T: DeserializeOwned>(db: &DbWrapper, key: &str) -> Result<()> {
+    db.get::<T>(key).map(|_| ())
+}
+
+type ShapeCheck = fn(&DbWrapper, &str) -> Result<()>;
+
+/// Keys with a known shape, a malformed one can be removed to fall back to its default.
+const KEYS: &[(&str, ShapeCheck)] = &[
+    (SCHEMA_VERSION, shape::<u32>),
+    (LOG_DIR, shape::<String>),
+    (LAST_LOGIN, shape::<u64>),
+    (WEB_SERVER_CONFIG, shape::<WebServerConfig>),
+    (SERVER_CONFIG, shape::<ServerConfig>),
+    (APP_CONFIG, shape::<AppConfig>),
+    (TERMINAL_CONFIG, shape::<TerminalConfig>),
+    (EXEC_CONFIG, shape::<ExecConfig>),
+    (TLS_CONFIG, shape::<TlsConfig>),
+    (RATE_LIMIT_CONFIG, shape::<RateLimitConfig>),
+    (ACCESS_CONFIG, shape::<AccessConfig>),
+    (AUDIT_CONFIG, shape::<AuditConfig>),
+    (ACCOUNTS, shape::<Vec<Account>>),
+    (API_TOKENS, shape::<Vec<ApiToken>>),
+];
+
+/// Stored values of the known keys that don't read back, with the reason.
+pub fn malformed_values(db: &DbWrapper) -> Vec<(String, String)> {
+    KEYS.iter()
+        .filter_map(|(key, check)| {
+            check(db, key)
+                .err()
+                .map(|e| (key.to_string(), e.to_string()))
+        })
+        .collect()
+}
+
+/// `serverbee-web db ...`, works on the db directly so the server must be stopped.
+pub fn run_db_command(args: &Args, action: DbAction) -> Result<()> {
+    let data_dir = Overlays::load(args)?
+        .top_level("data_dir")
+        .map(PathBuf::from);
+    let path = db_path(data_dir.clone());
+    match action {
+        DbAction::Check { fix } => {
+            let db = DbWrapper::open(data_dir)?;
+            println!("Database:       {}", path.display());
+            println!("Size on disk:   {} bytes", db.size_on_disk()?);
+            println!(
+                "Unclean stop:   {}",
+                if db.was_recovered() { "yes" } else { "no" }
+            );
+            match schema_version(&db) {
+                Ok(version) if pending(&db)?.is_empty() => {
+                    println!("Schema version: {}", version)
+                }
+                Ok(version) => println!(
+                    "Schema version: {}, {} migrated at the next start",
+                    version,
+                    latest_version()
+                ),
+                // reported as malformed below
+                Err(_) => println!("Schema version: unreadable"),
+            }
+            let checksum = db
+                .checksum()
+                .map_err(|e| anyhow!("database is damaged: {}", e))?;
+            println!("Checksum:       {:08x}", checksum);
+            let malformed = malformed_values(&db);
+            if malformed.is_empty() {
+                println!("All values are readable");
+                return Ok(());
+            }
+            for (key, error) in &malformed {
+                println!("Malformed {}: {}", key, error);
+            }
+            if !fix {
+                return Err(anyhow!(
+                    "{} malformed values, `serverbee-web db check --fix` removes them",
+                    malformed.len()
+                ));
+            }
+            for (key, _) in &malformed {
+                db.remove(key)?;
+            }
+            db.flush()?;
+            eprintln!(
+                "Removed {}, they are back to their defaults",
+                malformed
+                    .iter()
+                    .map(|(key, _)| key.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        DbAction::Compact => {
+            let (before, after) = DbWrapper::compact(data_dir)?;
+            eprintln!(
+                "Compacted {} from {} to {} bytes",
+                path.display(),
+                before,
+                after
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,27 +1,221 @@
-use anyhow::Result;
+use crate::db::cipher::Cipher;
+use crate::utils::common_util::get_now_timestamp;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use sled::Db;
-use std::path::PathBuf;
+use serde_json::Value;
+use sled::{Batch, Db, Tree};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// A value with fields that are kept encrypted in the db.
+pub trait Sealed {
+    fn secrets(&mut self) -> Vec<&mut Option<String>>;
+}
+
+impl Sealed for Option<String> {
+    fn secrets(&mut self) -> Vec<&mut Option<String>> {
+        vec![self]
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DbWrapper {
     db: Db,
+    cipher: Option<Cipher>,
+}
+
+/// The sled directory in `data_dir`, or `db` in the working directory.
+pub fn db_path(data_dir: Option<PathBuf>) -> PathBuf {
+    data_dir
+        .map(|p| p.join("db"))
+        .unwrap_or_else(|| PathBuf::from("db"))
+}
+
+/// The error of a lock held by another process.
+fn is_would_block(io: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    let codes = [libc::EWOULDBLOCK, libc::EAGAIN];
+    // ERROR_LOCK_VIOLATION
+    #[cfg(windows)]
+    let codes = [33];
+    io.kind() == ErrorKind::WouldBlock
+        || io.raw_os_error().is_some_and(|code| codes.contains(&code))
+}
+
+/// Whether another process holds the lock sled takes on the `db` file in `path`.
+/// sled 0.34 only keeps the lock error in a message, so it is tried again here.
+#[cfg(unix)]
+fn lock_held(path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let file = match fs::File::open(path.join("db")) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+    // the same lock as sled, released right away
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) };
+        return false;
+    }
+    is_would_block(&std::io::Error::last_os_error())
+}
+
+#[cfg(windows)]
+fn lock_held(_path: &Path) -> bool {
+    false
+}
+
+pub fn is_locked(path: &Path, e: &sled::Error) -> bool {
+    match e {
+        sled::Error::Io(io) => is_would_block(io) || lock_held(path),
+        _ => false,
+    }
+}
+
+/// Only what sled reports as damaged data, an I/O error may pass and is not a reason
+/// to start over.
+pub fn is_corrupt(e: &sled::Error) -> bool {
+    // a damaged conf file reads as `Unsupported`
+    matches!(
+        e,
+        sled::Error::Corruption { .. }
+            | sled::Error::ReportableBug(_)
+            | sled::Error::Unsupported(_)
+    )
+}
+
+fn open_error(path: &Path, e: sled::Error) -> anyhow::Error {
+    if is_locked(path, &e) {
+        return anyhow!(
+            "database {} is used by another process, is serverbee-web already running?",
+            path.display()
+        );
+    }
+    anyhow!("can not open database {}: {}", path.display(), e)
 }
 
 impl DbWrapper {
-    fn init_sled_db(path: PathBuf) -> Db {
-        sled::open(path).unwrap()
+    /// Open the database in `data_dir`, a corrupt one is moved aside and started over.
+    pub fn new(data_dir: Option<PathBuf>) -> Result<DbWrapper> {
+        let path = db_path(data_dir);
+        let db = match sled::open(&path) {
+            Ok(db) => db,
+            Err(e) if is_corrupt(&e) => {
+                let backup = DbWrapper::move_aside(&path, "corrupt")?;
+                eprintln!(
+                    "Database {} is corrupt ({}), moved it to {} and started a new one",
+                    path.display(),
+                    e,
+                    backup.display()
+                );
+                sled::open(&path).map_err(|e| open_error(&path, e))?
+            }
+            Err(e) => return Err(open_error(&path, e)),
+        };
+        Ok(DbWrapper { db, cipher: None })
     }
 
-    pub fn new(path: Option<PathBuf>) -> DbWrapper {
-        DbWrapper {
-            db: DbWrapper::init_sled_db(
-                path.map(|p| p.join("db"))
-                    .unwrap_or_else(|| PathBuf::from("db")),
-            ),
+    /// Open the database in `data_dir` as it is, for inspecting it.
+    pub fn open(data_dir: Option<PathBuf>) -> Result<DbWrapper> {
+        let path = db_path(data_dir);
+        let db = sled::open(&path).map_err(|e| open_error(&path, e))?;
+        Ok(DbWrapper { db, cipher: None })
+    }
+
+    /// Rename the database directory to `<path>.<suffix>-<timestamp>`.
+    fn move_aside(path: &Path, suffix: &str) -> Result<PathBuf> {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}-{}", suffix, get_now_timestamp()));
+        let backup = PathBuf::from(name);
+        fs::rename(path, &backup)
+            .map_err(|e| anyhow!("can not move {} aside: {}", path.display(), e))?;
+        Ok(backup)
+    }
+
+    /// Rewrite the database in `data_dir` into a fresh directory, dropping the space
+    /// sled keeps for old versions of values. Returns the size before and after.
+    pub fn compact(data_dir: Option<PathBuf>) -> Result<(u64, u64)> {
+        let path = db_path(data_dir);
+        let mut name = path.as_os_str().to_owned();
+        name.push(".compact");
+        let target = PathBuf::from(name);
+        let (before, checksum) = {
+            let db = sled::open(&path).map_err(|e| open_error(&path, e))?;
+            let before = db.size_on_disk()?;
+            let checksum = db.checksum()?;
+            if target.exists() {
+                fs::remove_dir_all(&target)?;
+            }
+            let compacted = sled::open(&target)?;
+            compacted.import(db.export());
+            compacted.flush()?;
+            if compacted.checksum()? != checksum {
+                drop(compacted);
+                fs::remove_dir_all(&target)?;
+                return Err(anyhow!(
+                    "compacted database differs from {}",
+                    path.display()
+                ));
+            }
+            (before, checksum)
+        };
+        let backup = DbWrapper::move_aside(&path, "old")?;
+        if let Err(e) = fs::rename(&target, &path) {
+            fs::rename(&backup, &path)?;
+            return Err(anyhow!("can not replace {}: {}", path.display(), e));
+        }
+        // the original is only deleted once the one in its place reads back the same
+        let after = sled::open(&path)
+            .map_err(|e| open_error(&path, e))
+            .and_then(|db| match db.checksum()? == checksum {
+                true => Ok(db.size_on_disk()?),
+                false => Err(anyhow!("compacted database differs from the original")),
+            });
+        match after {
+            Ok(after) => {
+                fs::remove_dir_all(&backup)?;
+                Ok((before, after))
+            }
+            Err(e) => {
+                fs::remove_dir_all(&path)?;
+                fs::rename(&backup, &path)?;
+                Err(e)
+            }
         }
     }
 
+    /// Bytes the database takes on disk.
+    pub fn size_on_disk(&self) -> Result<u64> {
+        Ok(self.db.size_on_disk()?)
+    }
+
+    /// Checksum of every key and value, reading them all finds damaged pages.
+    pub fn checksum(&self) -> Result<u32> {
+        Ok(self.db.checksum()?)
+    }
+
+    /// Whether the last shutdown was unclean and sled recovered from its log.
+    pub fn was_recovered(&self) -> bool {
+        self.db.was_recovered()
+    }
+
+    /// Whether nothing was ever stored in the default keyspace.
+    pub fn is_empty(&self) -> bool {
+        self.db.is_empty()
+    }
+
+    pub fn remove(&self, key: &str) -> Result<()> {
+        self.db.remove(key)?;
+        Ok(())
+    }
+
+    /// Encrypt `Sealed` values with `cipher`, without one they are stored as they are.
+    pub fn with_cipher(mut self, cipher: Cipher) -> DbWrapper {
+        self.cipher = Some(cipher);
+        self
+    }
+
     pub fn get<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
         self.db
             .get(key)?
@@ -29,9 +223,90 @@ impl DbWrapper {
             .transpose()
     }
 
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
     pub fn set<T: Serialize>(&self, key: &str, data: &T) {
         self.db
             .insert(key, serde_json::to_string(&data).unwrap().as_bytes())
             .unwrap();
     }
+
+    /// Read a value and decrypt its secrets,
+    /// secrets stored before encryption was enabled are encrypted on the way.
+    pub fn get_sealed<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: Sealed + Serialize + Clone + for<'de> Deserialize<'de>,
+    {
+        let mut value = match self.get::<T>(key)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let mut plaintext = false;
+        for secret in value.secrets().into_iter().flatten() {
+            if !Cipher::is_sealed(secret) {
+                plaintext = true;
+                continue;
+            }
+            let cipher = self
+                .cipher
+                .as_ref()
+                .ok_or_else(|| anyhow!("{} is encrypted but no secret key is loaded", key))?;
+            *secret = cipher.open(secret).map_err(|e| anyhow!("{}: {}", key, e))?;
+        }
+        if plaintext && self.cipher.is_some() {
+            self.set_sealed(key, &value);
+        }
+        Ok(Some(value))
+    }
+
+    /// A copy of a value with its secrets encrypted, as `set_sealed` stores it.
+    pub fn seal<T: Sealed + Clone>(&self, data: &T) -> T {
+        let mut sealed = data.clone();
+        if let Some(cipher) = &self.cipher {
+            for secret in sealed.secrets().into_iter().flatten() {
+                *secret = cipher.seal(secret);
+            }
+        }
+        sealed
+    }
+
+    /// Write a value with its secrets encrypted.
+    pub fn set_sealed<T: Sealed + Serialize + Clone>(&self, key: &str, data: &T) {
+        self.set(key, &self.seal(data));
+    }
+
+    /// Write or remove several values at once, either all of them change or none.
+    pub fn set_batch(&self, values: &[(&str, Option<Value>)]) -> Result<()> {
+        let mut batch = Batch::default();
+        for (key, value) in values {
+            match value {
+                Some(value) => batch.insert(*key, serde_json::to_string(value)?.as_bytes()),
+                None => batch.remove(*key),
+            }
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub fn get_secret(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.get_sealed::<Option<String>>(key)?.flatten())
+    }
+
+    pub fn set_secret(&self, key: &str, secret: &str) {
+        self.set_sealed(key, &Some(secret.to_string()));
+    }
+
+    /// A separate keyspace, for records that don't fit under a single key.
+    pub fn open_tree(&self, name: &str) -> Result<Tree> {
+        Ok(self.db.open_tree(name)?)
+    }
+
+    /// A unique id that grows with every call, across restarts too.
+    pub fn generate_id(&self) -> Result<u64> {
+        Ok(self.db.generate_id()?)
+    }
 }
@@ -0,0 +1,98 @@
+use crate::config::app::AppConfig;
+use crate::config::constant::{APP_CONFIG, DEFAULT_ACCOUNT, SCHEMA_VERSION, SERVER_CONFIG};
+use crate::config::server::ServerConfig;
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{AccountStore, Role};
+use anyhow::{anyhow, Result};
+
+/// A step bringing the database to `version` from the one before it.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    run: fn(&DbWrapper) -> Result<()>,
+}
+
+/// Every migration in order, append new ones with the next version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "move the app token into the admin account",
+        run: app_token_to_account,
+    },
+    Migration {
+        version: 2,
+        description: "encrypt the stored server token",
+        run: seal_server_token,
+    },
+];
+
+/// The schema this build writes.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or_default()
+}
+
+/// The schema of the database, 0 for one written before it was versioned.
+pub fn schema_version(db: &DbWrapper) -> Result<u32> {
+    Ok(db
+        .get::<u32>(SCHEMA_VERSION)
+        .map_err(|e| anyhow!("{}: {}", SCHEMA_VERSION, e))?
+        .unwrap_or_default())
+}
+
+/// Migrations not applied to the database yet.
+pub fn pending(db: &DbWrapper) -> Result<Vec<&'static Migration>> {
+    let version = schema_version(db)?;
+    if version > latest_version() {
+        return Err(anyhow!(
+            "database schema {} is newer than {} this serverbee-web supports, upgrade serverbee-web",
+            version,
+            latest_version()
+        ));
+    }
+    Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+}
+
+/// Apply the pending migrations in order, the version is stored after each
+/// so an interrupted run continues where it stopped.
+pub fn migrate(db: &DbWrapper) -> Result<Vec<&'static Migration>> {
+    if db.is_empty() {
+        // a new database starts at the latest schema
+        db.set(SCHEMA_VERSION, &latest_version());
+        db.flush()?;
+        return Ok(vec![]);
+    }
+    let pending = pending(db)?;
+    for migration in &pending {
+        (migration.run)(db).map_err(|e| {
+            anyhow!(
+                "migrating database to schema {} ({}): {}",
+                migration.version,
+                migration.description,
+                e
+            )
+        })?;
+        db.set(SCHEMA_VERSION, &migration.version);
+        db.flush()?;
+    }
+    Ok(pending)
+}
+
+fn app_token_to_account(db: &DbWrapper) -> Result<()> {
+    let app = match db.get_sealed::<AppConfig>(APP_CONFIG)? {
+        Some(app) => app,
+        None => return Ok(()),
+    };
+    if let Some(token) = app.token() {
+        AccountStore::new(db.clone()).upsert(DEFAULT_ACCOUNT, Role::Admin, Some(token))?;
+    }
+    db.remove(APP_CONFIG)
+}
+
+fn seal_server_token(db: &DbWrapper) -> Result<()> {
+    // reading reseals a plaintext token
+    if let Some(server) = db.get_sealed::<ServerConfig>(SERVER_CONFIG)? {
+        db.set_sealed(SERVER_CONFIG, &server);
+    }
+    Ok(())
+}
@@ -1 +1,4 @@
+pub mod cipher;
+pub mod db_command;
 pub mod db_wrapper;
+pub mod migration;
@@ -0,0 +1,305 @@
+use crate::config::exec::ExecConfig;
+use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::{server_env_names, Utf8Buffer};
+use actix::Message;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+use tokio_util::sync::CancellationToken;
+
+const READ_BUFFER_SIZE: usize = 8192;
+
+/// Body of `POST /exec`, and the first message sent on `/exec/ws`.
+/// Either `script` names an allowlisted script or `command` is free-form shell input.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ExecRequest {
+    #[serde(default)]
+    pub script: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Seconds before the command is killed.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl ExecRequest {
+    /// The script name, or the command when it is free-form.
+    pub fn target(&self) -> String {
+        self.script
+            .clone()
+            .or_else(|| self.command.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ExecOutput {
+    /// None when the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    /// Whether stdout or stderr was cut at `max_output`.
+    pub truncated: bool,
+    /// Milliseconds the command ran.
+    pub duration: u64,
+}
+
+impl JsonResponder for ExecOutput {}
+
+/// A streamed piece of a running command, sent as JSON text frames on `/exec/ws`.
+#[derive(Message, Serialize, Debug, Clone, PartialEq)]
+#[rtype(result = "()")]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ExecEvent {
+    Stdout {
+        data: String,
+    },
+    Stderr {
+        data: String,
+    },
+    Exit {
+        exit_code: Option<i32>,
+        timed_out: bool,
+        duration: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecStream {
+    Stdout,
+    Stderr,
+}
+
+struct ExecExit {
+    status: Option<ExitStatus>,
+    timed_out: bool,
+    duration: u64,
+}
+
+/// Build the command for a request, rejecting unknown scripts
+/// and free-form input unless the config allows it.
+/// `env` and `cwd` of the request only apply to free-form input, a script runs as configured.
+pub fn prepare(config: &ExecConfig, request: &ExecRequest) -> Result<Command> {
+    let mut cmd = match (&request.script, &request.command) {
+        (Some(name), _) => {
+            let script = config
+                .script(name)
+                .ok_or_else(|| anyhow!("脚本 {} 不存在", name))?;
+            if !request.env.is_empty() || request.cwd.is_some() {
+                return Err(anyhow!("脚本 {} 不接受 env 或 cwd", name));
+            }
+            let mut cmd = Command::new(&script.command);
+            cmd.args(&script.args).envs(&script.env);
+            if let Some(cwd) = &script.cwd {
+                cmd.current_dir(cwd);
+            }
+            info!("Exec script: {}", name);
+            cmd
+        }
+        (None, Some(command)) => {
+            if !config.allow_free_form() {
+                return Err(anyhow!("未开启自由命令执行"));
+            }
+            info!("Exec command: {}", command);
+            let mut cmd = shell_command(command);
+            cmd.envs(&request.env);
+            if let Some(cwd) = &request.cwd {
+                cmd.current_dir(cwd);
+            }
+            cmd
+        }
+        (None, None) => return Err(anyhow!("缺少 script 或 command")),
+    };
+
+    for name in server_env_names() {
+        cmd.env_remove(name);
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // own process group, so a timeout also takes down whatever the command started
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setpgid(0, 0);
+            Ok(())
+        });
+    }
+    Ok(cmd)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd.exe");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Run a command to completion, keeping at most `max_output` bytes of each stream.
+pub async fn run(cmd: Command, timeout: Duration, max_output: usize) -> Result<ExecOutput> {
+    let mut stdout = vec![];
+    let mut stderr = vec![];
+    let mut truncated = false;
+    let exit = execute(cmd, timeout, CancellationToken::new(), |stream, data| {
+        let buf = match stream {
+            ExecStream::Stdout => &mut stdout,
+            ExecStream::Stderr => &mut stderr,
+        };
+        let room = max_output.saturating_sub(buf.len());
+        if data.len() > room {
+            truncated = true;
+        }
+        buf.extend_from_slice(&data[..data.len().min(room)]);
+    })
+    .await?;
+
+    Ok(ExecOutput {
+        exit_code: exit.status.and_then(|s| s.code()),
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        timed_out: exit.timed_out,
+        truncated,
+        duration: exit.duration,
+    })
+}
+
+/// Run a command and forward its output as it arrives, ending with an `Exit` event.
+/// Cancelling the token kills the command.
+pub async fn stream<F>(cmd: Command, timeout: Duration, cancel: CancellationToken, mut on_event: F)
+where
+    F: FnMut(ExecEvent),
+{
+    let mut stdout = Utf8Buffer::default();
+    let mut stderr = Utf8Buffer::default();
+    let result = execute(cmd, timeout, cancel, |stream, data| match stream {
+        ExecStream::Stdout => {
+            if let Some(data) = stdout.push(data) {
+                on_event(ExecEvent::Stdout { data })
+            }
+        }
+        ExecStream::Stderr => {
+            if let Some(data) = stderr.push(data) {
+                on_event(ExecEvent::Stderr { data })
+            }
+        }
+    })
+    .await;
+    if let Some(data) = stdout.finish() {
+        on_event(ExecEvent::Stdout { data });
+    }
+    if let Some(data) = stderr.finish() {
+        on_event(ExecEvent::Stderr { data });
+    }
+
+    on_event(match result {
+        Ok(exit) => ExecEvent::Exit {
+            exit_code: exit.status.and_then(|s| s.code()),
+            timed_out: exit.timed_out,
+            duration: exit.duration,
+        },
+        Err(e) => ExecEvent::Error {
+            message: e.to_string(),
+        },
+    });
+}
+
+async fn execute<F>(
+    mut cmd: Command,
+    timeout: Duration,
+    cancel: CancellationToken,
+    mut on_output: F,
+) -> Result<ExecExit>
+where
+    F: FnMut(ExecStream, &[u8]),
+{
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(|e| anyhow!("启动命令失败: {}", e))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("take stdout failed"))?;
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow!("take stderr failed"))?;
+
+    let mut out_buf = [0u8; READ_BUFFER_SIZE];
+    let mut err_buf = [0u8; READ_BUFFER_SIZE];
+    let mut out_open = true;
+    let mut err_open = true;
+    let mut status = None;
+    let mut timed_out = false;
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+
+    while out_open || err_open || status.is_none() {
+        tokio::select! {
+            read = stdout.read(&mut out_buf), if out_open => match read {
+                Ok(n) if n > 0 => on_output(ExecStream::Stdout, &out_buf[..n]),
+                _ => out_open = false,
+            },
+            read = stderr.read(&mut err_buf), if err_open => match read {
+                Ok(n) if n > 0 => on_output(ExecStream::Stderr, &err_buf[..n]),
+                _ => err_open = false,
+            },
+            exited = child.wait(), if status.is_none() => {
+                status = Some(exited?);
+            },
+            _ = &mut deadline => {
+                // a background process may still hold the pipes after the command exited
+                if status.is_none() {
+                    warn!("Exec command timed out after {}s", timeout.as_secs());
+                    timed_out = true;
+                    status = kill(&mut child).await;
+                }
+                break;
+            },
+            _ = cancel.cancelled() => {
+                if status.is_none() {
+                    status = kill(&mut child).await;
+                }
+                break;
+            },
+        }
+    }
+
+    Ok(ExecExit {
+        status,
+        timed_out,
+        duration: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn kill(child: &mut Child) -> Option<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+    }
+    if let Err(e) = child.kill().await {
+        warn!("kill exec child error: {}", e);
+    }
+    child.wait().await.ok()
+}
@@ -0,0 +1,64 @@
+use crate::config::config::Config;
+use crate::handler::result::HttpResult;
+use crate::token::account::Role;
+use crate::traits::json_response::JsonResponse;
+use crate::vo::account::{AccountTokenVo, AccountVo};
+use crate::vo::formator::Convert;
+use actix_web::{web, Responder};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AccountInfo {
+    pub name: String,
+    pub role: Role,
+    /// Generated when not given.
+    pub token: Option<String>,
+}
+
+pub async fn list_accounts_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let accounts: Vec<AccountVo> = config
+        .read()
+        .unwrap()
+        .accounts()
+        .list()
+        .iter()
+        .map(|a| a.convert())
+        .collect();
+    JsonResponse(HttpResult::success(Some(accounts)))
+}
+
+pub async fn save_account_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: web::Json<AccountInfo>,
+) -> impl Responder {
+    let info = info.into_inner();
+    warn!("Save account: {} with role {}", info.name, info.role);
+    let config = config.write().unwrap();
+    match config.accounts().upsert(&info.name, info.role, info.token) {
+        Ok(token) => {
+            config.sessions().revoke_account(&info.name);
+            JsonResponse(HttpResult::success(Some(AccountTokenVo {
+                name: info.name,
+                role: info.role,
+                token,
+            })))
+        }
+        Err(e) => JsonResponse(HttpResult::<AccountTokenVo>::error(e.to_string())),
+    }
+}
+
+pub async fn remove_account_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    name: web::Path<String>,
+) -> impl Responder {
+    warn!("Remove account: {}", name);
+    let config = config.write().unwrap();
+    if config.accounts().remove(&name) {
+        config.sessions().revoke_account(&name);
+        JsonResponse(HttpResult::<()>::new(true))
+    } else {
+        JsonResponse(HttpResult::new_msg(false, "账户不存在".into()))
+    }
+}
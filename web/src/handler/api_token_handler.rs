@@ -0,0 +1,71 @@
+use crate::config::config::Config;
+use crate::handler::result::HttpResult;
+use crate::token::scope::Scope;
+use crate::traits::json_response::JsonResponse;
+use crate::utils::common_util::get_now_timestamp;
+use crate::vo::api_token::{ApiTokenVo, CreatedApiTokenVo};
+use crate::vo::formator::Convert;
+use actix_web::{web, Responder};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ApiTokenInfo {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Seconds the token is valid for, never expires when neither this nor `expires_at` is set.
+    pub ttl: Option<u64>,
+    /// Unix timestamp the token expires at.
+    pub expires_at: Option<u64>,
+}
+
+pub async fn list_api_tokens_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let tokens: Vec<ApiTokenVo> = config
+        .read()
+        .unwrap()
+        .api_tokens()
+        .list()
+        .iter()
+        .map(|t| t.convert())
+        .collect();
+    JsonResponse(HttpResult::success(Some(tokens)))
+}
+
+pub async fn create_api_token_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: web::Json<ApiTokenInfo>,
+) -> impl Responder {
+    let info = info.into_inner();
+    warn!(
+        "Create API token: {} with scopes {:?}",
+        info.name, info.scopes
+    );
+    let expires_at = info
+        .expires_at
+        .or_else(|| info.ttl.map(|ttl| get_now_timestamp() + ttl));
+    let config = config.write().unwrap();
+    match config
+        .api_tokens()
+        .create(&info.name, info.scopes, expires_at)
+    {
+        Ok((api_token, token)) => JsonResponse(HttpResult::success(Some(CreatedApiTokenVo {
+            token,
+            info: api_token.convert(),
+        }))),
+        Err(e) => JsonResponse(HttpResult::<CreatedApiTokenVo>::error(e.to_string())),
+    }
+}
+
+pub async fn revoke_api_token_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    id: web::Path<String>,
+) -> impl Responder {
+    warn!("Revoke API token: {}", id);
+    let config = config.write().unwrap();
+    if config.api_tokens().revoke(&id) {
+        JsonResponse(HttpResult::<()>::new(true))
+    } else {
+        JsonResponse(HttpResult::new_msg(false, "Token 不存在或已撤销".into()))
+    }
+}
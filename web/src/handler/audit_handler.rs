@@ -0,0 +1,37 @@
+use crate::audit::audit_log::AuditFilter;
+use crate::config::config::Config;
+use crate::handler::result::HttpResult;
+use crate::traits::json_response::JsonResponse;
+use crate::vo::audit::AuditEntryVo;
+use crate::vo::formator::Convert;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, Responder};
+use std::sync::{Arc, RwLock};
+
+pub async fn list_audit_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    let audit_log = config.read().unwrap().audit_log();
+    let entries: Vec<AuditEntryVo> = audit_log
+        .query(&filter)
+        .iter()
+        .map(|entry| entry.convert())
+        .collect();
+    JsonResponse(HttpResult::success(Some(entries)))
+}
+
+/// The matching entries as JSON lines, oldest first.
+pub async fn export_audit_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    let audit_log = config.read().unwrap().audit_log();
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("audit.jsonl".to_string())],
+        })
+        .body(audit_log.export(&filter))
+}
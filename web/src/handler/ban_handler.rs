@@ -0,0 +1,32 @@
+use crate::config::config::Config;
+use crate::handler::result::HttpResult;
+use crate::traits::json_response::JsonResponse;
+use crate::utils::common_util::get_now_timestamp;
+use crate::vo::ban::BanVo;
+use crate::vo::formator::Convert;
+use actix_web::{web, Responder};
+use log::info;
+use std::sync::{Arc, RwLock};
+
+pub async fn list_bans_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let lockout = config.read().unwrap().lockout();
+    let bans: Vec<BanVo> = lockout
+        .bans(get_now_timestamp())
+        .iter()
+        .map(|ban| ban.convert())
+        .collect();
+    JsonResponse(HttpResult::success(Some(bans)))
+}
+
+pub async fn unban_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    ip: web::Path<String>,
+) -> impl Responder {
+    let lockout = config.read().unwrap().lockout();
+    if lockout.unban(&ip) {
+        info!("{} unbanned", ip);
+        JsonResponse(HttpResult::<()>::new(true))
+    } else {
+        JsonResponse(HttpResult::new_msg(false, "该 IP 未被封禁".into()))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::config::config::Config;
 use crate::config::server::ServerConfig;
 use crate::handler::http_handler::TokenInfo;
+use crate::utils::common_util::mask_token;
 use actix_web::{web, HttpResponse, Responder};
 use log::warn;
 use serde_json::Value;
@@ -27,7 +28,7 @@ pub async fn view_client_token(config: web::Data<Arc<RwLock<Config>>>) -> impl R
     match config.read() {
         Ok(c) => {
             let token = c.server_token();
-            token.unwrap_or("".into())
+            token.as_deref().map(mask_token).unwrap_or_default()
         }
         Err(_) => "".into(),
     }
@@ -37,7 +38,7 @@ pub async fn view_client_token(config: web::Data<Arc<RwLock<Config>>>) -> impl R
 pub async fn view_client_config(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
     warn!("Local Event: view_client_token");
     match config.read() {
-        Ok(c) => HttpResponse::Ok().json(c.server_token()),
+        Ok(c) => HttpResponse::Ok().json(c.server_token().as_deref().map(mask_token)),
         Err(_) => HttpResponse::Forbidden().json(Value::Object(Default::default())),
     }
 }
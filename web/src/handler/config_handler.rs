@@ -1,15 +1,33 @@
+use crate::config::access::AccessConfig;
 use crate::config::app::AppConfig;
-use actix_web::{web, Responder};
+use crate::config::audit::AuditConfig;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+use serde::Deserialize;
 use std::sync::{Arc, RwLock};
 
 use crate::config::config::Config;
+use crate::config::document::{ConfigDocument, ConfigFormat};
+use crate::config::exec::ExecConfig;
+use crate::config::rate_limit::RateLimitConfig;
 use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
 use crate::config::web_server::WebServerConfig;
 use crate::handler::result::HttpResult;
+use crate::rebind::Rebinder;
 use crate::traits::json_response::JsonResponse;
+use crate::vo::config::access::AccessConfigVo;
 use crate::vo::config::app::AppConfigVo;
+use crate::vo::config::audit::AuditConfigVo;
 use crate::vo::config::config::ConfigVo;
+use crate::vo::config::effective::EffectiveConfigVo;
+use crate::vo::config::exec::ExecConfigVo;
+use crate::vo::config::rate_limit::RateLimitConfigVo;
 use crate::vo::config::server::ServerConfigVo;
+use crate::vo::config::terminal::TerminalConfigVo;
+use crate::vo::config::tls::TlsConfigVo;
 use crate::vo::config::web_server::WebServerConfigVo;
 use crate::vo::formator::Convert;
 
@@ -18,6 +36,14 @@ pub async fn get_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl
     JsonResponse(HttpResult::<ConfigVo>::success(Some(config)))
 }
 
+/// Every setting with the layer it came from: default, database, file, env or cli.
+pub async fn get_effective_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    let effective = config.read().unwrap().effective_config().convert();
+    JsonResponse(HttpResult::<EffectiveConfigVo>::success(Some(effective)))
+}
+
 pub async fn get_server_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
     let config = config.read().unwrap().clone().convert();
     JsonResponse(HttpResult::<ServerConfigVo>::success(Some(config.server)))
@@ -63,15 +89,190 @@ pub async fn get_web_server_config_handler(
     )))
 }
 
+/// Move the HTTP server to the new settings before saving them,
+/// they aren't saved when the server can't listen on them.
 pub async fn set_web_server_config_handler(
     config: web::Data<Arc<RwLock<Config>>>,
+    rebinder: web::Data<Rebinder>,
     web_server_config: web::Json<WebServerConfig>,
+) -> impl Responder {
+    let web_server = web_server_config.into_inner();
+    if let Err(e) = web_server.validate() {
+        return JsonResponse(HttpResult::<()>::error(e.to_string()));
+    }
+    if config.read().unwrap().web_server_config() == web_server {
+        return JsonResponse(HttpResult::success(None));
+    }
+    if let Err(e) = rebinder.rebind(web_server.clone()).await {
+        return JsonResponse(HttpResult::<()>::error(e.to_string()));
+    }
+    match config.write().unwrap().set_web_server_config(web_server) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn get_terminal_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<TerminalConfigVo>::success(Some(
+        config.terminal,
+    )))
+}
+
+pub async fn set_terminal_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    terminal_config: web::Json<TerminalConfig>,
 ) -> impl Responder {
     let mut config = config.write().unwrap();
-    let mut web_server = config.web_server_config();
-    web_server.merge(web_server_config.into_inner());
-    match config.set_web_server_config(web_server) {
+    match config.set_terminal_config(terminal_config.into_inner()) {
         Ok(_) => JsonResponse(HttpResult::success(None)),
         Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
     }
 }
+
+pub async fn get_exec_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<ExecConfigVo>::success(Some(config.exec)))
+}
+
+pub async fn set_exec_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    exec_config: web::Json<ExecConfig>,
+) -> impl Responder {
+    let mut config = config.write().unwrap();
+    match config.set_exec_config(exec_config.into_inner()) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn get_tls_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<TlsConfigVo>::success(Some(config.tls)))
+}
+
+pub async fn set_tls_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    tls_config: web::Json<TlsConfig>,
+) -> impl Responder {
+    let mut config = config.write().unwrap();
+    match config.set_tls_config(tls_config.into_inner()) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn get_rate_limit_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<RateLimitConfigVo>::success(Some(
+        config.rate_limit,
+    )))
+}
+
+pub async fn set_rate_limit_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    rate_limit_config: web::Json<RateLimitConfig>,
+) -> impl Responder {
+    let mut config = config.write().unwrap();
+    match config.set_rate_limit_config(rate_limit_config.into_inner()) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn get_access_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<AccessConfigVo>::success(Some(config.access)))
+}
+
+pub async fn set_access_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    access_config: web::Json<AccessConfig>,
+) -> impl Responder {
+    let mut config = config.write().unwrap();
+    match config.set_access_config(access_config.into_inner()) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn get_audit_config_handler(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let config = config.read().unwrap().clone().convert();
+    JsonResponse(HttpResult::<AuditConfigVo>::success(Some(config.audit)))
+}
+
+pub async fn set_audit_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    audit_config: web::Json<AuditConfig>,
+) -> impl Responder {
+    let mut config = config.write().unwrap();
+    match config.set_audit_config(audit_config.into_inner()) {
+        Ok(_) => JsonResponse(HttpResult::success(None)),
+        Err(e) => JsonResponse(HttpResult::<()>::error(e.to_string())),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExportQuery {
+    /// `json` unless `yaml` is asked for.
+    pub format: Option<ConfigFormat>,
+    #[serde(default)]
+    pub redact: bool,
+}
+
+/// The whole configuration as a versioned document, for `import` on another host.
+pub async fn export_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    let format = query.format.unwrap_or(ConfigFormat::Json);
+    let document = config.read().unwrap().export_config(query.redact);
+    match document.format(format) {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "serverbee-config.{}",
+                    format.extension()
+                ))],
+            })
+            .body(body),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Validate a YAML or JSON document from `export` and apply it, nothing changes when it
+/// is invalid. It is stored in one write, and put back when the web server can't move to it.
+pub async fn import_config_handler(
+    config: web::Data<Arc<RwLock<Config>>>,
+    rebinder: web::Data<Rebinder>,
+    body: String,
+) -> impl Responder {
+    let import = ConfigDocument::parse(&body).and_then(|document| {
+        let sections = document.sections()?;
+        config
+            .read()
+            .unwrap()
+            .stage_import(sections, document.credentials)
+    });
+    let import = match import {
+        Ok(import) => import,
+        Err(e) => return JsonResponse(HttpResult::<()>::error(e.to_string())),
+    };
+    if let Err(e) = config.read().unwrap().store_import(&import) {
+        return JsonResponse(HttpResult::<()>::error(e.to_string()));
+    }
+    if let Some(web_server) = import.rebind.clone() {
+        if let Err(e) = rebinder.rebind(web_server).await {
+            if let Err(undo) = config.read().unwrap().undo_import(&import) {
+                error!("Error undoing config import: {}", undo);
+            }
+            return JsonResponse(HttpResult::<()>::error(e.to_string()));
+        }
+    }
+    config.write().unwrap().apply_import(import);
+    JsonResponse(HttpResult::success(None))
+}
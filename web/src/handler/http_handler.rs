@@ -1,8 +1,11 @@
+use crate::audit::set_target;
 use crate::config::config::Config;
 use crate::handler::result::HttpResult;
-use crate::token::communication_token::CommunicationToken;
+use crate::token::communication_token::{
+    AccountsToken, CommunicationToken, PrincipalKind, ProcessToken,
+};
 use crate::traits::json_response::JsonResponse;
-use actix_web::{post, web, HttpResponse, Responder};
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
 use log::warn;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
@@ -19,9 +22,11 @@ pub async fn version() -> impl Responder {
 
 #[post("/kill")]
 pub async fn kill_process(
-    _token: CommunicationToken,
+    _token: ProcessToken,
+    req: HttpRequest,
     info: web::Json<KilledInfo>,
 ) -> impl Responder {
+    set_target(&req, info.pid.clone());
     let pid: Pid = info.pid.parse().unwrap();
     let mut sys = System::new();
     let refresh_res = sys.refresh_process(pid);
@@ -40,35 +45,56 @@ pub struct TokenInfo {
     pub token: String,
 }
 
+/// Replace the token of the calling account
 #[post("/token/rest")]
 pub async fn rest_token(
-    _token: CommunicationToken,
+    token: AccountsToken,
     config: web::Data<Arc<RwLock<Config>>>,
     info: web::Json<TokenInfo>,
 ) -> impl Responder {
-    rest_token_local(config, info).await
-}
-
-pub async fn check_token(_token: CommunicationToken) -> impl Responder {
-    HttpResponse::Ok().finish()
-}
-
-/// private api localhost only
-// /local/token/view
-pub async fn view_token(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
-    warn!("Local Event: view_token");
-    return match config.read() {
-        Ok(guard) => {
-            let token = guard.app_token();
-            token.unwrap_or_default()
+    let role = match token.kind {
+        PrincipalKind::Account(role) => role,
+        PrincipalKind::ApiToken(_) => {
+            return JsonResponse(HttpResult::new_msg(
+                false,
+                "API Token 无法重置, 请重新创建".into(),
+            ))
+        }
+        PrincipalKind::Certificate(_) => {
+            return JsonResponse(HttpResult::new_msg(
+                false,
+                "客户端证书没有可重置的 Token".into(),
+            ))
         }
+    };
+    // an empty token would be replaced by a generated one nobody gets to see
+    if info.token.trim().is_empty() {
+        return JsonResponse(HttpResult::new_msg(false, "Token 不能为空".into()));
+    }
+    warn!("Account {} reset its token", token.name);
+    let accounts = match config.read() {
+        Ok(guard) => guard.accounts(),
         Err(e) => {
             warn!("Failed to acquire config read lock: {:?}", e);
-            "".into()
+            return JsonResponse(HttpResult::<()>::new(false));
         }
     };
+    match accounts.upsert(&token.name, role, Some(info.token.clone())) {
+        Ok(_) => {
+            if let Ok(guard) = config.read() {
+                guard.sessions().revoke_account(&token.name);
+            }
+            JsonResponse(HttpResult::new(true))
+        }
+        Err(e) => JsonResponse(HttpResult::error(e.to_string())),
+    }
+}
+
+pub async fn check_token(_token: CommunicationToken) -> impl Responder {
+    HttpResponse::Ok().finish()
 }
 
+/// private api localhost only
 // /local/token/clear
 pub async fn clear_token(config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
     warn!("Local Event: clear_token");
@@ -85,23 +111,3 @@ pub async fn clear_token(config: web::Data<Arc<RwLock<Config>>>) -> impl Respond
     };
     JsonResponse(HttpResult::<()>::new(res.is_ok()))
 }
-
-// /local/token/rest
-pub async fn rest_token_local(
-    config: web::Data<Arc<RwLock<Config>>>,
-    info: web::Json<TokenInfo>,
-) -> impl Responder {
-    warn!("Local Event: rest_token");
-
-    let res = match config.write() {
-        Ok(mut guard) => guard.set_app_token(Some(info.token.clone())),
-        Err(e) => {
-            warn!("Failed to acquire config write lock: {:?}", e);
-            Err(anyhow::anyhow!(
-                "Failed to acquire config write lock: {:?}",
-                e
-            ))
-        }
-    };
-    JsonResponse(HttpResult::<()>::new(res.is_ok()))
-}
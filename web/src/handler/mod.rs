@@ -1,3 +1,7 @@
+pub(crate) mod account_handler;
+pub(crate) mod api_token_handler;
+pub(crate) mod audit_handler;
+pub(crate) mod ban_handler;
 pub(crate) mod client_handler;
 pub(crate) mod config_handler;
 pub(crate) mod http_handler;
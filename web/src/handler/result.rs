@@ -45,4 +45,8 @@ impl<T: JsonResponder> HttpResult<T> {
         }
     }
 }
-impl<T: JsonResponder> JsonResponder for HttpResult<T> {}
+impl<T: JsonResponder> JsonResponder for HttpResult<T> {
+    fn failure(&self) -> Option<String> {
+        (!self.success).then(|| self.message.clone().unwrap_or_default())
+    }
+}
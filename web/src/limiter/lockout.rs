@@ -0,0 +1,106 @@
+use crate::config::rate_limit::RateLimitConfig;
+use log::warn;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Default)]
+struct Record {
+    /// Timestamps of failures still inside the window.
+    failures: Vec<u64>,
+    /// How many times the IP was banned, makes every ban twice as long as the last.
+    bans: u32,
+    banned_until: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ban {
+    pub ip: String,
+    pub bans: u32,
+    pub banned_until: u64,
+}
+
+/// Bans IPs that keep failing to authenticate, kept in memory only.
+#[derive(Debug, Clone, Default)]
+pub struct Lockout {
+    records: Arc<Mutex<HashMap<String, Record>>>,
+}
+
+impl Lockout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seconds left on the ban of `ip`, if any.
+    pub fn banned_for(&self, ip: &str, now: u64) -> Option<u64> {
+        let records = self.records.lock().unwrap();
+        records
+            .get(ip)
+            .filter(|record| record.banned_until > now)
+            .map(|record| record.banned_until - now)
+    }
+
+    /// Count a failed authentication, returns the ban in seconds when this one triggered it.
+    pub fn record_failure(&self, ip: &str, config: &RateLimitConfig, now: u64) -> Option<u64> {
+        if config.max_auth_failures() == 0 {
+            return None;
+        }
+        let mut records = self.records.lock().unwrap();
+        Lockout::prune(&mut records, config, now);
+        let record = records.entry(ip.to_string()).or_default();
+        let window_start = now.saturating_sub(config.failure_window());
+        record.failures.retain(|at| *at > window_start);
+        record.failures.push(now);
+        if (record.failures.len() as u32) < config.max_auth_failures() {
+            return None;
+        }
+        record.failures.clear();
+        record.bans += 1;
+        let duration = config.ban_duration_for(record.bans);
+        record.banned_until = now + duration;
+        warn!(
+            "{} banned for {} seconds after repeated authentication failures",
+            ip, duration
+        );
+        Some(duration)
+    }
+
+    /// A successful authentication forgets the failures of `ip`. Its earlier bans still
+    /// lengthen the next one, knowing one token must not reset the escalation.
+    pub fn record_success(&self, ip: &str) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(record) = records.get_mut(ip) {
+            record.failures.clear();
+        }
+    }
+
+    /// Currently banned IPs, the longest ban first.
+    pub fn bans(&self, now: u64) -> Vec<Ban> {
+        let records = self.records.lock().unwrap();
+        let mut bans: Vec<Ban> = records
+            .iter()
+            .filter(|(_, record)| record.banned_until > now)
+            .map(|(ip, record)| Ban {
+                ip: ip.clone(),
+                bans: record.bans,
+                banned_until: record.banned_until,
+            })
+            .collect();
+        bans.sort_by_key(|ban| std::cmp::Reverse(ban.banned_until));
+        bans
+    }
+
+    /// Lift the ban of `ip` and forget its history, returns false if it was not known.
+    pub fn unban(&self, ip: &str) -> bool {
+        self.records.lock().unwrap().remove(ip).is_some()
+    }
+
+    /// Drop records that can no longer ban or lengthen a ban.
+    fn prune(records: &mut HashMap<String, Record>, config: &RateLimitConfig, now: u64) {
+        let window_start = now.saturating_sub(config.failure_window());
+        let forget_before = now.saturating_sub(config.max_ban_duration());
+        records.retain(|_, record| {
+            record.banned_until > forget_before
+                || record.failures.iter().any(|at| *at > window_start)
+        });
+    }
+}
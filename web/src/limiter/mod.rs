@@ -0,0 +1,40 @@
+pub mod lockout;
+pub mod rate_limiter;
+
+use crate::access::client_ip;
+use crate::config::config::Config;
+use actix_web::web::Data;
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use log::warn;
+use std::sync::{Arc, RwLock};
+
+/// Counts a request against the heavy request limit of its IP,
+/// rejecting it with 429 once the limit is reached.
+#[derive(Debug)]
+pub struct HeavyRequest {
+    pub ip: String,
+}
+
+impl FromRequest for HeavyRequest {
+    type Error = actix_web::Error;
+    type Future = futures_util::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let ip = client_ip(req);
+        let allowed = match req.app_data::<Data<Arc<RwLock<Config>>>>() {
+            Some(config) => {
+                let config = config.read().unwrap();
+                config
+                    .rate_limiter()
+                    .allow(&ip, config.rate_limit_config().heavy_requests_per_minute())
+            }
+            None => true,
+        };
+        futures_util::future::ready(if allowed {
+            Ok(HeavyRequest { ip })
+        } else {
+            warn!("Too many requests to {}, request from: {}", req.path(), ip);
+            Err(actix_web::error::ErrorTooManyRequests("Too many requests"))
+        })
+    }
+}
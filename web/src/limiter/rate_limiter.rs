@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Buckets kept before full ones are dropped.
+pub(crate) const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per IP, refilled evenly over a minute.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a token for `ip`, `per_minute` of 0 always allows.
+    pub fn allow(&self, ip: &str, per_minute: u32) -> bool {
+        self.allow_at(ip, per_minute, Instant::now())
+    }
+
+    pub fn allow_at(&self, ip: &str, per_minute: u32, now: Instant) -> bool {
+        if per_minute == 0 {
+            return true;
+        }
+        let capacity = per_minute as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(ip) {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * capacity / 60.0
+                    < capacity
+            });
+            // none was full, make room by dropping the one left alone the longest
+            while buckets.len() >= MAX_BUCKETS {
+                let oldest = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.updated)
+                    .map(|(ip, _)| ip.clone());
+                match oldest {
+                    Some(oldest) => buckets.remove(&oldest),
+                    None => break,
+                };
+            }
+        }
+        let bucket = buckets.entry(ip.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity / 60.0).min(capacity);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
@@ -1,34 +1,61 @@
 #![cfg_attr(feature = "subsystem", windows_subsystem = "windows")]
 
-use cli::Args;
+use cli::{Args, Command};
 use std::net::TcpListener;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
 use crate::config::config::Config;
+use crate::config::config_command::run_config_command;
+use crate::config::web_server::WebServerConfig;
+use crate::db::db_command::run_db_command;
 use crate::handler::http_handler::{check_token, kill_process, rest_token, version};
+use crate::pty::session_manager::PtySessionManager;
+use crate::rebind::{listen_addrs, Listeners, Rebinder};
 
 use crate::report::reporter::Reporter;
+use crate::route::account_route::account_service;
+use crate::route::audit_route::audit_service;
+use crate::route::auth_route::auth_service;
 use crate::route::config_route::config_services;
+use crate::route::exec_route::exec_service;
 use crate::route::local_route::local_services;
 use crate::route::page_route::page_services;
 use crate::route::pty_route::pty_service;
 use crate::server::echo_ws;
-use actix_web::{middleware, web, App, HttpServer};
+use crate::service::service_command::run_service_command;
+use crate::shutdown::{Shutdown, SignalEvent, Signals};
+use crate::tls::cert_resolver::ReloadingCertResolver;
+use crate::tls::client_cert;
+use crate::tls::redirect::redirect_to_https;
+use crate::token::token_command::run_token_command;
+use actix_web::dev::{Server, ServerHandle, Service};
+use actix_web::{middleware, web, App, HttpResponse, HttpServer};
 use clap::Parser;
-use log::info;
+use futures_util::future::{ready, Either};
+use log::{error, info, warn};
 
+mod access;
+mod audit;
 mod cli;
 mod config;
 mod db;
+mod exec;
 mod handler;
+mod limiter;
 mod model;
 
 mod pty;
+mod rebind;
 mod report;
 mod route;
 mod server;
+mod service;
+mod shutdown;
 mod system_info;
 mod test;
+mod tls;
 mod token;
 mod traits;
 mod utils;
@@ -38,58 +65,359 @@ mod vo;
 async fn main() -> std::io::Result<()> {
     let args = Args::parse();
 
-    let config = Config::new(args);
+    if let Some(command) = args.command.clone() {
+        return run_command(command, &args);
+    }
 
-    let host = config.server_host().unwrap_or_else(|| String::from(""));
+    let config = Config::new(args.clone());
 
     let port = config.server_port();
 
+    let tls = config.tls_config();
+
+    let data_dir = config.data_dir();
+
+    if config.accounts().is_empty() {
+        warn!(
+            "No account configured, all requests will be rejected. Create one with `curl -X POST -H \"Content-Type: application/json\" -d '{{\"name\": \"admin\", \"role\": \"admin\"}}' http://127.0.0.1:{}/local/users` or start with --app-token",
+            port
+        );
+    }
+
     let config = Arc::new(RwLock::new(config));
 
-    let report_config = Arc::clone(&config);
+    let shutdown = Shutdown::new();
 
-    actix_rt::spawn(async {
-        Reporter::run(report_config).await;
-    });
+    let mut signals = Signals::new()?;
 
-    let is_dual_stack = is_ipv6_supported();
+    shutdown.spawn(Reporter::run(Arc::clone(&config), shutdown.clone()));
 
-    let bind_addr = if is_dual_stack {
-        format!("[::]:{}", port)
-    } else {
-        format!("0.0.0.0:{}", port)
-    };
+    let pty_sessions = Arc::new(PtySessionManager::new(Arc::clone(&config)));
+
+    shutdown.spawn_cancellable(PtySessionManager::run_reaper(Arc::clone(&pty_sessions)));
+
+    shutdown.spawn_cancellable(audit::run_pruner(Arc::clone(&config)));
+
+    let is_dual_stack = is_ipv6_supported();
 
     if is_dual_stack {
         info!("dual stack is supported");
-        info!("starting HTTP server at IPv4 http://localhost:{}", port);
-        info!("starting HTTP server at IPv6 http://[::1]:{}", port);
     } else {
         info!("System doesn't support dual stack");
-        info!("starting HTTP server at http://localhost:{}", port);
     }
 
-    HttpServer::new(move || {
-        App::new()
-            .app_data(web::Data::new(Arc::clone(&config)))
-            .app_data(web::JsonConfig::default().limit(4096))
-            .configure(config_services)
-            .configure(|cfg| local_services(cfg, &host))
-            .configure(pty_service)
-            .service(web::resource("/version").to(version))
-            .service(web::resource("/check").to(check_token))
-            .service(kill_process)
-            .service(rest_token)
-            // websocket route
-            .service(web::resource("/ws").route(web::get().to(echo_ws)))
-            .configure(page_services)
-            // enable logger
-            .wrap(middleware::Logger::default())
-    })
-    .workers(2)
-    .bind(bind_addr)?
-    .run()
-    .await
+    let tls_config = match tls.enabled() {
+        true => {
+            let (tls_config, resolver) = tls::server_config(&tls, &data_dir).map_err(|e| {
+                error!("Error configuring TLS: {}", e);
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+            })?;
+            shutdown.spawn_cancellable(ReloadingCertResolver::watch(
+                resolver,
+                tls.reload_interval(),
+            ));
+            Some(tls_config)
+        }
+        false => None,
+    };
+
+    let mut redirect_handle = None;
+    if let Some(redirect_port) = tls.redirect_port().filter(|_| tls.enabled()) {
+        let redirect_addr = if is_dual_stack {
+            format!("[::]:{}", redirect_port)
+        } else {
+            format!("0.0.0.0:{}", redirect_port)
+        };
+        info!(
+            "redirecting HTTP on port {} to HTTPS on port {}",
+            redirect_port, port
+        );
+        let redirect_config = Arc::clone(&config);
+        let redirect = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(Arc::clone(&redirect_config)))
+                .default_service(web::to(redirect_to_https))
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(redirect_addr)?
+        .run();
+        redirect_handle = Some(redirect.handle());
+        actix_rt::spawn(redirect);
+    }
+
+    let (rebinder, mut rebind_requests) = Rebinder::new();
+    let server = ServerFactory {
+        config,
+        pty_sessions,
+        rebinder,
+        shutdown,
+        tls_config,
+        dual_stack: is_dual_stack,
+    };
+
+    let web_server = server.config.read().unwrap().web_server_config();
+    let mut running = server
+        .run(&web_server)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    loop {
+        tokio::select! {
+            result = &mut running.task => return result.unwrap_or(Ok(())),
+            Some(request) = rebind_requests.recv() => {
+                let result = server.rebind(&mut running, &request.config);
+                let _ = request.reply.send(result);
+            }
+            signal = signals.recv() => match signal {
+                SignalEvent::Terminate(name) => {
+                    info!("Received {}, shutting down", name);
+                    break;
+                }
+                SignalEvent::Reload => {
+                    info!("Received SIGHUP, reloading");
+                    server.config.read().unwrap().reopen_logs();
+                    if let Err(e) = server.reload(&mut running, &args) {
+                        error!("Keeping the configuration as it was: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    let handles: Vec<ServerHandle> = std::iter::once(running.handle)
+        .chain(redirect_handle)
+        .collect();
+    server.shut_down(&handles).await;
+    Ok(())
+}
+
+/// The HTTP server in use and the sockets it listens on.
+struct RunningServer {
+    listeners: Listeners,
+    handle: ServerHandle,
+    task: JoinHandle<std::io::Result<()>>,
+}
+
+/// Builds the HTTP server, once at start and again for each rebind.
+struct ServerFactory {
+    config: Arc<RwLock<Config>>,
+    pty_sessions: Arc<PtySessionManager>,
+    rebinder: Rebinder,
+    shutdown: Shutdown,
+    tls_config: Option<rustls::ServerConfig>,
+    dual_stack: bool,
+}
+
+impl ServerFactory {
+    fn run(&self, web_server: &WebServerConfig) -> anyhow::Result<RunningServer> {
+        let (listeners, server) = self.start(web_server, &Listeners::default())?;
+        Ok(RunningServer {
+            listeners,
+            handle: server.handle(),
+            task: actix_rt::spawn(server),
+        })
+    }
+
+    /// Move to a server on `web_server`, keeping the running one when that fails.
+    fn rebind(
+        &self,
+        running: &mut RunningServer,
+        web_server: &WebServerConfig,
+    ) -> anyhow::Result<()> {
+        match self.start(web_server, &running.listeners) {
+            Ok((listeners, next)) => {
+                // the old server finishes its requests, this one included
+                let previous = std::mem::replace(&mut running.handle, next.handle());
+                actix_rt::spawn(previous.stop(true));
+                running.task = actix_rt::spawn(next);
+                running.listeners = listeners;
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Keeping the web server as it was: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Apply the config file and environment as they are now, for SIGHUP.
+    fn reload(&self, running: &mut RunningServer, args: &Args) -> anyhow::Result<()> {
+        let (layers, sections) = self.config.read().unwrap().reload_layers(args)?;
+        if let Some(web_server) = &sections.web_server {
+            if self.config.read().unwrap().web_server_config() != *web_server {
+                self.rebind(running, web_server)?;
+            }
+        }
+        self.config.write().unwrap().apply_layers(layers, sections)
+    }
+
+    /// Stop accepting connections, close the WebSockets and shells, let the background
+    /// tasks finish and flush the database, giving up on what is left after the deadline.
+    async fn shut_down(&self, servers: &[ServerHandle]) {
+        let timeout = self
+            .config
+            .read()
+            .unwrap()
+            .web_server_config()
+            .shutdown_timeout();
+        let graceful = async {
+            for server in servers {
+                server.pause().await;
+            }
+            self.shutdown.cancel();
+            self.pty_sessions.shutdown().await;
+            self.shutdown.join().await;
+            for server in servers {
+                server.stop(true).await;
+            }
+        };
+        if tokio::time::timeout(Duration::from_secs(timeout), graceful)
+            .await
+            .is_err()
+        {
+            warn!("Not stopped within {} seconds, stopping now", timeout);
+            for server in servers {
+                server.stop(false).await;
+            }
+        }
+        if let Err(e) = self.config.read().unwrap().flush() {
+            error!("Error flushing the database: {}", e);
+        }
+        info!("serverbee-web stopped");
+    }
+
+    /// Run a server with the settings of `web_server`, reusing the sockets in `current`
+    /// that are still listened on. The running server is untouched when this fails.
+    fn start(
+        &self,
+        web_server: &WebServerConfig,
+        current: &Listeners,
+    ) -> anyhow::Result<(Listeners, Server)> {
+        web_server.validate()?;
+        let addrs = listen_addrs(web_server, self.dual_stack)?;
+        let listeners = current.rebind(&addrs)?;
+
+        let config = Arc::clone(&self.config);
+        let pty_sessions = Arc::clone(&self.pty_sessions);
+        let rebinder = self.rebinder.clone();
+        let shutdown = self.shutdown.clone();
+        let json_limit = web_server.json_limit();
+        let mut server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(Arc::clone(&config)))
+                .app_data(web::Data::new(Arc::clone(&pty_sessions)))
+                .app_data(web::Data::new(rebinder.clone()))
+                .app_data(web::Data::new(shutdown.clone()))
+                .app_data(web::JsonConfig::default().limit(json_limit))
+                .configure(config_services)
+                .configure(local_services)
+                .configure(pty_service)
+                .configure(exec_service)
+                .configure(account_service)
+                .configure(auth_service)
+                .configure(audit_service)
+                .service(web::resource("/version").to(version))
+                .service(web::resource("/check").to(check_token))
+                .service(kill_process)
+                .service(rest_token)
+                // websocket route
+                .service(web::resource("/ws").route(web::get().to(echo_ws)))
+                .configure(page_services)
+                // refuse addresses outside the access rule of the route group
+                .wrap_fn(|req, srv| match access::denied(req.request()) {
+                    Some(reason) => Either::Left(ready(Ok(
+                        req.into_response(HttpResponse::Forbidden().body(reason))
+                    ))),
+                    None => Either::Right(srv.call(req)),
+                })
+                // record privileged actions, refused ones included
+                .wrap_fn(|req, srv| {
+                    let action = audit::action_of(req.method(), req.path());
+                    let res = srv.call(req);
+                    async move {
+                        let res = res.await?;
+                        if let Some((action, target)) = action {
+                            audit::record_response(&res, action, target);
+                        }
+                        Ok(res)
+                    }
+                })
+                // enable logger
+                .wrap(middleware::Logger::default())
+        })
+        .on_connect(client_cert::on_connect)
+        .workers(web_server.workers())
+        .shutdown_timeout(web_server.shutdown_timeout())
+        // signals are handled in `main` to shut everything down together
+        .disable_signals();
+
+        for socket in listeners.sockets()? {
+            server = match &self.tls_config {
+                Some(tls_config) => server.listen_rustls_0_21(socket, tls_config.clone())?,
+                None => server.listen(socket)?,
+            };
+        }
+
+        let scheme = if self.tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        for addr in listeners.addrs() {
+            info!(
+                "starting HTTP server at {}://{} with {} workers",
+                scheme,
+                addr,
+                web_server.workers()
+            );
+        }
+        Ok((listeners, server.run()))
+    }
+}
+
+fn run_command(command: Command, args: &Args) -> std::io::Result<()> {
+    match command {
+        Command::PtyExec { uid, gid, command } => {
+            #[cfg(unix)]
+            if let Err(e) = pty::privilege::exec_as(uid, gid, &command) {
+                eprintln!("serverbee-web pty-exec: {}", e);
+            }
+            #[cfg(not(unix))]
+            eprintln!(
+                "serverbee-web pty-exec: switching to uid {:?} gid {:?} is not supported, refusing to run {:?}",
+                uid, gid, command
+            );
+            std::process::exit(126);
+        }
+        Command::Token { action } => {
+            if let Err(e) = run_token_command(args, action) {
+                eprintln!("serverbee-web token: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Config { action } => {
+            if let Err(e) = run_config_command(args, action) {
+                eprintln!("serverbee-web config: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Db { action } => {
+            if let Err(e) = run_db_command(args, action) {
+                eprintln!("serverbee-web db: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Service { action } => {
+            if let Err(e) = run_service_command(args, action) {
+                eprintln!("serverbee-web service: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+    }
 }
 
 fn is_ipv6_supported() -> bool {
@@ -5,6 +5,7 @@ pub mod network;
 pub mod overview;
 pub mod process;
 pub mod realtime_status;
+pub mod recording;
 pub mod usage;
 pub mod user;
 pub mod simple_process;
@@ -0,0 +1,10 @@
+use crate::pty::recorder::RecordingHeader;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct Recording {
+    pub id: String,
+    pub size: u64,
+    pub modified: u64,
+    pub header: RecordingHeader,
+}
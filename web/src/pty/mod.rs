@@ -1,2 +1,9 @@
+#[cfg(unix)]
+pub mod privilege;
+pub mod profile;
 pub mod pty_manager;
+pub mod recorder;
+pub mod recording_store;
+pub mod session;
+pub mod session_manager;
 pub mod shell_type;
@@ -0,0 +1,81 @@
+use anyhow::{anyhow, Result};
+use std::ffi::{CStr, CString};
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+pub struct UserEntry {
+    pub name: String,
+    pub home: String,
+    pub gid: u32,
+}
+
+pub fn lookup_user(uid: u32) -> Option<UserEntry> {
+    unsafe {
+        let entry = libc::getpwuid(uid);
+        if entry.is_null() {
+            return None;
+        }
+        Some(UserEntry {
+            name: CStr::from_ptr((*entry).pw_name)
+                .to_string_lossy()
+                .into_owned(),
+            home: CStr::from_ptr((*entry).pw_dir)
+                .to_string_lossy()
+                .into_owned(),
+            gid: (*entry).pw_gid,
+        })
+    }
+}
+
+/// The group to switch to, refusing to keep root's when none is known for the user.
+pub fn target_gid(uid: Option<u32>, gid: Option<u32>, user: Option<&UserEntry>) -> Result<u32> {
+    gid.or(user.map(|u| u.gid)).ok_or_else(|| match uid {
+        Some(uid) => anyhow!("uid {} has no passwd entry, set gid in the profile", uid),
+        None => anyhow!("no uid or gid given"),
+    })
+}
+
+fn drop_privileges(uid: Option<u32>, gid: Option<u32>) -> Result<()> {
+    let user = uid.and_then(lookup_user);
+    let gid = target_gid(uid, gid, user.as_ref())?;
+    unsafe {
+        // the supplementary groups of root are always replaced
+        let groups_set = match &user {
+            Some(user) => {
+                let name = CString::new(user.name.clone())?;
+                libc::initgroups(name.as_ptr(), gid as _)
+            }
+            None => libc::setgroups(0, std::ptr::null()),
+        };
+        if groups_set != 0 {
+            return Err(anyhow!("setgroups: {}", std::io::Error::last_os_error()));
+        }
+        if libc::setgid(gid) != 0 {
+            return Err(anyhow!(
+                "setgid {}: {}",
+                gid,
+                std::io::Error::last_os_error()
+            ));
+        }
+        if let Some(uid) = uid {
+            if libc::setuid(uid) != 0 {
+                return Err(anyhow!(
+                    "setuid {}: {}",
+                    uid,
+                    std::io::Error::last_os_error()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Entry point of `serverbee-web pty-exec`: switch to the given user and
+/// replace this process with the terminal command. Only returns on failure.
+pub fn exec_as(uid: Option<u32>, gid: Option<u32>, command: &[String]) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("no command given"))?;
+    drop_privileges(uid, gid)?;
+    Err(Command::new(program).args(args).exec().into())
+}
@@ -0,0 +1,96 @@
+use crate::config::terminal::{TerminalConfig, TerminalProfile};
+use crate::pty::shell_type::ShellTypeExt;
+use crate::utils::common_util::server_env_names;
+use anyhow::{anyhow, Result};
+use portable_pty::CommandBuilder;
+
+/// Pick the profile for the `shell` query parameter: a configured profile by name,
+/// else the configured default profile. A built-in or installed shell is only picked
+/// when no profile is configured, a request never gets around the user of a profile.
+pub fn resolve_profile(config: &TerminalConfig, shell: Option<&str>) -> Result<TerminalProfile> {
+    if let Some(profile) = shell.and_then(|name| config.profile(name)) {
+        return Ok(profile);
+    }
+    if let Some(profile) = config
+        .default_profile()
+        .and_then(|name| config.profile(&name))
+    {
+        return Ok(profile);
+    }
+    if !config.profiles().is_empty() {
+        return Err(anyhow!(
+            "unknown terminal profile {}",
+            shell.unwrap_or_default()
+        ));
+    }
+    let shell_type = shell.map(|shell| shell.to_shell_type()).unwrap_or_default();
+    let name = shell.unwrap_or(shell_type.to_str());
+    Ok(TerminalProfile::new(name, shell_type.to_str()))
+}
+
+/// Build the command spawned in the pty for a profile.
+/// Switching user goes through `serverbee-web pty-exec` which drops privileges before exec.
+pub fn command_for(profile: &TerminalProfile) -> Result<CommandBuilder> {
+    let mut cmd = if profile.uid.is_some() || profile.gid.is_some() {
+        privileged_command(profile)?
+    } else {
+        let mut cmd = CommandBuilder::new(&profile.command);
+        cmd.args(&profile.args);
+        cmd
+    };
+    if let Some(cwd) = &profile.cwd {
+        cmd.cwd(cwd);
+    }
+    for name in server_env_names() {
+        cmd.env_remove(name);
+    }
+    for (key, value) in &profile.env {
+        cmd.env(key, value);
+    }
+    Ok(cmd)
+}
+
+/// Only root can switch to the user or group of a profile, say so before spawning.
+pub fn check_switch_user(profile: &TerminalProfile, euid: u32) -> Result<()> {
+    if euid != 0 {
+        return Err(anyhow!(
+            "profile {} sets uid/gid, serverbee-web must run as root to switch user (running as uid {})",
+            profile.name,
+            euid
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn privileged_command(profile: &TerminalProfile) -> Result<CommandBuilder> {
+    use crate::pty::privilege::lookup_user;
+
+    check_switch_user(profile, unsafe { libc::geteuid() })?;
+    let mut cmd = CommandBuilder::new(std::env::current_exe()?);
+    cmd.arg("pty-exec");
+    if let Some(uid) = profile.uid {
+        cmd.args(["--uid", &uid.to_string()]);
+        if let Some(user) = lookup_user(uid) {
+            cmd.env("HOME", &user.home);
+            cmd.env("USER", &user.name);
+            cmd.env("LOGNAME", &user.name);
+            cmd.cwd(&user.home);
+        }
+    }
+    if let Some(gid) = profile.gid {
+        cmd.args(["--gid", &gid.to_string()]);
+    }
+    cmd.arg("--");
+    cmd.arg(&profile.command);
+    cmd.args(&profile.args);
+    Ok(cmd)
+}
+
+#[cfg(not(unix))]
+fn privileged_command(profile: &TerminalProfile) -> Result<CommandBuilder> {
+    Err(anyhow::anyhow!(
+        "profile {} sets uid/gid which is only supported on unix",
+        profile.name
+    ))
+}
@@ -1,26 +1,42 @@
-use crate::pty::shell_type::ShellType;
+use crate::config::terminal::TerminalProfile;
+use crate::pty::profile::command_for;
 use actix::Message;
-use log::error;
-use portable_pty::{native_pty_system, CommandBuilder, PtyPair, PtySize};
-use std::thread::{sleep, spawn};
-use std::{
-    io::{BufRead, BufReader, Write},
-    time::Duration,
-};
+use anyhow::{anyhow, Result};
+use log::{debug, error, warn};
+use portable_pty::{native_pty_system, Child, ChildKiller, MasterPty, PtySize};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::spawn;
+use std::time::Duration;
+
+/// How long a shell gets to exit after SIGHUP before it is killed
+pub(crate) const KILL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+const READ_BUFFER_SIZE: usize = 8192;
 
 #[derive(Message, Debug, Eq, PartialEq)]
 #[rtype(result = "()")]
 pub enum PtyMessage {
     Buffer(Vec<u8>),
+    /// The shell exited by itself with this code.
+    Exit(u32),
+    /// The session was closed from the server side.
+    Closed,
 }
 
 pub struct PtyManager {
-    pty_pair: PtyPair,
+    master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    child: Option<Box<dyn Child + Send + Sync>>,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pid: Option<u32>,
+    exited: Arc<AtomicBool>,
 }
 
 impl PtyManager {
-    pub fn new(shell_type: ShellType) -> Self {
+    pub fn new(profile: &TerminalProfile) -> Result<Self> {
         let pty_system = native_pty_system();
 
         let pty_pair = pty_system
@@ -30,67 +46,141 @@ impl PtyManager {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .unwrap();
+            .map_err(|e| anyhow!("open pty failed: {}", e))?;
 
-        #[cfg(target_os = "windows")]
-        let cmd = CommandBuilder::new(shell_type.to_str());
-        #[cfg(not(target_os = "windows"))]
-        let cmd = CommandBuilder::new(shell_type.to_str());
+        let cmd = command_for(profile)?;
 
-        let mut child = if let Ok(child) = pty_pair.slave.spawn_command(cmd) {
-            child
-        } else {
-            panic!("spawn shell failed")
-        };
+        let child = pty_pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| anyhow!("spawn shell {} failed: {}", profile.command, e))?;
 
-        spawn(move || {
-            child.wait().unwrap();
-        });
+        // drop our handle on the slave side so reads hit EOF once the shell is gone
+        drop(pty_pair.slave);
 
-        let writer = pty_pair.master.take_writer().unwrap();
+        let writer = pty_pair
+            .master
+            .take_writer()
+            .map_err(|e| anyhow!("take pty writer failed: {}", e))?;
 
-        Self { pty_pair, writer }
+        Ok(Self {
+            master: pty_pair.master,
+            writer,
+            killer: child.clone_killer(),
+            pid: child.process_id(),
+            child: Some(child),
+            exited: Arc::new(AtomicBool::new(false)),
+        })
     }
 
-    pub fn start(&self) -> std::sync::mpsc::Receiver<PtyMessage> {
-        let (tx, rx) = std::sync::mpsc::channel::<PtyMessage>();
-
-        let reader = self.pty_pair.master.try_clone_reader().unwrap();
+    /// Spawn the single thread serving this pty: it forwards output until EOF,
+    /// then reaps the shell and reports its exit code.
+    pub fn start<F>(&mut self, mut on_message: F) -> Result<()>
+    where
+        F: FnMut(PtyMessage) + Send + 'static,
+    {
+        let mut child = self
+            .child
+            .take()
+            .ok_or_else(|| anyhow!("pty already started"))?;
+        let mut reader = self
+            .master
+            .try_clone_reader()
+            .map_err(|e| anyhow!("clone pty reader failed: {}", e))?;
+        let exited = Arc::clone(&self.exited);
 
         spawn(move || {
-            let mut buf = BufReader::new(reader);
+            let mut buf = [0u8; READ_BUFFER_SIZE];
             loop {
-                sleep(Duration::from_millis(1));
-                let data = buf.fill_buf().unwrap().to_vec();
-                buf.consume(data.len());
-                if data.len() > 0 {
-                    if let Err(e) = tx.send(PtyMessage::Buffer(data)) {
-                        println!("send pty message error: {}", e);
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => on_message(PtyMessage::Buffer(buf[..n].to_vec())),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        // EIO is how Linux reports a hung up pty
+                        debug!("pty read finished: {}", e);
+                        break;
                     }
                 }
             }
+            let code = match child.wait() {
+                Ok(status) => status.exit_code(),
+                Err(e) => {
+                    error!("wait pty child error: {}", e);
+                    1
+                }
+            };
+            exited.store(true, Ordering::SeqCst);
+            on_message(PtyMessage::Exit(code));
         });
-        rx
+        Ok(())
     }
 
     pub fn write_to_pty(&mut self, data: &str) -> std::io::Result<()> {
         write!(self.writer, "{}", data)
     }
 
+    /// Hang up the shell, escalating to SIGKILL on its process group
+    /// if it is still around after the grace period.
+    pub fn kill(&mut self) {
+        if self.exited.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Err(e) = self.killer.kill() {
+            warn!("kill pty child error: {}", e);
+        }
+
+        #[cfg(unix)]
+        if let (Some(pid), Ok(handle)) = (self.pid, tokio::runtime::Handle::try_current()) {
+            let exited = Arc::clone(&self.exited);
+            handle.spawn(async move {
+                tokio::time::sleep(KILL_GRACE_PERIOD).await;
+                if !exited.load(Ordering::SeqCst) {
+                    warn!("pty child {} ignored SIGHUP, sending SIGKILL", pid);
+                    unsafe {
+                        libc::kill(-(pid as i32), libc::SIGKILL);
+                    }
+                }
+            });
+        }
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.exited.load(Ordering::SeqCst)
+    }
+
+    /// Kill the shell and its process group without a grace period.
+    pub fn force_kill(&mut self) {
+        if self.has_exited() {
+            return;
+        }
+        #[cfg(unix)]
+        if let Some(pid) = self.pid {
+            unsafe {
+                libc::kill(-(pid as i32), libc::SIGKILL);
+            }
+            return;
+        }
+        if let Err(e) = self.killer.kill() {
+            warn!("kill pty child error: {}", e);
+        }
+    }
+
     pub fn resize_pty(&self, rows: u16, cols: u16) -> bool {
-        match self.pty_pair
+        match self
             .master
             .resize(PtySize {
                 rows,
                 cols,
                 ..Default::default()
             })
-            .map(|_| true) {
-                Ok(result) => result,
-                Err(e) => {
-                    error!("resize pty error: {}", e);
-                    false
-                }
+            .map(|_| true)
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("resize pty error: {}", e);
+                false
             }
+        }
     }
 }
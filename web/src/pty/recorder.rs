@@ -0,0 +1,114 @@
+use crate::utils::common_util::Utf8Buffer;
+use anyhow::Result;
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs::{set_permissions, Permissions};
+use std::fs::{DirBuilder, File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Header line of an asciicast v2 file, extended with who opened the session.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RecordingHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<String>,
+}
+
+/// Writes a terminal session to disk as asciicast v2, one event per line.
+/// See https://docs.asciinema.org/manual/asciicast/v2/
+pub struct Recorder {
+    writer: LineWriter<File>,
+    started: Instant,
+    record_input: bool,
+    /// Start of a character split across two reads of the pty.
+    pending: Utf8Buffer,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, header: &RecordingHeader, record_input: bool) -> Result<Self> {
+        // recordings can hold what was typed, passwords included
+        let mut dirs = DirBuilder::new();
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+            dirs.mode(0o700);
+            options.mode(0o600);
+        }
+        if let Some(dir) = path.parent() {
+            dirs.recursive(true).create(dir)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                set_permissions(dir, Permissions::from_mode(0o700))?;
+            }
+        }
+        let mut writer = LineWriter::new(options.open(path)?);
+        writeln!(writer, "{}", serde_json::to_string(header)?)?;
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+            record_input,
+            pending: Utf8Buffer::default(),
+        })
+    }
+
+    fn event(&mut self, code: &str, data: &str) {
+        let line = json!([self.started.elapsed().as_secs_f64(), code, data]);
+        if let Err(e) = writeln!(self.writer, "{}", line) {
+            error!("Error writing recording: {}", e);
+        }
+    }
+
+    pub fn output(&mut self, data: &[u8]) {
+        if let Some(text) = self.pending.push(data) {
+            self.event("o", &text);
+        }
+    }
+
+    pub fn input(&mut self, data: &str) {
+        if self.record_input {
+            self.event("i", data);
+        }
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    pub fn marker(&mut self, label: &str) {
+        self.event("m", label);
+    }
+
+    /// Write out a character left cut off and the closing marker.
+    pub fn finish(&mut self, reason: &str) {
+        if let Some(text) = self.pending.finish() {
+            self.event("o", &text);
+        }
+        self.marker(reason);
+        self.flush();
+    }
+
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            error!("Error writing recording: {}", e);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::model::recording::Recording;
+use crate::pty::recorder::RecordingHeader;
+use crate::utils::common_util::get_now_timestamp;
+use log::{error, info};
+use std::fs::{read_dir, remove_file, File};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RECORDING_EXTENSION: &str = "cast";
+
+/// Directory of asciicast recordings, one file per session.
+pub struct RecordingStore {
+    dir: PathBuf,
+}
+
+impl RecordingStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Recording ids end up in file names, so only a safe charset is allowed.
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    pub fn new_recording_path(&self, session: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}.{}",
+            get_now_timestamp(),
+            session,
+            RECORDING_EXTENSION
+        ))
+    }
+
+    pub fn path(&self, id: &str) -> Option<PathBuf> {
+        if !RecordingStore::is_valid_id(id) {
+            return None;
+        }
+        let path = self.dir.join(format!("{}.{}", id, RECORDING_EXTENSION));
+        path.is_file().then_some(path)
+    }
+
+    fn read_header(path: &PathBuf) -> Option<RecordingHeader> {
+        let mut line = String::new();
+        BufReader::new(File::open(path).ok()?)
+            .read_line(&mut line)
+            .ok()?;
+        serde_json::from_str(&line).ok()
+    }
+
+    /// All recordings, newest first.
+    pub fn list(&self) -> Vec<Recording> {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        let mut recordings: Vec<Recording> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == RECORDING_EXTENSION))
+            .filter_map(|path| {
+                let metadata = path.metadata().ok()?;
+                let modified = metadata
+                    .modified()
+                    .ok()?
+                    .duration_since(UNIX_EPOCH)
+                    .ok()?
+                    .as_secs();
+                Some(Recording {
+                    id: path.file_stem()?.to_str()?.to_string(),
+                    size: metadata.len(),
+                    modified,
+                    header: RecordingStore::read_header(&path).unwrap_or_default(),
+                })
+            })
+            .collect();
+        recordings.sort_by_key(|r| std::cmp::Reverse(r.modified));
+        recordings
+    }
+
+    /// Delete recordings older than `retention_days` and beyond the newest `max_files`.
+    /// A limit of 0 disables that check.
+    pub fn prune(&self, retention_days: u64, max_files: usize) {
+        let oldest_allowed = SystemTime::now()
+            .checked_sub(Duration::from_secs(retention_days * 24 * 60 * 60))
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        for (index, recording) in self.list().iter().enumerate() {
+            let expired = retention_days > 0 && recording.modified < oldest_allowed;
+            let overflow = max_files > 0 && index >= max_files;
+            if !(expired || overflow) {
+                continue;
+            }
+            if let Some(path) = self.path(&recording.id) {
+                match remove_file(&path) {
+                    Ok(_) => info!("Recording {} removed by retention policy", recording.id),
+                    Err(e) => error!("Error removing recording {}: {}", recording.id, e),
+                }
+            }
+        }
+    }
+
+    /// Output events of a recording as (seconds since start, data).
+    pub fn output_events(&self, id: &str) -> Option<Vec<(f64, String)>> {
+        let file = File::open(self.path(id)?).ok()?;
+        let events = BufReader::new(file)
+            .lines()
+            .skip(1)
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<(f64, String, String)>(&line).ok())
+            .filter(|(_, code, _)| code == "o")
+            .map(|(time, _, data)| (time, data))
+            .collect();
+        Some(events)
+    }
+}
+
+/// How long to wait before sending the event at `time` seconds, `elapsed` into a playback at
+/// `speed`. Measured from the start of the playback, a late event is sent right away.
+pub fn playback_delay(time: f64, speed: f64, elapsed: Duration) -> Duration {
+    Duration::from_secs_f64((time / speed).max(0.0)).saturating_sub(elapsed)
+}
@@ -0,0 +1,227 @@
+use crate::config::terminal::TerminalProfile;
+use crate::pty::pty_manager::{PtyManager, PtyMessage};
+use crate::pty::recorder::Recorder;
+use crate::utils::common_util::get_now_timestamp;
+use actix::Recipient;
+use anyhow::Result;
+use log::error;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How a session ended, told to viewers that attach after it did.
+#[derive(Clone, Copy)]
+enum Ending {
+    Exit(u32),
+    Closed,
+}
+
+impl Ending {
+    fn message(self) -> PtyMessage {
+        match self {
+            Ending::Exit(code) => PtyMessage::Exit(code),
+            Ending::Closed => PtyMessage::Closed,
+        }
+    }
+}
+
+struct SessionState {
+    scrollback: VecDeque<u8>,
+    viewers: HashMap<usize, Recipient<PtyMessage>>,
+    detached_at: Option<Instant>,
+    ended: Option<Ending>,
+}
+
+/// A shell kept alive on the server, shared by any number of viewers.
+pub struct PtySession {
+    name: String,
+    profile: TerminalProfile,
+    created_at: u64,
+    scrollback_size: usize,
+    pty_manager: Mutex<PtyManager>,
+    state: Mutex<SessionState>,
+    recorder: Mutex<Option<Recorder>>,
+}
+
+impl PtySession {
+    pub fn new(name: String, profile: TerminalProfile, scrollback_size: usize) -> Result<Self> {
+        let pty_manager = PtyManager::new(&profile)?;
+        Ok(Self {
+            name,
+            profile,
+            created_at: get_now_timestamp(),
+            scrollback_size,
+            pty_manager: Mutex::new(pty_manager),
+            state: Mutex::new(SessionState {
+                scrollback: VecDeque::with_capacity(scrollback_size),
+                viewers: HashMap::new(),
+                detached_at: Some(Instant::now()),
+                ended: None,
+            }),
+            recorder: Mutex::new(None),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the profile the session was started with.
+    pub fn shell(&self) -> &str {
+        &self.profile.name
+    }
+
+    pub fn command(&self) -> &str {
+        &self.profile.command
+    }
+
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    pub fn start<F>(&self, on_message: F) -> Result<()>
+    where
+        F: FnMut(PtyMessage) + Send + 'static,
+    {
+        self.pty_manager.lock().unwrap().start(on_message)
+    }
+
+    pub fn set_recorder(&self, recorder: Recorder) {
+        *self.recorder.lock().unwrap() = Some(recorder);
+    }
+
+    /// Mark why the recording ends and close its file.
+    pub fn stop_recording(&self, reason: &str) {
+        if let Some(mut recorder) = self.recorder.lock().unwrap().take() {
+            recorder.finish(reason);
+        }
+    }
+
+    fn record<F: FnOnce(&mut Recorder)>(&self, f: F) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            f(recorder);
+        }
+    }
+
+    /// Register a viewer and return the scrollback to replay to it.
+    /// A viewer coming after the shell is gone is told so right away.
+    pub fn attach(&self, id: usize, recipient: Recipient<PtyMessage>, remote_ip: &str) -> Vec<u8> {
+        self.record(|r| r.marker(&format!("attach {}", remote_ip)));
+        let mut state = self.state.lock().unwrap();
+        match state.ended {
+            Some(ending) => recipient.do_send(ending.message()),
+            None => {
+                state.viewers.insert(id, recipient);
+                state.detached_at = None;
+            }
+        }
+        state.scrollback.iter().copied().collect()
+    }
+
+    /// Remove a viewer, returns true if nobody is watching the session anymore.
+    pub fn detach(&self, id: usize) -> bool {
+        self.record(|r| r.marker("detach"));
+        let mut state = self.state.lock().unwrap();
+        state.viewers.remove(&id);
+        if state.viewers.is_empty() {
+            state.detached_at = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+
+    pub fn viewers(&self) -> usize {
+        self.state.lock().unwrap().viewers.len()
+    }
+
+    /// How long the session has been without viewers, `None` while attached.
+    pub fn idle_for(&self) -> Option<Duration> {
+        self.state
+            .lock()
+            .unwrap()
+            .detached_at
+            .map(|detached_at| detached_at.elapsed())
+    }
+
+    /// Append shell output to the scrollback and broadcast it to every viewer.
+    pub fn push_output(&self, data: Vec<u8>) {
+        self.record(|r| r.output(&data));
+        let mut state = self.state.lock().unwrap();
+        state.scrollback.extend(data.iter());
+        let overflow = state.scrollback.len().saturating_sub(self.scrollback_size);
+        state.scrollback.drain(..overflow);
+        for recipient in state.viewers.values() {
+            recipient.do_send(PtyMessage::Buffer(data.clone()));
+        }
+    }
+
+    /// Tell every viewer the session was closed by the server.
+    pub fn close_viewers(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.ended.get_or_insert(Ending::Closed);
+        for (_, recipient) in state.viewers.drain() {
+            recipient.do_send(PtyMessage::Closed);
+        }
+    }
+
+    /// Tell every viewer the shell exited with `code`.
+    pub fn exit_viewers(&self, code: u32) {
+        self.record(|r| r.marker(&format!("exit {}", code)));
+        let mut state = self.state.lock().unwrap();
+        state.ended = Some(Ending::Exit(code));
+        for (_, recipient) in state.viewers.drain() {
+            recipient.do_send(PtyMessage::Exit(code));
+        }
+    }
+
+    pub fn write(&self, data: &str) {
+        self.record(|r| r.input(data));
+        match self.pty_manager.lock() {
+            Ok(mut pty_manager) => {
+                if let Err(e) = pty_manager.write_to_pty(data) {
+                    error!("Error writing to pty: {}", e);
+                }
+            }
+            Err(e) => {
+                error!("Error getting pty manager lock: {}", e)
+            }
+        }
+    }
+
+    pub fn resize(&self, rows: u16, cols: u16) {
+        self.record(|r| r.resize(rows, cols));
+        match self.pty_manager.lock() {
+            Ok(pty_manager) => {
+                pty_manager.resize_pty(rows, cols);
+            }
+            Err(e) => {
+                error!("Error getting pty manager lock: {}", e)
+            }
+        }
+    }
+
+    pub fn kill(&self) {
+        match self.pty_manager.lock() {
+            Ok(mut pty_manager) => pty_manager.kill(),
+            Err(e) => {
+                error!("Error getting pty manager lock: {}", e)
+            }
+        }
+    }
+
+    pub fn force_kill(&self) {
+        match self.pty_manager.lock() {
+            Ok(mut pty_manager) => pty_manager.force_kill(),
+            Err(e) => {
+                error!("Error getting pty manager lock: {}", e)
+            }
+        }
+    }
+
+    pub fn has_exited(&self) -> bool {
+        match self.pty_manager.lock() {
+            Ok(pty_manager) => pty_manager.has_exited(),
+            Err(_) => true,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::config::config::Config;
+use crate::config::terminal::TerminalProfile;
+use crate::pty::pty_manager::{PtyMessage, KILL_GRACE_PERIOD};
+use crate::pty::recorder::{Recorder, RecordingHeader};
+use crate::pty::recording_store::RecordingStore;
+use crate::pty::session::PtySession;
+use actix::Recipient;
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// How often detached sessions are checked against the idle timeout
+const REAP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often shells are checked for having exited while shutting down
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const MAX_SESSION_NAME_LEN: usize = 64;
+
+pub struct PtySessionManager {
+    config: Arc<RwLock<Config>>,
+    sessions: Mutex<HashMap<String, Arc<PtySession>>>,
+    next_id: AtomicUsize,
+}
+
+impl PtySessionManager {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicUsize::new(1),
+        }
+    }
+
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= MAX_SESSION_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    /// Attach a viewer to the named session, creating it if needed. An existing session
+    /// must run the profile the client asked for, if it asked for one. A viewer of a
+    /// session whose shell already exited gets the exit and is closed.
+    /// Returns the session, the viewer id and the scrollback to replay.
+    pub fn attach(
+        self: &Arc<Self>,
+        name: Option<String>,
+        profile: TerminalProfile,
+        profile_requested: bool,
+        recipient: Recipient<PtyMessage>,
+        remote_ip: &str,
+    ) -> Result<(Arc<PtySession>, usize, Vec<u8>)> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let name = name.unwrap_or_else(|| format!("session-{}", id));
+        if !PtySessionManager::is_valid_name(&name) {
+            return Err(anyhow!("Invalid session name: {}", name));
+        }
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = match sessions.get(&name) {
+            Some(session) if profile_requested && session.shell() != profile.name => {
+                return Err(anyhow!(
+                    "Session {} runs profile {}, not {}",
+                    name,
+                    session.shell(),
+                    profile.name
+                ));
+            }
+            Some(session) => Arc::clone(session),
+            None => {
+                let scrollback_size = self
+                    .config
+                    .read()
+                    .unwrap()
+                    .terminal_config()
+                    .scrollback_size();
+                let session = Arc::new(PtySession::new(name.clone(), profile, scrollback_size)?);
+                self.start_recording(&session, remote_ip);
+                let forward_session = Arc::downgrade(&session);
+                let manager = Arc::downgrade(self);
+                session.start(move |msg| {
+                    let session = match forward_session.upgrade() {
+                        Some(session) => session,
+                        None => return,
+                    };
+                    match msg {
+                        PtyMessage::Buffer(data) => session.push_output(data),
+                        PtyMessage::Exit(code) => {
+                            if let Some(manager) = manager.upgrade() {
+                                manager.remove(&session);
+                            }
+                            info!("Pty session {} exited with code {}", session.name(), code);
+                            session.exit_viewers(code);
+                        }
+                        PtyMessage::Closed => session.close_viewers(),
+                    }
+                })?;
+                info!("Pty session {} created", name);
+                sessions.insert(name, Arc::clone(&session));
+                session
+            }
+        };
+        let scrollback = session.attach(id, recipient, remote_ip);
+        Ok((session, id, scrollback))
+    }
+
+    pub fn recording_store(&self) -> RecordingStore {
+        RecordingStore::new(self.config.read().unwrap().recording_dir())
+    }
+
+    fn start_recording(&self, session: &PtySession, remote_ip: &str) {
+        let terminal = self.config.read().unwrap().terminal_config();
+        if !terminal.record() {
+            return;
+        }
+        let store = self.recording_store();
+        let header = RecordingHeader {
+            version: 2,
+            width: 80,
+            height: 24,
+            timestamp: session.created_at(),
+            title: Some(session.name().to_string()),
+            env: HashMap::from([("SHELL".to_string(), session.command().to_string())]),
+            session: Some(session.name().to_string()),
+            shell: Some(session.shell().to_string()),
+            remote_ip: Some(remote_ip.to_string()),
+        };
+        let path = store.new_recording_path(session.name());
+        match Recorder::create(&path, &header, terminal.record_input()) {
+            Ok(recorder) => {
+                info!("Recording pty session {} to {:?}", session.name(), path);
+                session.set_recorder(recorder);
+            }
+            Err(e) => error!("Error creating recording {:?}: {}", path, e),
+        }
+        store.prune(
+            terminal.recording_retention_days(),
+            terminal.recording_max_files(),
+        );
+    }
+
+    pub fn detach(&self, name: &str, id: usize) {
+        let session = match self.sessions.lock().unwrap().get(name) {
+            Some(session) => Arc::clone(session),
+            None => return,
+        };
+        if session.detach(id) && self.idle_timeout().is_zero() {
+            self.kill(name);
+        }
+    }
+
+    pub fn list(&self) -> Vec<Arc<PtySession>> {
+        let mut sessions: Vec<Arc<PtySession>> =
+            self.sessions.lock().unwrap().values().cloned().collect();
+        sessions.sort_by_key(|s| s.created_at());
+        sessions
+    }
+
+    /// Kill the shell of the named session and disconnect its viewers.
+    pub fn kill(&self, name: &str) -> bool {
+        let session = self.sessions.lock().unwrap().remove(name);
+        match session {
+            Some(session) => {
+                info!("Pty session {} closed", name);
+                session.stop_recording("kill");
+                session.kill();
+                session.close_viewers();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Hang up every shell and close the recordings, for stopping the server.
+    /// Shells still running after the grace period are killed.
+    pub async fn shutdown(&self) {
+        let sessions: Vec<Arc<PtySession>> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, session)| session)
+            .collect();
+        for session in &sessions {
+            info!(
+                "Pty session {} closed, server is shutting down",
+                session.name()
+            );
+            session.stop_recording("shutdown");
+            session.kill();
+            session.close_viewers();
+        }
+        let deadline = Instant::now() + KILL_GRACE_PERIOD;
+        while sessions.iter().any(|s| !s.has_exited()) && Instant::now() < deadline {
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        }
+        for session in sessions.iter().filter(|s| !s.has_exited()) {
+            warn!("Pty session {} ignored SIGHUP, killing it", session.name());
+            session.force_kill();
+        }
+    }
+
+    /// Forget a session whose shell is gone, unless the name was already reused.
+    fn remove(&self, session: &Arc<PtySession>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions
+            .get(session.name())
+            .is_some_and(|s| Arc::ptr_eq(s, session))
+        {
+            sessions.remove(session.name());
+        }
+    }
+
+    fn idle_timeout(&self) -> Duration {
+        let timeout = match self.config.read() {
+            Ok(config) => config.terminal_config().session_idle_timeout(),
+            Err(e) => {
+                warn!("Failed to acquire config read lock: {:?}", e);
+                0
+            }
+        };
+        Duration::from_secs(timeout)
+    }
+
+    /// Close every detached session whose idle timeout has expired.
+    pub fn reap(&self) {
+        let timeout = self.idle_timeout();
+        let expired: Vec<String> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|s| s.idle_for().is_some_and(|idle| idle >= timeout))
+            .map(|s| s.name().to_string())
+            .collect();
+        for name in expired {
+            self.kill(&name);
+        }
+    }
+
+    pub async fn run_reaper(manager: Arc<PtySessionManager>) {
+        let mut interval = tokio::time::interval(REAP_INTERVAL);
+        loop {
+            interval.tick().await;
+            manager.reap();
+        }
+    }
+}
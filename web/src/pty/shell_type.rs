@@ -1,12 +1,18 @@
 use std::env;
+#[cfg(not(target_os = "windows"))]
+use std::fs::read_to_string;
+#[cfg(not(target_os = "windows"))]
+use std::path::Path;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ShellType {
     Zsh,
     Bash,
     Sh,
     Powershell,
     Cmd,
+    /// Any other login shell listed in /etc/shells, such as fish or nu.
+    Custom(String),
 }
 
 impl Default for ShellType {
@@ -42,8 +48,28 @@ impl ShellType {
             ShellType::Sh => "sh",
             ShellType::Powershell => "powershell.exe",
             ShellType::Cmd => "cmd.exe",
+            ShellType::Custom(path) => path,
         }
     }
+
+    /// Find an installed shell by name or full path in /etc/shells.
+    #[cfg(not(target_os = "windows"))]
+    pub fn installed(name: &str) -> Option<ShellType> {
+        read_to_string("/etc/shells")
+            .ok()?
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .find(|path| {
+                *path == name || Path::new(path).file_name().is_some_and(|file| file == name)
+            })
+            .map(|path| ShellType::Custom(path.to_string()))
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn installed(_name: &str) -> Option<ShellType> {
+        None
+    }
 }
 
 pub trait ShellTypeExt {
@@ -58,7 +84,7 @@ impl ShellTypeExt for str {
             "sh" => ShellType::Sh,
             "powershell.exe" => ShellType::Powershell,
             "cmd.exe" => ShellType::Cmd,
-            _ => ShellType::default(),
+            _ => ShellType::installed(self).unwrap_or_default(),
         }
     }
 }
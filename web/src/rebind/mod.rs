@@ -0,0 +1,139 @@
+use crate::config::web_server::{IpMode, WebServerConfig};
+use anyhow::{anyhow, Result};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+use tokio::sync::{mpsc, oneshot};
+
+/// Pending connections per socket, as actix-web binds them.
+const BACKLOG: i32 = 1024;
+
+/// An address the HTTP server listens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListenAddr {
+    pub addr: SocketAddr,
+    /// Refuse IPv4 on an IPv6 socket.
+    pub v6_only: bool,
+}
+
+impl Display for ListenAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.addr)?;
+        if self.addr.is_ipv6() && !self.v6_only {
+            write!(f, " (IPv4 and IPv6)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Addresses for a web server config, explicit IPv6 addresses don't take IPv4.
+pub fn listen_addrs(config: &WebServerConfig, dual_stack: bool) -> Result<Vec<ListenAddr>> {
+    let port = config.port();
+    let ips = config.bind_ips()?;
+    if !ips.is_empty() {
+        return Ok(ips
+            .into_iter()
+            .map(|ip| ListenAddr {
+                addr: SocketAddr::new(ip, port),
+                v6_only: ip.is_ipv6(),
+            })
+            .collect());
+    }
+    let any_v4 = ListenAddr {
+        addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port),
+        v6_only: false,
+    };
+    let any_v6 = |v6_only| ListenAddr {
+        addr: SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port),
+        v6_only,
+    };
+    Ok(match config.ip_mode() {
+        IpMode::Dual if dual_stack => vec![any_v6(false)],
+        IpMode::Dual | IpMode::Ipv4 => vec![any_v4],
+        IpMode::Ipv6 => vec![any_v6(true)],
+    })
+}
+
+fn bind(addr: ListenAddr) -> io::Result<TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(addr.addr),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    if addr.addr.is_ipv6() {
+        socket.set_only_v6(addr.v6_only)?;
+    }
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.addr.into())?;
+    socket.listen(BACKLOG)?;
+    Ok(socket.into())
+}
+
+/// Sockets of the running server, kept so a rebind can hand unchanged ones to the next server.
+#[derive(Debug, Default)]
+pub struct Listeners(Vec<(ListenAddr, TcpListener)>);
+
+impl Listeners {
+    /// Bind `addrs`, sharing the sockets of addresses already bound so moving to a server
+    /// on the same address doesn't conflict with the one it replaces.
+    /// Nothing is bound when any address fails.
+    pub fn rebind(&self, addrs: &[ListenAddr]) -> io::Result<Listeners> {
+        let mut listeners = Vec::with_capacity(addrs.len());
+        for addr in addrs {
+            let bound = self.0.iter().find(|(bound, _)| bound == addr);
+            let listener = match bound {
+                Some((_, listener)) => listener.try_clone(),
+                None => bind(*addr),
+            }
+            .map_err(|e| io::Error::new(e.kind(), format!("can not listen on {}: {}", addr, e)))?;
+            listeners.push((*addr, listener));
+        }
+        Ok(Listeners(listeners))
+    }
+
+    pub fn addrs(&self) -> Vec<ListenAddr> {
+        self.0.iter().map(|(addr, _)| *addr).collect()
+    }
+
+    /// Handles of the sockets for the server, which takes ownership of them.
+    pub fn sockets(&self) -> io::Result<Vec<TcpListener>> {
+        self.0
+            .iter()
+            .map(|(_, listener)| listener.try_clone())
+            .collect()
+    }
+}
+
+/// A web server config to move the running server to, answered once it runs on it.
+#[derive(Debug)]
+pub struct RebindRequest {
+    pub config: WebServerConfig,
+    pub reply: oneshot::Sender<Result<()>>,
+}
+
+/// Asks the loop in `main` to restart the HTTP server with new settings.
+#[derive(Debug, Clone)]
+pub struct Rebinder {
+    sender: mpsc::UnboundedSender<RebindRequest>,
+}
+
+impl Rebinder {
+    pub fn new() -> (Rebinder, mpsc::UnboundedReceiver<RebindRequest>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Rebinder { sender }, receiver)
+    }
+
+    /// Start a server on `config` and stop the current one gracefully.
+    /// On error the current server keeps running as it was.
+    pub async fn rebind(&self, config: WebServerConfig) -> Result<()> {
+        let (reply, receiver) = oneshot::channel();
+        self.sender
+            .send(RebindRequest { config, reply })
+            .map_err(|_| anyhow!("server is shutting down"))?;
+        receiver
+            .await
+            .map_err(|_| anyhow!("server is shutting down"))?
+    }
+}
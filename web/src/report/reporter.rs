@@ -1,6 +1,7 @@
 use crate::config::config::Config;
 use crate::report::client::Client;
 use crate::report::constant::{CHECK_TOKEN_ENDPOINT, PERSIST_ENDPOINT, REGISTER_ENDPOINT};
+use crate::shutdown::Shutdown;
 use crate::system_info::SystemInfo;
 use crate::vo::formator::Convert;
 use crate::vo::fusion::Fusion;
@@ -42,14 +43,24 @@ impl Report {
 
 pub struct Reporter {
     config: Arc<RwLock<Config>>,
+    shutdown: Shutdown,
 }
 
 impl Reporter {
-    pub async fn run(config: Arc<RwLock<Config>>) {
-        let reporter = Reporter { config };
+    /// Report until the server stops, a report being sent then is finished first.
+    pub async fn run(config: Arc<RwLock<Config>>, shutdown: Shutdown) {
+        let reporter = Reporter { config, shutdown };
         reporter.start().await;
     }
 
+    /// Sleep for `duration`, false when the server stops first.
+    async fn wait(&self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = self.shutdown.cancelled() => false,
+            _ = sleep(duration) => true,
+        }
+    }
+
     pub async fn start(&self) {
         let token = self.config.read().unwrap().server_token();
         let server_host = self.config.read().unwrap().server_host();
@@ -72,7 +83,9 @@ impl Reporter {
                     error!("Error: {:?}", err);
                     warn!("Will retry after 10 minutes.");
                     // 等待 10 分钟后重试
-                    tokio::time::sleep(Duration::from_secs(10 * 60)).await;
+                    if !self.wait(Duration::from_secs(10 * 60)).await {
+                        return;
+                    }
                 }
             }
         }
@@ -81,45 +94,41 @@ impl Reporter {
     async fn task(&self) {
         let client = reqwest::Client::new();
         let url = self.persist_url();
-        let config = self.config.clone();
-
-        actix_rt::spawn(async move {
-            let mut sys = SystemInfo::new();
-            let mut retry_count = 0;
-            loop {
-                let token = config
-                    .read()
-                    .unwrap()
-                    .server_token()
-                    .expect("Token is empty!");
-                match Reporter::report_fusion_data(
-                    &client,
-                    token,
-                    &url,
-                    sys.get_fusion_with_simple_process(),
-                )
-                .await
-                {
-                    Ok(_) => {
+        let mut sys = SystemInfo::new();
+        let mut retry_count = 0;
+        loop {
+            let token = self.get_token();
+            let wait = match Reporter::report_fusion_data(
+                &client,
+                token,
+                &url,
+                sys.get_fusion_with_simple_process(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    retry_count = 0;
+                    Duration::from_secs(30 * 60)
+                }
+                Err(err) => {
+                    error!("Send persistent data failed!");
+                    error!("Error: {:?}", err);
+                    // retry after 5 minutes if failed
+                    // max retry count is 5, if failed 5 times, then retry after 30 minutes
+                    retry_count += 1;
+                    if retry_count >= 5 {
                         retry_count = 0;
-                        sleep(Duration::from_secs(30 * 60)).await;
-                    }
-                    Err(err) => {
-                        error!("Send persistent data failed!");
-                        error!("Error: {:?}", err);
-                        // retry after 5 minutes if failed
-                        // max retry count is 5, if failed 5 times, then retry after 30 minutes
-                        retry_count += 1;
-                        if retry_count >= 5 {
-                            retry_count = 0;
-                            sleep(Duration::from_secs(30 * 60)).await;
-                        } else {
-                            sleep(Duration::from_secs(5 * 60)).await;
-                        }
+                        Duration::from_secs(30 * 60)
+                    } else {
+                        Duration::from_secs(5 * 60)
                     }
                 }
+            };
+            if !self.wait(wait).await {
+                info!("Reporter stopped");
+                return;
             }
-        });
+        }
     }
 
     async fn report_fusion_data(
@@ -0,0 +1,45 @@
+use crate::audit::set_target;
+use crate::config::config::Config;
+use crate::handler::account_handler::{
+    list_accounts_handler, remove_account_handler, save_account_handler, AccountInfo,
+};
+use crate::token::communication_token::AccountsToken;
+use actix_web::{web, HttpRequest, Responder};
+use std::sync::{Arc, RwLock};
+
+/// GET /users
+async fn list_accounts(
+    _token: AccountsToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    list_accounts_handler(config).await
+}
+
+/// POST /users
+async fn save_account(
+    _token: AccountsToken,
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: web::Json<AccountInfo>,
+) -> impl Responder {
+    set_target(&req, info.name.clone());
+    save_account_handler(config, info).await
+}
+
+/// DELETE /users/{name}
+async fn remove_account(
+    _token: AccountsToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    name: web::Path<String>,
+) -> impl Responder {
+    remove_account_handler(config, name).await
+}
+
+pub fn account_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/users")
+            .route(web::get().to(list_accounts))
+            .route(web::post().to(save_account)),
+    )
+    .service(web::resource("/users/{name}").route(web::delete().to(remove_account)));
+}
@@ -0,0 +1,29 @@
+use crate::audit::audit_log::AuditFilter;
+use crate::config::config::Config;
+use crate::handler::audit_handler::{export_audit_handler, list_audit_handler};
+use crate::token::communication_token::ConfigToken;
+use actix_web::{web, Responder};
+use std::sync::{Arc, RwLock};
+
+/// GET /audit
+async fn list_audit(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    list_audit_handler(config, filter).await
+}
+
+/// GET /audit/export
+async fn export_audit(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    export_audit_handler(config, filter).await
+}
+
+pub fn audit_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/audit").route(web::get().to(list_audit)))
+        .service(web::resource("/audit/export").route(web::get().to(export_audit)));
+}
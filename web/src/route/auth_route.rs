@@ -0,0 +1,145 @@
+use crate::access::client_ip;
+use crate::audit::set_target;
+use crate::config::config::Config;
+use crate::config::constant::WS_TICKET_TTL;
+use crate::handler::result::HttpResult;
+use crate::token::communication_token::Principal;
+use crate::token::session::SessionTokens;
+use crate::traits::json_response::JsonResponse;
+use crate::utils::common_util::get_now_timestamp;
+use crate::vo::auth::TicketVo;
+use actix_web::{web, HttpRequest, Responder};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct LoginInfo {
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RefreshInfo {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct LogoutInfo {
+    pub refresh_token: Option<String>,
+    /// End every session of the account instead of just this one.
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// POST /auth/login
+async fn login(
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: web::Json<LoginInfo>,
+) -> impl Responder {
+    let (accounts, sessions, lockout, rate_limit) = {
+        let config = config.read().unwrap();
+        (
+            config.accounts(),
+            config.sessions(),
+            config.lockout(),
+            config.rate_limit_config(),
+        )
+    };
+    let ip = client_ip(&req);
+    let now = get_now_timestamp();
+    if let Some(seconds) = lockout.banned_for(&ip, now) {
+        return JsonResponse(HttpResult::<SessionTokens>::new_msg(
+            false,
+            format!("尝试次数过多, 请 {} 秒后重试", seconds),
+        ));
+    }
+    let account = match accounts.authenticate(&info.token) {
+        Some(account) => account,
+        None => {
+            warn!("Login failed, request from: {}", ip);
+            lockout.record_failure(&ip, &rate_limit, now);
+            return JsonResponse(HttpResult::<SessionTokens>::new_msg(
+                false,
+                "Token 无效".into(),
+            ));
+        }
+    };
+    lockout.record_success(&ip);
+    set_target(&req, account.name.clone());
+    match sessions.login(&account.name) {
+        Ok(tokens) => JsonResponse(HttpResult::success(Some(tokens))),
+        Err(e) => JsonResponse(HttpResult::error(e.to_string())),
+    }
+}
+
+/// POST /auth/refresh
+async fn refresh(
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: web::Json<RefreshInfo>,
+) -> impl Responder {
+    let (sessions, lockout, rate_limit) = {
+        let config = config.read().unwrap();
+        (
+            config.sessions(),
+            config.lockout(),
+            config.rate_limit_config(),
+        )
+    };
+    let ip = client_ip(&req);
+    let now = get_now_timestamp();
+    if let Some(seconds) = lockout.banned_for(&ip, now) {
+        return JsonResponse(HttpResult::<SessionTokens>::new_msg(
+            false,
+            format!("尝试次数过多, 请 {} 秒后重试", seconds),
+        ));
+    }
+    match sessions.refresh(&info.refresh_token) {
+        Ok(tokens) => JsonResponse(HttpResult::success(Some(tokens))),
+        Err(e) => {
+            lockout.record_failure(&ip, &rate_limit, now);
+            JsonResponse(HttpResult::<SessionTokens>::error(e.to_string()))
+        }
+    }
+}
+
+/// POST /auth/logout, the session is found by the refresh token in the body
+/// or the access token in the `Authorization` header.
+async fn logout(
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+    info: Option<web::Json<LogoutInfo>>,
+) -> impl Responder {
+    let info = info.map(|info| info.into_inner()).unwrap_or_default();
+    let access_token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value));
+    let sessions = config.read().unwrap().sessions();
+    if sessions.logout(info.refresh_token.as_deref(), access_token, info.all) > 0 {
+        JsonResponse(HttpResult::<()>::new(true))
+    } else {
+        JsonResponse(HttpResult::new_msg(false, "会话不存在".into()))
+    }
+}
+
+/// POST /auth/ticket
+async fn ticket(principal: Principal, config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    let sessions = config.read().unwrap().sessions();
+    JsonResponse(HttpResult::success(Some(TicketVo {
+        ticket: sessions.issue_ticket(principal),
+        expires_in: WS_TICKET_TTL,
+    })))
+}
+
+pub fn auth_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auth")
+            .service(web::resource("/login").route(web::post().to(login)))
+            .service(web::resource("/refresh").route(web::post().to(refresh)))
+            .service(web::resource("/logout").route(web::post().to(logout)))
+            .service(web::resource("/ticket").route(web::post().to(ticket))),
+    );
+}
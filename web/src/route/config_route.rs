@@ -1,32 +1,47 @@
+use crate::config::access::AccessConfig;
 use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
 use crate::config::config::Config;
+use crate::config::exec::ExecConfig;
+use crate::config::rate_limit::RateLimitConfig;
 use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
 use crate::config::web_server::WebServerConfig;
 use crate::handler::config_handler::{
-    get_app_config_handler, get_config_handler, get_server_config_handler,
-    get_web_server_config_handler, set_app_config_handler, set_server_config_handler,
-    set_web_server_config_handler,
+    get_access_config_handler, get_app_config_handler, get_audit_config_handler,
+    get_config_handler, get_effective_config_handler, get_exec_config_handler,
+    get_rate_limit_config_handler, get_server_config_handler, get_terminal_config_handler,
+    get_tls_config_handler, get_web_server_config_handler, set_access_config_handler,
+    set_app_config_handler, set_audit_config_handler, set_exec_config_handler,
+    set_rate_limit_config_handler, set_server_config_handler, set_terminal_config_handler,
+    set_tls_config_handler, set_web_server_config_handler,
 };
-use crate::token::communication_token::CommunicationToken;
+use crate::rebind::Rebinder;
+use crate::token::communication_token::ConfigToken;
 use actix_web::{web, Responder};
 use std::sync::{Arc, RwLock};
 
-async fn get_config(
-    _token: CommunicationToken,
+async fn get_config(_token: ConfigToken, config: web::Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_config_handler(config).await
+}
+
+async fn get_effective_config(
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
 ) -> impl Responder {
-    get_config_handler(config).await
+    get_effective_config_handler(config).await
 }
 
 async fn get_server_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
 ) -> impl Responder {
     get_server_config_handler(config).await
 }
 
 async fn set_server_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
     server_config: web::Json<ServerConfig>,
 ) -> impl Responder {
@@ -34,14 +49,14 @@ async fn set_server_config(
 }
 
 async fn get_app_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
 ) -> impl Responder {
     get_app_config_handler(config).await
 }
 
 async fn set_app_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
     app_config: web::Json<AppConfig>,
 ) -> impl Responder {
@@ -49,18 +64,109 @@ async fn set_app_config(
 }
 
 async fn get_web_server_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
 ) -> impl Responder {
     get_web_server_config_handler(config).await
 }
 
 async fn set_web_server_config(
-    _token: CommunicationToken,
+    _token: ConfigToken,
     config: web::Data<Arc<RwLock<Config>>>,
+    rebinder: web::Data<Rebinder>,
     web_server_config: web::Json<WebServerConfig>,
 ) -> impl Responder {
-    set_web_server_config_handler(config, web_server_config).await
+    set_web_server_config_handler(config, rebinder, web_server_config).await
+}
+
+async fn get_terminal_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_terminal_config_handler(config).await
+}
+
+async fn set_terminal_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    terminal_config: web::Json<TerminalConfig>,
+) -> impl Responder {
+    set_terminal_config_handler(config, terminal_config).await
+}
+
+async fn get_exec_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_exec_config_handler(config).await
+}
+
+async fn set_exec_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    exec_config: web::Json<ExecConfig>,
+) -> impl Responder {
+    set_exec_config_handler(config, exec_config).await
+}
+
+async fn get_tls_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_tls_config_handler(config).await
+}
+
+async fn set_tls_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    tls_config: web::Json<TlsConfig>,
+) -> impl Responder {
+    set_tls_config_handler(config, tls_config).await
+}
+
+async fn get_rate_limit_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_rate_limit_config_handler(config).await
+}
+
+async fn set_rate_limit_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    rate_limit_config: web::Json<RateLimitConfig>,
+) -> impl Responder {
+    set_rate_limit_config_handler(config, rate_limit_config).await
+}
+
+async fn get_access_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_access_config_handler(config).await
+}
+
+async fn set_access_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    access_config: web::Json<AccessConfig>,
+) -> impl Responder {
+    set_access_config_handler(config, access_config).await
+}
+
+async fn get_audit_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> impl Responder {
+    get_audit_config_handler(config).await
+}
+
+async fn set_audit_config(
+    _token: ConfigToken,
+    config: web::Data<Arc<RwLock<Config>>>,
+    audit_config: web::Json<AuditConfig>,
+) -> impl Responder {
+    set_audit_config_handler(config, audit_config).await
 }
 
 pub fn config_services(cfg: &mut web::ServiceConfig) {
@@ -87,6 +193,52 @@ pub fn config_services(cfg: &mut web::ServiceConfig) {
                         .route(web::get().to(get_web_server_config))
                         .route(web::post().to(set_web_server_config)),
                 ),
+            )
+            .service(
+                web::scope("/terminal").service(
+                    web::resource("")
+                        .route(web::get().to(get_terminal_config))
+                        .route(web::post().to(set_terminal_config)),
+                ),
+            )
+            .service(
+                web::scope("/exec").service(
+                    web::resource("")
+                        .route(web::get().to(get_exec_config))
+                        .route(web::post().to(set_exec_config)),
+                ),
+            )
+            .service(
+                web::scope("/tls").service(
+                    web::resource("")
+                        .route(web::get().to(get_tls_config))
+                        .route(web::post().to(set_tls_config)),
+                ),
+            )
+            .service(
+                web::scope("/rate_limit").service(
+                    web::resource("")
+                        .route(web::get().to(get_rate_limit_config))
+                        .route(web::post().to(set_rate_limit_config)),
+                ),
+            )
+            .service(
+                web::scope("/access").service(
+                    web::resource("")
+                        .route(web::get().to(get_access_config))
+                        .route(web::post().to(set_access_config)),
+                ),
+            )
+            .service(
+                web::scope("/audit").service(
+                    web::resource("")
+                        .route(web::get().to(get_audit_config))
+                        .route(web::post().to(set_audit_config)),
+                ),
+            )
+            .service(
+                web::scope("/effective")
+                    .service(web::resource("").route(web::get().to(get_effective_config))),
             ),
     );
 }
@@ -0,0 +1,142 @@
+use crate::audit::{set_target, Auditor};
+use crate::config::config::Config;
+use crate::config::exec::ExecConfig;
+use crate::exec::executor::{prepare, run, stream, ExecEvent, ExecOutput, ExecRequest};
+use crate::handler::result::HttpResult;
+use crate::limiter::HeavyRequest;
+use crate::token::communication_token::ExecToken;
+use crate::traits::json_response::JsonResponse;
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_web_actors::ws;
+use log::{error, warn};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// POST /exec
+async fn exec(
+    _token: ExecToken,
+    _limit: HeavyRequest,
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+    request: web::Json<ExecRequest>,
+) -> impl Responder {
+    set_target(&req, request.target());
+    let exec_config = config.read().unwrap().exec_config();
+    let cmd = match prepare(&exec_config, &request) {
+        Ok(cmd) => cmd,
+        Err(e) => return JsonResponse(HttpResult::<ExecOutput>::error(e.to_string())),
+    };
+    let timeout = Duration::from_secs(exec_config.timeout(request.timeout));
+    match run(cmd, timeout, exec_config.max_output()).await {
+        Ok(output) => JsonResponse(HttpResult::success(Some(output))),
+        Err(e) => {
+            error!("Error running exec command: {}", e);
+            JsonResponse(HttpResult::error(e.to_string()))
+        }
+    }
+}
+
+/// Runs one command per connection: the first text message is an `ExecRequest`,
+/// then output is streamed back as `ExecEvent` JSON until the command exits.
+struct ExecWs {
+    config: ExecConfig,
+    cancel: CancellationToken,
+    running: bool,
+    auditor: Option<Auditor>,
+}
+
+impl ExecWs {
+    fn start_command(&mut self, text: &str, ctx: &mut ws::WebsocketContext<Self>) {
+        let request = match serde_json::from_str::<ExecRequest>(text) {
+            Ok(request) => request,
+            Err(e) => {
+                return ctx.notify(ExecEvent::Error {
+                    message: format!("请求格式错误: {}", e),
+                })
+            }
+        };
+        let prepared = prepare(&self.config, &request);
+        if let Some(auditor) = &self.auditor {
+            let result = prepared.as_ref().map(|_| ()).map_err(|e| e.to_string());
+            auditor.record("exec.run", Some(request.target()), result);
+        }
+        let cmd = match prepared {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                return ctx.notify(ExecEvent::Error {
+                    message: e.to_string(),
+                })
+            }
+        };
+        self.running = true;
+        let timeout = Duration::from_secs(self.config.timeout(request.timeout));
+        let cancel = self.cancel.clone();
+        let recipient = ctx.address().recipient();
+        actix::spawn(stream(cmd, timeout, cancel, move |event| {
+            recipient.do_send(event)
+        }));
+    }
+}
+
+impl Actor for ExecWs {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.cancel.cancel();
+    }
+}
+
+impl actix::Handler<ExecEvent> for ExecWs {
+    type Result = ();
+
+    fn handle(&mut self, msg: ExecEvent, ctx: &mut Self::Context) -> Self::Result {
+        ctx.text(serde_json::to_string(&msg).unwrap_or_default());
+        if let ExecEvent::Exit { .. } | ExecEvent::Error { .. } = msg {
+            ctx.close(None);
+            ctx.stop();
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ExecWs {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Text(text)) if !self.running => self.start_command(&text, ctx),
+            Ok(ws::Message::Close(reason)) => {
+                warn!("Exec WebSocket closing for reason: {:?}", reason);
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// GET /exec/ws
+async fn exec_ws(
+    _token: ExecToken,
+    _limit: HeavyRequest,
+    req: HttpRequest,
+    stream: web::Payload,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> Result<HttpResponse, Error> {
+    let exec_config = config.read().unwrap().exec_config();
+    ws::start(
+        ExecWs {
+            config: exec_config,
+            cancel: CancellationToken::new(),
+            running: false,
+            auditor: Auditor::of(&req),
+        },
+        &req,
+        stream,
+    )
+}
+
+pub fn exec_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/exec").route(web::post().to(exec)))
+        .service(web::resource("/exec/ws").route(web::get().to(exec_ws)));
+}
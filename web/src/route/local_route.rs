@@ -1,14 +1,36 @@
+use crate::audit::audit_log::AuditFilter;
+use crate::audit::set_target;
+use crate::config::access::AccessConfig;
 use crate::config::app::AppConfig;
+use crate::config::audit::AuditConfig;
 use crate::config::config::Config;
+use crate::config::exec::ExecConfig;
+use crate::config::rate_limit::RateLimitConfig;
 use crate::config::server::ServerConfig;
+use crate::config::terminal::TerminalConfig;
+use crate::config::tls::TlsConfig;
 use crate::config::web_server::WebServerConfig;
+use crate::handler::account_handler::{
+    list_accounts_handler, remove_account_handler, save_account_handler, AccountInfo,
+};
+use crate::handler::api_token_handler::{
+    create_api_token_handler, list_api_tokens_handler, revoke_api_token_handler, ApiTokenInfo,
+};
+use crate::handler::audit_handler::{export_audit_handler, list_audit_handler};
+use crate::handler::ban_handler::{list_bans_handler, unban_handler};
 use crate::handler::config_handler::{
-    get_app_config_handler, get_config_handler, get_server_config_handler,
-    get_web_server_config_handler, set_app_config_handler, set_server_config_handler,
-    set_web_server_config_handler,
+    export_config_handler, get_access_config_handler, get_app_config_handler,
+    get_audit_config_handler, get_config_handler, get_effective_config_handler,
+    get_exec_config_handler, get_rate_limit_config_handler, get_server_config_handler,
+    get_terminal_config_handler, get_tls_config_handler, get_web_server_config_handler,
+    import_config_handler, set_access_config_handler, set_app_config_handler,
+    set_audit_config_handler, set_exec_config_handler, set_rate_limit_config_handler,
+    set_server_config_handler, set_terminal_config_handler, set_tls_config_handler,
+    set_web_server_config_handler, ExportQuery,
 };
+use crate::rebind::Rebinder;
 use actix_web::web::{get, post, resource, scope, Data};
-use actix_web::{guard, web, Responder};
+use actix_web::{web, HttpRequest, Responder};
 use std::sync::{Arc, RwLock};
 
 async fn get_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
@@ -43,20 +65,163 @@ async fn get_web_server_config(config: Data<Arc<RwLock<Config>>>) -> impl Respon
 
 async fn set_web_server_config(
     config: Data<Arc<RwLock<Config>>>,
+    rebinder: Data<Rebinder>,
     web_server_config: web::Json<WebServerConfig>,
 ) -> impl Responder {
-    set_web_server_config_handler(config, web_server_config).await
+    set_web_server_config_handler(config, rebinder, web_server_config).await
+}
+
+async fn get_terminal_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_terminal_config_handler(config).await
+}
+
+async fn set_terminal_config(
+    config: Data<Arc<RwLock<Config>>>,
+    terminal_config: web::Json<TerminalConfig>,
+) -> impl Responder {
+    set_terminal_config_handler(config, terminal_config).await
+}
+
+async fn get_exec_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_exec_config_handler(config).await
+}
+
+async fn set_exec_config(
+    config: Data<Arc<RwLock<Config>>>,
+    exec_config: web::Json<ExecConfig>,
+) -> impl Responder {
+    set_exec_config_handler(config, exec_config).await
+}
+
+async fn get_tls_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_tls_config_handler(config).await
+}
+
+async fn set_tls_config(
+    config: Data<Arc<RwLock<Config>>>,
+    tls_config: web::Json<TlsConfig>,
+) -> impl Responder {
+    set_tls_config_handler(config, tls_config).await
+}
+
+async fn get_rate_limit_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_rate_limit_config_handler(config).await
+}
+
+async fn set_rate_limit_config(
+    config: Data<Arc<RwLock<Config>>>,
+    rate_limit_config: web::Json<RateLimitConfig>,
+) -> impl Responder {
+    set_rate_limit_config_handler(config, rate_limit_config).await
+}
+
+async fn export_config(
+    config: Data<Arc<RwLock<Config>>>,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    export_config_handler(config, query).await
+}
+
+async fn import_config(
+    config: Data<Arc<RwLock<Config>>>,
+    rebinder: Data<Rebinder>,
+    body: String,
+) -> impl Responder {
+    import_config_handler(config, rebinder, body).await
+}
+
+async fn get_effective_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_effective_config_handler(config).await
+}
+
+async fn get_access_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_access_config_handler(config).await
 }
 
-pub fn local_services(cfg: &mut web::ServiceConfig, host: &str) {
+async fn set_access_config(
+    config: Data<Arc<RwLock<Config>>>,
+    access_config: web::Json<AccessConfig>,
+) -> impl Responder {
+    set_access_config_handler(config, access_config).await
+}
+
+async fn get_audit_config(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    get_audit_config_handler(config).await
+}
+
+async fn set_audit_config(
+    config: Data<Arc<RwLock<Config>>>,
+    audit_config: web::Json<AuditConfig>,
+) -> impl Responder {
+    set_audit_config_handler(config, audit_config).await
+}
+
+async fn list_audit(
+    config: Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    list_audit_handler(config, filter).await
+}
+
+async fn export_audit(
+    config: Data<Arc<RwLock<Config>>>,
+    filter: web::Query<AuditFilter>,
+) -> impl Responder {
+    export_audit_handler(config, filter).await
+}
+
+async fn list_bans(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    list_bans_handler(config).await
+}
+
+async fn unban(config: Data<Arc<RwLock<Config>>>, ip: web::Path<String>) -> impl Responder {
+    unban_handler(config, ip).await
+}
+
+async fn list_accounts(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    list_accounts_handler(config).await
+}
+
+async fn save_account(
+    req: HttpRequest,
+    config: Data<Arc<RwLock<Config>>>,
+    info: web::Json<AccountInfo>,
+) -> impl Responder {
+    set_target(&req, info.name.clone());
+    save_account_handler(config, info).await
+}
+
+async fn remove_account(
+    config: Data<Arc<RwLock<Config>>>,
+    name: web::Path<String>,
+) -> impl Responder {
+    remove_account_handler(config, name).await
+}
+
+async fn list_api_tokens(config: Data<Arc<RwLock<Config>>>) -> impl Responder {
+    list_api_tokens_handler(config).await
+}
+
+async fn create_api_token(
+    req: HttpRequest,
+    config: Data<Arc<RwLock<Config>>>,
+    info: web::Json<ApiTokenInfo>,
+) -> impl Responder {
+    set_target(&req, info.name.clone());
+    create_api_token_handler(config, info).await
+}
+
+async fn revoke_api_token(
+    config: Data<Arc<RwLock<Config>>>,
+    id: web::Path<String>,
+) -> impl Responder {
+    revoke_api_token_handler(config, id).await
+}
+
+/// Routes for the machine itself, the `local` access rule decides who that is.
+pub fn local_services(cfg: &mut web::ServiceConfig) {
     cfg.service(
         scope("/local")
-            .guard(
-                guard::Any(guard::Host("localhost"))
-                    .or(guard::Host("127.0.0.1"))
-                    .or(guard::Host("[::1]"))
-                    .or(guard::Host(host)),
-            )
             .service(
                 scope("/config")
                     .service(resource("").route(get().to(get_config)))
@@ -74,7 +239,56 @@ pub fn local_services(cfg: &mut web::ServiceConfig, host: &str) {
                         resource("/web_server")
                             .route(get().to(get_web_server_config))
                             .route(post().to(set_web_server_config)),
-                    ),
-            ),
+                    )
+                    .service(
+                        resource("/terminal")
+                            .route(get().to(get_terminal_config))
+                            .route(post().to(set_terminal_config)),
+                    )
+                    .service(
+                        resource("/exec")
+                            .route(get().to(get_exec_config))
+                            .route(post().to(set_exec_config)),
+                    )
+                    .service(
+                        resource("/tls")
+                            .route(get().to(get_tls_config))
+                            .route(post().to(set_tls_config)),
+                    )
+                    .service(
+                        resource("/rate_limit")
+                            .route(get().to(get_rate_limit_config))
+                            .route(post().to(set_rate_limit_config)),
+                    )
+                    .service(
+                        resource("/access")
+                            .route(get().to(get_access_config))
+                            .route(post().to(set_access_config)),
+                    )
+                    .service(
+                        resource("/audit")
+                            .route(get().to(get_audit_config))
+                            .route(post().to(set_audit_config)),
+                    )
+                    .service(resource("/effective").route(get().to(get_effective_config)))
+                    .service(resource("/export").route(get().to(export_config)))
+                    .service(resource("/import").route(post().to(import_config))),
+            )
+            .service(
+                resource("/users")
+                    .route(get().to(list_accounts))
+                    .route(post().to(save_account)),
+            )
+            .service(resource("/users/{name}").route(web::delete().to(remove_account)))
+            .service(
+                resource("/tokens")
+                    .route(get().to(list_api_tokens))
+                    .route(post().to(create_api_token)),
+            )
+            .service(resource("/tokens/{id}").route(web::delete().to(revoke_api_token)))
+            .service(resource("/audit").route(get().to(list_audit)))
+            .service(resource("/audit/export").route(get().to(export_audit)))
+            .service(resource("/bans").route(get().to(list_bans)))
+            .service(resource("/bans/{ip}").route(web::delete().to(unban))),
     );
 }
@@ -1,4 +1,8 @@
+pub mod account_route;
+pub mod audit_route;
+pub mod auth_route;
 pub mod config_route;
+pub mod exec_route;
 pub mod local_route;
 pub mod page_route;
 pub mod pty_route;
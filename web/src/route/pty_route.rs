@@ -1,26 +1,66 @@
+use crate::access::client_ip;
+use crate::audit::set_target;
 use crate::config::config::Config;
-use crate::pty::pty_manager::{PtyManager, PtyMessage};
-use crate::pty::shell_type::{ShellType, ShellTypeExt};
+use crate::config::terminal::TerminalProfile;
+use crate::handler::result::HttpResult;
+use crate::limiter::HeavyRequest;
+use crate::pty::profile::resolve_profile;
+use crate::pty::pty_manager::PtyMessage;
+use crate::pty::recording_store::playback_delay;
+use crate::pty::session::PtySession;
+use crate::pty::session_manager::PtySessionManager;
+use crate::shutdown::{close_reason, Shutdown};
+use crate::token::communication_token::TerminalToken;
+use crate::traits::json_response::JsonResponse;
 use crate::utils::common_util::get_terminal_time_format;
 use crate::utils::pty_util::{makeword, MAGIC_FLAG};
-use actix::{Actor, AsyncContext, StreamHandler};
-use actix_web::{web, Error, HttpRequest, HttpResponse};
+use crate::vo::formator::Convert;
+use crate::vo::pty_session::PtySessionVo;
+use crate::vo::recording::RecordingVo;
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_files::NamedFile;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
 use actix_web_actors::ws;
 use log::{debug, error, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::str::from_utf8;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 struct PtyWs {
-    pty_manager: Arc<Mutex<PtyManager>>,
+    sessions: Arc<PtySessionManager>,
     config: Arc<RwLock<Config>>,
+    profile: TerminalProfile,
+    /// The client named the profile with `shell`, a reattached session must run it.
+    profile_requested: bool,
+    name: Option<String>,
+    remote_ip: String,
+    session: Option<Arc<PtySession>>,
+    viewer_id: usize,
+    shutdown: Shutdown,
 }
 
 impl PtyWs {
-    pub fn new(shell_type: ShellType, config: Arc<RwLock<Config>>) -> Self {
+    pub fn new(
+        sessions: Arc<PtySessionManager>,
+        profile: TerminalProfile,
+        profile_requested: bool,
+        name: Option<String>,
+        remote_ip: String,
+        config: Arc<RwLock<Config>>,
+        shutdown: Shutdown,
+    ) -> Self {
         Self {
-            pty_manager: Arc::new(Mutex::new(PtyManager::new(shell_type))),
+            sessions,
             config,
+            profile,
+            profile_requested,
+            name,
+            remote_ip,
+            session: None,
+            viewer_id: 0,
+            shutdown,
         }
     }
 }
@@ -33,6 +73,20 @@ impl actix::Handler<PtyMessage> for PtyWs {
             PtyMessage::Buffer(data) => {
                 ctx.binary(data);
             }
+            PtyMessage::Exit(code) => {
+                ctx.text(format!("\r\nProcess exited with code {}.\r\n", code));
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Normal,
+                    description: Some(format!("exit code {}", code)),
+                }));
+                ctx.stop();
+            }
+            PtyMessage::Closed => {
+                ctx.text("\r\nSession closed.\r\n");
+                // killed by the shutdown before the actor saw it
+                ctx.close(self.shutdown.is_cancelled().then(close_reason));
+                ctx.stop();
+            }
         }
     }
 }
@@ -41,6 +95,8 @@ impl Actor for PtyWs {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        self.shutdown.close_on_shutdown(ctx);
+
         let last_login = match self.config.try_read() {
             Ok(config) => get_terminal_time_format(config.last_login()),
             Err(_) => "Unknown".to_string(),
@@ -71,20 +127,34 @@ impl Actor for PtyWs {
         ctx.text("Documentation: https://docs.serverbee.app\r\n");
         ctx.text("\r\n");
 
-        let rx = self.pty_manager.lock().unwrap().start();
-
-        let addr = ctx.address();
-
-        std::thread::spawn(move || loop {
-            match rx.recv() {
-                Ok(msg) => {
-                    addr.do_send(msg);
-                }
-                Err(e) => {
-                    println!("recv error: {}", e);
+        match self.sessions.attach(
+            self.name.take(),
+            self.profile.clone(),
+            self.profile_requested,
+            ctx.address().recipient(),
+            &self.remote_ip,
+        ) {
+            Ok((session, viewer_id, scrollback)) => {
+                ctx.text(format!("Session: {}\r\n", session.name()));
+                if !scrollback.is_empty() {
+                    ctx.binary(scrollback);
                 }
+                self.session = Some(session);
+                self.viewer_id = viewer_id;
             }
-        });
+            Err(e) => {
+                error!("Error attaching pty session: {}", e);
+                ctx.text(format!("{}\r\n", e));
+                ctx.close(None);
+                ctx.stop();
+            }
+        }
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Some(session) = self.session.take() {
+            self.sessions.detach(session.name(), self.viewer_id);
+        }
     }
 }
 
@@ -93,13 +163,16 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for PtyWs {
         match msg {
             Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
             Ok(ws::Message::Text(text)) => {
-                self.pty_manager
-                    .lock()
-                    .unwrap()
-                    .write_to_pty(&text)
-                    .unwrap();
+                if let Some(session) = &self.session {
+                    session.write(&text);
+                }
             }
             Ok(ws::Message::Binary(bin)) => {
+                let session = match &self.session {
+                    Some(session) => session,
+                    None => return,
+                };
+
                 let bin = bin.to_vec();
 
                 if bin.len() == 6 && bin[0] == MAGIC_FLAG[0] && bin[1] == MAGIC_FLAG[1] {
@@ -108,32 +181,16 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for PtyWs {
 
                     debug!("set pty size rows:{:?} cols: {:?}", rows, cols);
 
-                    match self.pty_manager.lock() {
-                        Ok(pty_manager) => {
-                            pty_manager.resize_pty(rows, cols);
-                        }
-                        Err(e) => {
-                            error!("Error getting pty manager lock: {}", e)
-                        }
-                    }
+                    session.resize(rows, cols);
                     return;
                 }
 
-                match self.pty_manager.lock() {
-                    Ok(mut pty_manager) => {
-                        if let Err(e) =
-                            pty_manager.write_to_pty(from_utf8(&bin).unwrap_or_default())
-                        {
-                            error!("Error writing to pty: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        error!("Error getting pty manager lock: {}", e)
-                    }
-                }
+                session.write(from_utf8(&bin).unwrap_or_default());
             }
             Ok(ws::Message::Close(reason)) => {
                 warn!("WebSocket connection is closing for reason: {:?}", reason);
+                ctx.close(reason);
+                ctx.stop();
             }
             _ => (),
         }
@@ -141,20 +198,181 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for PtyWs {
 }
 
 async fn pty_index(
+    _token: TerminalToken,
+    _limit: HeavyRequest,
     req: HttpRequest,
     stream: web::Payload,
     config: web::Data<Arc<RwLock<Config>>>,
+    sessions: web::Data<Arc<PtySessionManager>>,
+    shutdown: web::Data<Shutdown>,
 ) -> Result<HttpResponse, Error> {
     let params: HashMap<String, String> =
         serde_urlencoded::from_str(req.query_string()).unwrap_or_else(|_| HashMap::new());
-    let shell = params
-        .get("shell")
-        .map(|value| value.to_shell_type())
-        .unwrap_or(ShellType::default());
-    let resp = ws::start(PtyWs::new(shell, config.as_ref().clone()), &req, stream);
+    let profile = resolve_profile(
+        &config.read().unwrap().terminal_config(),
+        params.get("shell").map(|value| value.as_str()),
+    )
+    .map_err(actix_web::error::ErrorBadRequest)?;
+    let name = params.get("session").cloned();
+    set_target(&req, name.clone().unwrap_or_else(|| profile.name.clone()));
+    let remote_ip = client_ip(&req);
+    let resp = ws::start(
+        PtyWs::new(
+            sessions.as_ref().clone(),
+            profile,
+            params.contains_key("shell"),
+            name,
+            remote_ip,
+            config.as_ref().clone(),
+            shutdown.as_ref().clone(),
+        ),
+        &req,
+        stream,
+    );
     resp
 }
 
+/// GET /pty/sessions
+async fn list_sessions(
+    _token: TerminalToken,
+    sessions: web::Data<Arc<PtySessionManager>>,
+) -> impl Responder {
+    let sessions: Vec<PtySessionVo> = sessions.list().iter().map(|s| s.convert()).collect();
+    JsonResponse(HttpResult::success(Some(sessions)))
+}
+
+/// DELETE /pty/sessions/{name}
+async fn kill_session(
+    _token: TerminalToken,
+    sessions: web::Data<Arc<PtySessionManager>>,
+    name: web::Path<String>,
+) -> impl Responder {
+    warn!("Kill pty session: {}", name);
+    if sessions.kill(&name) {
+        JsonResponse(HttpResult::<()>::new(true))
+    } else {
+        JsonResponse(HttpResult::new_msg(false, "会话不存在".into()))
+    }
+}
+
+/// Replays the output of a recording with its original timing.
+struct PlaybackWs {
+    events: VecDeque<(f64, String)>,
+    speed: f64,
+    started: Instant,
+    shutdown: Shutdown,
+}
+
+impl PlaybackWs {
+    /// Send the next event at its offset from the start, one at a time to keep the order,
+    /// and close the socket after the last one.
+    fn play(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let Some((time, data)) = self.events.pop_front() else {
+            ctx.close(Some(ws::CloseCode::Normal.into()));
+            ctx.stop();
+            return;
+        };
+        let delay = playback_delay(time, self.speed, self.started.elapsed());
+        ctx.run_later(delay, move |act, ctx| {
+            ctx.text(data);
+            act.play(ctx);
+        });
+    }
+}
+
+impl Actor for PlaybackWs {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.shutdown.close_on_shutdown(ctx);
+        self.started = Instant::now();
+        self.play(ctx);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for PlaybackWs {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// GET /pty/recordings
+async fn list_recordings(
+    _token: TerminalToken,
+    sessions: web::Data<Arc<PtySessionManager>>,
+) -> impl Responder {
+    let recordings: Vec<RecordingVo> = sessions
+        .recording_store()
+        .list()
+        .iter()
+        .map(|r| r.convert())
+        .collect();
+    JsonResponse(HttpResult::success(Some(recordings)))
+}
+
+/// GET /pty/recordings/{id}
+async fn download_recording(
+    _token: TerminalToken,
+    sessions: web::Data<Arc<PtySessionManager>>,
+    id: web::Path<String>,
+) -> Result<NamedFile, Error> {
+    let path = sessions
+        .recording_store()
+        .path(&id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Recording not found"))?;
+    Ok(NamedFile::open(path)?
+        .set_content_type("application/x-asciicast".parse().unwrap())
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("{}.cast", id))],
+        }))
+}
+
+/// GET /pty/recordings/{id}/play?speed=1
+async fn play_recording(
+    _token: TerminalToken,
+    req: HttpRequest,
+    stream: web::Payload,
+    sessions: web::Data<Arc<PtySessionManager>>,
+    shutdown: web::Data<Shutdown>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let events = sessions
+        .recording_store()
+        .output_events(&id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Recording not found"))?;
+    let params: HashMap<String, String> =
+        serde_urlencoded::from_str(req.query_string()).unwrap_or_else(|_| HashMap::new());
+    let speed = params
+        .get("speed")
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|speed| *speed > 0.0)
+        .unwrap_or(1.0);
+    let shutdown = shutdown.as_ref().clone();
+    ws::start(
+        PlaybackWs {
+            events: events.into(),
+            speed,
+            started: Instant::now(),
+            shutdown,
+        },
+        &req,
+        stream,
+    )
+}
+
 pub fn pty_service(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/pty").route(web::get().to(pty_index)));
+    cfg.service(web::resource("/pty").route(web::get().to(pty_index)))
+        .service(web::resource("/pty/sessions").route(web::get().to(list_sessions)))
+        .service(web::resource("/pty/sessions/{name}").route(web::delete().to(kill_session)))
+        .service(web::resource("/pty/recordings").route(web::get().to(list_recordings)))
+        .service(web::resource("/pty/recordings/{id}").route(web::get().to(download_recording)))
+        .service(web::resource("/pty/recordings/{id}/play").route(web::get().to(play_recording)));
 }
@@ -1,11 +1,16 @@
 use std::time::{Duration, Instant};
 
+use crate::config::config::Config;
+use crate::limiter::rate_limiter::RateLimiter;
+use crate::limiter::HeavyRequest;
+use crate::shutdown::Shutdown;
 use crate::system_info::SystemInfo;
 use crate::token::communication_token::CommunicationToken;
 use actix::prelude::*;
 use actix_web::{web, Error, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
 use log::warn;
+use std::sync::{Arc, RwLock};
 
 /// How often heartbeat pings are sent
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
@@ -56,22 +61,39 @@ pub struct Sort {
     pub by: SortBy,
 }
 
+/// Switching to process mode is expensive, so it counts against the heavy request limit.
+pub struct ProcessLimit {
+    pub limiter: RateLimiter,
+    pub ip: String,
+    pub per_minute: u32,
+}
+
+impl ProcessLimit {
+    fn allow(&self) -> bool {
+        self.limiter.allow(&self.ip, self.per_minute)
+    }
+}
+
 pub struct MyWebSocket {
     hb: Instant,
     sys: SystemInfo,
     signal: Signal,
     pid: Option<String>,
     sort: Option<Sort>,
+    process_limit: ProcessLimit,
+    shutdown: Shutdown,
 }
 
 impl MyWebSocket {
-    pub fn new() -> Self {
+    pub fn new(process_limit: ProcessLimit, shutdown: Shutdown) -> Self {
         Self {
             hb: Instant::now(),
             sys: SystemInfo::new(),
             signal: Signal::Less,
             pid: None,
             sort: None,
+            process_limit,
+            shutdown,
         }
     }
 
@@ -108,6 +130,7 @@ impl Actor for MyWebSocket {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        self.shutdown.close_on_shutdown(ctx);
         self.hb(ctx);
         self.task(ctx);
     }
@@ -137,6 +160,13 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MyWebSocket {
                             self.sort = None;
                         }
                         Some("/process") => {
+                            if !self.process_limit.allow() {
+                                warn!(
+                                    "Too many process mode requests, request from: {}",
+                                    self.process_limit.ip
+                                );
+                                return;
+                            }
                             let param = command.next();
                             self.signal = Signal::Process;
                             self.pid = param.map(|s| s.to_string());
@@ -177,8 +207,23 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MyWebSocket {
 /// WebSocket handshake and start `MyWebSocket` actor.
 pub async fn echo_ws(
     _token: CommunicationToken,
+    limit: HeavyRequest,
     req: HttpRequest,
     stream: web::Payload,
+    config: web::Data<Arc<RwLock<Config>>>,
+    shutdown: web::Data<Shutdown>,
 ) -> Result<HttpResponse, Error> {
-    ws::start(MyWebSocket::new(), &req, stream)
+    let process_limit = {
+        let config = config.read().unwrap();
+        ProcessLimit {
+            limiter: config.rate_limiter(),
+            ip: limit.ip,
+            per_minute: config.rate_limit_config().heavy_requests_per_minute(),
+        }
+    };
+    ws::start(
+        MyWebSocket::new(process_limit, shutdown.as_ref().clone()),
+        &req,
+        stream,
+    )
 }
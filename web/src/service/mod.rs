@@ -0,0 +1,2 @@
+pub mod service_command;
+pub mod unit;
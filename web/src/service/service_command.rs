@@ -0,0 +1,237 @@
+use crate::cli::{Args, ServiceAction};
+use crate::config::constant::MAX_SHUTDOWN_TIMEOUT;
+use crate::config::layers::{database_layer, ConfigLayers, Overlays};
+use crate::config::terminal::TerminalConfig;
+use crate::db::db_wrapper::{db_path, DbWrapper};
+use crate::service::unit::{ServiceUnit, Step, DEFAULT_DATA_DIR, DEFAULT_LOG_DIR};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn absolute(path: PathBuf) -> Result<PathBuf> {
+    match path.is_absolute() {
+        true => Ok(path),
+        false => Ok(std::env::current_dir()?.join(path)),
+    }
+}
+
+fn is_root() -> bool {
+    #[cfg(unix)]
+    unsafe {
+        libc::geteuid() == 0
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+fn user_exists(user: &str) -> bool {
+    #[cfg(unix)]
+    {
+        let name = match std::ffi::CString::new(user) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        unsafe { !libc::getpwnam(name.as_ptr()).is_null() }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = user;
+        false
+    }
+}
+
+/// Terminal profiles that switch user, which a service not running as root can't start.
+/// The database is only read when it exists and isn't held by a running server.
+fn privileged_profiles(overlays: &Overlays, data_dir: &Path) -> Vec<String> {
+    let database = match db_path(Some(data_dir.to_path_buf())).exists() {
+        true => DbWrapper::open(Some(data_dir.to_path_buf()))
+            .and_then(|db| database_layer(&db))
+            .unwrap_or_default(),
+        false => Default::default(),
+    };
+    let layers = ConfigLayers::new(database, overlays.clone());
+    layers
+        .section::<TerminalConfig>("terminal", &mut vec![])
+        .privileged_profiles()
+}
+
+fn systemctl(args: &[&str]) -> Result<String> {
+    let output = Command::new("systemctl")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("can not run systemctl: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn execute(step: &Step) -> Result<()> {
+    match step {
+        Step::Run(args) => {
+            let status = Command::new(&args[0])
+                .args(&args[1..])
+                .status()
+                .map_err(|e| anyhow!("{}: {}", args[0], e))?;
+            if !status.success() {
+                return Err(anyhow!("`{}` failed with {}", step, status));
+            }
+        }
+        Step::Write(path, content) => {
+            fs::write(path, content).map_err(|e| anyhow!("{}: {}", path.display(), e))?
+        }
+        Step::Remove(path) => {
+            if path.exists() {
+                fs::remove_file(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the steps, or run them in order stopping at the first failure.
+fn apply(steps: &[Step], dry_run: bool) -> Result<()> {
+    for step in steps {
+        match dry_run {
+            true => println!("# {}", step),
+            false => {
+                eprintln!("{}", step);
+                execute(step)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_system(dry_run: bool) -> Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+    if !cfg!(target_os = "linux") || !Path::new("/run/systemd/system").exists() {
+        return Err(anyhow!("systemd is not running on this system"));
+    }
+    if !is_root() {
+        return Err(anyhow!(
+            "run it as root, or with --dry-run to see the changes"
+        ));
+    }
+    Ok(())
+}
+
+/// `serverbee-web service ...`, installs this binary as a systemd service.
+pub fn run_service_command(args: &Args, action: ServiceAction) -> Result<()> {
+    let overlays = Overlays::load(args)?;
+    let path_of = |key: &str, default: &str| {
+        absolute(PathBuf::from(
+            overlays
+                .top_level(key)
+                .unwrap_or_else(|| default.to_string()),
+        ))
+    };
+    let unit = |name: String, user: String| -> Result<ServiceUnit> {
+        Ok(ServiceUnit {
+            name,
+            binary: std::env::current_exe()?.canonicalize()?,
+            user,
+            data_dir: path_of("data_dir", DEFAULT_DATA_DIR)?,
+            log_dir: path_of("log_dir", DEFAULT_LOG_DIR)?,
+            config: args
+                .config
+                .clone()
+                .map(PathBuf::from)
+                .map(absolute)
+                .transpose()?,
+            secret_key_file: overlays
+                .top_level("secret_key_file")
+                .map(PathBuf::from)
+                .map(absolute)
+                .transpose()?,
+            stop_timeout: MAX_SHUTDOWN_TIMEOUT + 5,
+        })
+    };
+    match action {
+        ServiceAction::Install {
+            name,
+            user,
+            dry_run,
+        } => {
+            check_system(dry_run)?;
+            let unit = unit(name, user)?;
+            if unit.user != "root"
+                && [Path::new("/root"), Path::new("/home")]
+                    .iter()
+                    .any(|dir| unit.binary.starts_with(dir))
+            {
+                eprintln!(
+                    "Warning: {} may not be readable by {}, consider moving it to /usr/local/bin",
+                    unit.binary.display(),
+                    unit.user
+                );
+            }
+            let profiles = privileged_profiles(&overlays, &unit.data_dir);
+            if unit.user != "root" && !profiles.is_empty() {
+                eprintln!(
+                    "Warning: terminal profiles {} set uid/gid, they can only be started when the service runs as root, install it with --user root",
+                    profiles.join(", ")
+                );
+            }
+            let create_user = unit.user != "root" && !user_exists(&unit.user);
+            let steps = unit.install_plan(create_user);
+            if dry_run {
+                println!("# {}", unit.path().display());
+                print!("{}", unit.render());
+                println!();
+            }
+            apply(&steps, dry_run)?;
+            if !dry_run {
+                eprintln!(
+                    "Installed {}, see `serverbee-web service status` and `journalctl -u {}`",
+                    unit.path().display(),
+                    unit.name
+                );
+            }
+        }
+        ServiceAction::Uninstall { name, dry_run } => {
+            check_system(dry_run)?;
+            let unit = unit(name, String::new())?;
+            if !dry_run && !unit.path().exists() {
+                return Err(anyhow!("{} is not installed", unit.path().display()));
+            }
+            apply(&unit.uninstall_plan(), dry_run)?;
+            if !dry_run {
+                eprintln!(
+                    "Removed {}, {} and its user are kept",
+                    unit.path().display(),
+                    unit.data_dir.display()
+                );
+            }
+        }
+        ServiceAction::Status { name } => {
+            let output = systemctl(&[
+                "show",
+                &name,
+                "--property=LoadState,FragmentPath,UnitFileState,ActiveState,SubState,MainPID,ActiveEnterTimestamp",
+            ])?;
+            let status: HashMap<&str, &str> = output
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect();
+            let get = |key: &str| status.get(key).copied().unwrap_or("-");
+            if get("LoadState") != "loaded" {
+                return Err(anyhow!(
+                    "{} is not installed, install it with `serverbee-web service install`",
+                    name
+                ));
+            }
+            println!("Unit:    {}", get("FragmentPath"));
+            println!("Enabled: {}", get("UnitFileState"));
+            println!("State:   {} ({})", get("ActiveState"), get("SubState"));
+            if get("MainPID") != "0" {
+                println!("PID:     {}", get("MainPID"));
+            }
+            if get("ActiveState") == "active" {
+                println!("Since:   {}", get("ActiveEnterTimestamp"));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Where systemd looks for units installed by the administrator.
+pub const UNIT_DIR: &str = "/etc/systemd/system";
+
+pub const DEFAULT_SERVICE_NAME: &str = "serverbee-web";
+
+pub const DEFAULT_SERVICE_USER: &str = "serverbee";
+
+/// Data directory of the service unless `-d` or the config file says otherwise
+pub const DEFAULT_DATA_DIR: &str = "/var/lib/serverbee";
+
+pub const DEFAULT_LOG_DIR: &str = "/var/log/serverbee";
+
+/// A long-running systemd service for serverbee-web.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceUnit {
+    pub name: String,
+    pub binary: PathBuf,
+    pub user: String,
+    pub data_dir: PathBuf,
+    pub log_dir: PathBuf,
+    pub config: Option<PathBuf>,
+    pub secret_key_file: Option<PathBuf>,
+    /// Seconds systemd waits for a stop before killing, above the server's own deadline.
+    pub stop_timeout: u64,
+}
+
+/// Quote an argument of `ExecStart` when systemd would split it.
+fn quote(value: &Path) -> String {
+    let value = value.display().to_string();
+    match value.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        true => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+        false => value,
+    }
+}
+
+impl ServiceUnit {
+    pub fn path(&self) -> PathBuf {
+        Path::new(UNIT_DIR).join(format!("{}.service", self.name))
+    }
+
+    fn exec_start(&self) -> String {
+        let mut args = vec![
+            quote(&self.binary),
+            "-d".to_string(),
+            quote(&self.data_dir),
+            "-l".to_string(),
+            quote(&self.log_dir),
+        ];
+        if let Some(config) = &self.config {
+            args.push("--config".to_string());
+            args.push(quote(config));
+        }
+        if let Some(secret_key_file) = &self.secret_key_file {
+            args.push("--secret-key-file".to_string());
+            args.push(quote(secret_key_file));
+        }
+        args.join(" ")
+    }
+
+    pub fn render(&self) -> String {
+        let mut writable = vec![quote(&self.data_dir)];
+        if self.log_dir != self.data_dir {
+            writable.push(quote(&self.log_dir));
+        }
+        format!(
+            r#"[Unit]
+Description=ServerBee Web
+Documentation=https://docs.serverbee.app
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=simple
+User={user}
+Group={user}
+ExecStart={exec_start}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+RestartSec=5
+TimeoutStopSec={stop_timeout}
+KillMode=mixed
+LimitNOFILE=65535
+
+# hardening, loosen with `systemctl edit {name}`
+NoNewPrivileges=true
+ProtectSystem=strict
+ReadWritePaths={writable}
+PrivateTmp=true
+ProtectKernelTunables=true
+ProtectKernelModules=true
+ProtectControlGroups=true
+RestrictSUIDSGID=true
+LockPersonality=true
+
+[Install]
+WantedBy=multi-user.target
+"#,
+            user = self.user,
+            exec_start = self.exec_start(),
+            stop_timeout = self.stop_timeout,
+            name = self.name,
+            writable = writable.join(" "),
+        )
+    }
+
+    /// Steps to install the unit and start it, the user is created when `create_user`.
+    pub fn install_plan(&self, create_user: bool) -> Vec<Step> {
+        let mut steps = vec![];
+        if create_user {
+            steps.push(Step::run(&[
+                "useradd",
+                "--system",
+                "--user-group",
+                "--no-create-home",
+                "--home-dir",
+                &self.data_dir.display().to_string(),
+                "--shell",
+                "/usr/sbin/nologin",
+                &self.user,
+            ]));
+        }
+        let mut dirs = vec![&self.data_dir];
+        if self.log_dir != self.data_dir {
+            dirs.push(&self.log_dir);
+        }
+        for dir in dirs {
+            steps.push(Step::run(&[
+                "install",
+                "-d",
+                "-o",
+                &self.user,
+                "-g",
+                &self.user,
+                "-m",
+                "0750",
+                &dir.display().to_string(),
+            ]));
+        }
+        steps.push(Step::Write(self.path(), self.render()));
+        steps.push(Step::run(&["systemctl", "daemon-reload"]));
+        steps.push(Step::run(&["systemctl", "enable", &self.name]));
+        // starts it, or moves a running one to the new unit
+        steps.push(Step::run(&["systemctl", "restart", &self.name]));
+        steps
+    }
+
+    /// Steps to stop the service and remove the unit, data and user are kept.
+    pub fn uninstall_plan(&self) -> Vec<Step> {
+        vec![
+            Step::run(&["systemctl", "disable", "--now", &self.name]),
+            Step::Remove(self.path()),
+            Step::run(&["systemctl", "daemon-reload"]),
+        ]
+    }
+}
+
+/// One change made by `service install` or `uninstall`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Run(Vec<String>),
+    Write(PathBuf, String),
+    Remove(PathBuf),
+}
+
+impl Step {
+    fn run(args: &[&str]) -> Step {
+        Step::Run(args.iter().map(|arg| arg.to_string()).collect())
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Run(args) => {
+                // as it would be typed into a shell
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| {
+                        match arg.contains(|c: char| c.is_whitespace() || "'\"$\\".contains(c)) {
+                            true => format!("'{}'", arg.replace('\'', "'\\''")),
+                            false => arg.clone(),
+                        }
+                    })
+                    .collect();
+                write!(f, "{}", args.join(" "))
+            }
+            Step::Write(path, _) => write!(f, "write {}", path.display()),
+            Step::Remove(path) => write!(f, "rm {}", path.display()),
+        }
+    }
+}
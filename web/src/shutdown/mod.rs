@@ -0,0 +1,129 @@
+use actix::{fut, Actor, ActorContext, ActorFutureExt, AsyncContext};
+use actix_web_actors::ws;
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Cancelled when the server stops, long running tasks and WebSockets watch it to stop in time.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// Run a task that returns on its own once `cancelled` fires, so it can finish what it was doing.
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, task: F) {
+        let handle = actix_rt::spawn(task);
+        self.tasks.lock().unwrap().push(handle);
+    }
+
+    /// Run a task that is dropped where it is when the server stops.
+    pub fn spawn_cancellable<F: Future<Output = ()> + 'static>(&self, task: F) {
+        let token = self.token.clone();
+        self.spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                _ = task => {}
+            }
+        });
+    }
+
+    /// Wait for the spawned tasks, call it after `cancel`.
+    pub async fn join(&self) {
+        let tasks: Vec<JoinHandle<()>> = self.tasks.lock().unwrap().drain(..).collect();
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Send a close frame and stop the WebSocket actor when the server stops.
+    pub fn close_on_shutdown<A>(&self, ctx: &mut ws::WebsocketContext<A>)
+    where
+        A: Actor<Context = ws::WebsocketContext<A>>,
+    {
+        let token = self.token.clone();
+        ctx.spawn(
+            fut::wrap_future::<_, A>(async move { token.cancelled().await }).map(
+                |_, _, ctx: &mut ws::WebsocketContext<A>| {
+                    ctx.close(Some(close_reason()));
+                    ctx.stop();
+                },
+            ),
+        );
+    }
+}
+
+/// Close frame telling a WebSocket client the server is going away.
+pub fn close_reason() -> ws::CloseReason {
+    ws::CloseReason {
+        code: ws::CloseCode::Away,
+        description: Some("Server is shutting down".into()),
+    }
+}
+
+/// What a signal asks the server to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalEvent {
+    /// Stop gracefully, with the name of the signal.
+    Terminate(&'static str),
+    /// Read the config file again and reopen the log file.
+    Reload,
+}
+
+/// SIGTERM and SIGINT stop the server, SIGHUP reloads it. Only Ctrl-C is handled on Windows.
+pub struct Signals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    pub fn new() -> io::Result<Signals> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Signals {
+                terminate: signal(SignalKind::terminate())?,
+                hangup: signal(SignalKind::hangup())?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Signals {})
+    }
+
+    pub async fn recv(&mut self) -> SignalEvent {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.terminate.recv() => SignalEvent::Terminate("SIGTERM"),
+                _ = tokio::signal::ctrl_c() => SignalEvent::Terminate("SIGINT"),
+                _ = self.hangup.recv() => SignalEvent::Reload,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            SignalEvent::Terminate("Ctrl-C")
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::access::{check, resolve_client_ip};
+use crate::config::access::{parse_cidr, AccessConfig, AccessGroup, AccessRule};
+use actix_web::test::TestRequest;
+use std::net::{IpAddr, SocketAddr};
+
+fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+}
+
+fn peer(value: &str) -> SocketAddr {
+    format!("{}:40000", value).parse().unwrap()
+}
+
+fn config(json: &str) -> AccessConfig {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_parse_cidr() {
+    assert!(parse_cidr("10.0.0.0/8").unwrap().contains(&ip("10.1.2.3")));
+    assert!(parse_cidr("192.168.1.7")
+        .unwrap()
+        .contains(&ip("192.168.1.7")));
+    assert!(!parse_cidr("192.168.1.7")
+        .unwrap()
+        .contains(&ip("192.168.1.8")));
+    assert!(parse_cidr("fd00::/8").unwrap().contains(&ip("fd12::1")));
+    assert!(parse_cidr("10.0.0.0/33").is_err());
+    assert!(parse_cidr("example.com").is_err());
+}
+
+#[test]
+fn test_rule_deny_wins_over_allow() {
+    let rule = AccessRule {
+        allow: vec!["10.0.0.0/8".to_string()],
+        deny: vec!["10.0.0.5".to_string()],
+    };
+    assert!(rule.permits(ip("10.0.0.4")));
+    assert!(!rule.permits(ip("10.0.0.5")));
+    assert!(!rule.permits(ip("192.168.0.1")));
+    assert!(AccessRule::default().permits(ip("192.168.0.1")));
+}
+
+#[test]
+fn test_group_of_path() {
+    assert_eq!(AccessGroup::of("/local/config"), AccessGroup::Local);
+    assert_eq!(AccessGroup::of("/localhost"), AccessGroup::Metrics);
+    assert_eq!(AccessGroup::of("/pty"), AccessGroup::Terminal);
+    assert_eq!(AccessGroup::of("/exec/jobs"), AccessGroup::Terminal);
+    assert_eq!(AccessGroup::of("/config/app"), AccessGroup::Config);
+    assert_eq!(AccessGroup::of("/users"), AccessGroup::Config);
+    assert_eq!(AccessGroup::of("/ws"), AccessGroup::Metrics);
+    assert_eq!(AccessGroup::of("/"), AccessGroup::Metrics);
+}
+
+#[test]
+fn test_validate_rejects_bad_cidr() {
+    assert!(AccessConfig::default().validate().is_ok());
+    let access = config(r#"{"terminal": {"allow": ["10.0.0.0/8", "nope"]}}"#);
+    assert!(access.validate().is_err());
+}
+
+#[test]
+fn test_forwarded_for_only_from_trusted_proxy() {
+    let access = config(r#"{"trusted_proxies": ["10.0.0.1"]}"#);
+    let proxied = TestRequest::default()
+        .peer_addr(peer("10.0.0.1"))
+        .insert_header(("X-Forwarded-For", "1.1.1.1, 203.0.113.9, 10.0.0.1"))
+        .to_http_request();
+    assert_eq!(
+        resolve_client_ip(&proxied, &access),
+        Some(ip("203.0.113.9"))
+    );
+
+    let spoofed = TestRequest::default()
+        .peer_addr(peer("198.51.100.2"))
+        .insert_header(("X-Forwarded-For", "127.0.0.1"))
+        .to_http_request();
+    assert_eq!(
+        resolve_client_ip(&spoofed, &access),
+        Some(ip("198.51.100.2"))
+    );
+}
+
+#[test]
+fn test_local_routes_by_socket_address() {
+    let access = AccessConfig::default();
+    let loopback = TestRequest::with_uri("/local/config")
+        .peer_addr(peer("127.0.0.1"))
+        .insert_header(("Host", "evil.example.com"))
+        .to_http_request();
+    assert_eq!(check(&loopback, &access), None);
+
+    let remote = TestRequest::with_uri("/local/config")
+        .peer_addr(peer("192.168.1.20"))
+        .insert_header(("Host", "localhost"))
+        .to_http_request();
+    assert!(check(&remote, &access).is_some());
+
+    let proxied = TestRequest::with_uri("/local/config")
+        .peer_addr(peer("127.0.0.1"))
+        .insert_header(("X-Forwarded-For", "192.168.1.20"))
+        .to_http_request();
+    assert!(check(&proxied, &access).is_some());
+}
+
+#[test]
+fn test_group_rule_applies_to_client() {
+    let access = config(r#"{"config": {"allow": ["192.168.0.0/16"]}}"#);
+    let inside = TestRequest::with_uri("/config/app")
+        .peer_addr(peer("192.168.3.4"))
+        .to_http_request();
+    assert_eq!(check(&inside, &access), None);
+    let outside = TestRequest::with_uri("/config/app")
+        .peer_addr(peer("203.0.113.9"))
+        .to_http_request();
+    assert!(check(&outside, &access).is_some());
+    let metrics = TestRequest::with_uri("/ws")
+        .peer_addr(peer("203.0.113.9"))
+        .to_http_request();
+    assert_eq!(check(&metrics, &access), None);
+}
@@ -0,0 +1,64 @@
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{hash_token, AccountStore, Role};
+use crate::token::scope::Scope;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+
+fn store(name: &str) -> AccountStore {
+    let dir = temp_dir().join(format!("serverbee-account-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    AccountStore::new(DbWrapper::new(Some(dir)).unwrap())
+}
+
+#[test]
+fn test_token_is_hashed_at_rest() {
+    let accounts = store("hashed");
+    let token = accounts
+        .upsert("hub", Role::Viewer, Some("secret".to_string()))
+        .unwrap();
+    assert_eq!(token, "secret");
+    let account = &accounts.list()[0];
+    assert_ne!(account.token_hash, "secret");
+    assert_eq!(account.token_hash, hash_token("secret"));
+    assert_eq!(accounts.authenticate("secret").unwrap().name, "hub");
+    assert!(accounts.authenticate("other").is_none());
+    assert!(accounts.authenticate("").is_none());
+}
+
+#[test]
+fn test_roles() {
+    let accounts = store("roles");
+    let token = accounts.upsert("ops", Role::Operator, None).unwrap();
+    assert_eq!(token.len(), 32);
+    let scopes = accounts.authenticate(&token).unwrap().role.scopes();
+    assert!(scopes.contains(&Scope::Metrics));
+    assert!(scopes.contains(&Scope::Exec));
+    assert!(!scopes.contains(&Scope::Terminal));
+    assert!(!scopes.contains(&Scope::Config));
+    assert_eq!(Role::Viewer.scopes(), vec![Scope::Metrics]);
+    assert_eq!(Role::Admin.scopes().len(), Scope::ALL.len());
+}
+
+#[test]
+fn test_upsert_and_remove() {
+    let accounts = store("upsert");
+    accounts
+        .upsert("admin", Role::Admin, Some("first".to_string()))
+        .unwrap();
+    accounts
+        .upsert("admin", Role::Admin, Some("second".to_string()))
+        .unwrap();
+    assert_eq!(accounts.list().len(), 1);
+    assert!(accounts.authenticate("first").is_none());
+    assert!(accounts.authenticate("second").is_some());
+
+    // tokens identify accounts, so they can't be shared
+    assert!(accounts
+        .upsert("viewer", Role::Viewer, Some("second".to_string()))
+        .is_err());
+    assert!(accounts.upsert("bad name", Role::Viewer, None).is_err());
+
+    assert!(accounts.remove("admin"));
+    assert!(!accounts.remove("admin"));
+    assert!(accounts.is_empty());
+}
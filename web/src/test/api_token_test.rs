@@ -0,0 +1,66 @@
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::api_token::ApiTokenStore;
+use crate::token::scope::Scope;
+use crate::utils::common_util::get_now_timestamp;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+
+fn store(name: &str) -> ApiTokenStore {
+    let dir = temp_dir().join(format!("serverbee-api-token-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    ApiTokenStore::new(DbWrapper::new(Some(dir)).unwrap())
+}
+
+#[test]
+fn test_create_and_authenticate() {
+    let tokens = store("create");
+    let (api_token, token) = tokens
+        .create("grafana", vec![Scope::Metrics], None)
+        .unwrap();
+    let found = tokens.authenticate(&token).unwrap();
+    assert_eq!(found.id, api_token.id);
+    assert_eq!(found.scopes, vec![Scope::Metrics]);
+    assert_ne!(found.token_hash, token);
+    assert!(tokens.authenticate("wrong").is_none());
+
+    assert!(tokens.create("", vec![Scope::Metrics], None).is_err());
+    assert!(tokens.create("empty", vec![], None).is_err());
+    assert!(tokens
+        .create("past", vec![Scope::Metrics], Some(get_now_timestamp() - 1))
+        .is_err());
+}
+
+#[test]
+fn test_expiry_and_revocation() {
+    let tokens = store("revoke");
+    let now = get_now_timestamp();
+    let (api_token, token) = tokens
+        .create("ci", vec![Scope::Exec], Some(now + 60))
+        .unwrap();
+    assert!(!api_token.is_expired(now));
+    assert!(api_token.is_expired(now + 60));
+
+    assert!(tokens.revoke(&api_token.id));
+    assert!(!tokens.revoke(&api_token.id));
+    assert!(tokens.authenticate(&token).unwrap().is_revoked());
+}
+
+#[test]
+fn test_touch_records_last_used() {
+    let tokens = store("touch");
+    let (api_token, _) = tokens
+        .create("grafana", vec![Scope::Metrics], None)
+        .unwrap();
+    assert!(tokens.list()[0].last_used.is_none());
+    tokens.touch(&api_token.id);
+    let last_used = tokens.list()[0].last_used;
+    assert!(last_used.is_some());
+    tokens.touch(&api_token.id);
+    assert_eq!(tokens.list()[0].last_used, last_used);
+}
+
+#[test]
+fn test_parse_scope() {
+    assert_eq!("terminal".parse::<Scope>(), Ok(Scope::Terminal));
+    assert!("root".parse::<Scope>().is_err());
+}
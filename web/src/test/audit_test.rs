@@ -0,0 +1,134 @@
+use crate::audit::action_of;
+use crate::audit::audit_log::{ActorKind, AuditEntry, AuditFilter, AuditLog, AuditResult};
+use crate::db::db_wrapper::DbWrapper;
+use actix_web::http::Method;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+
+fn log(name: &str) -> AuditLog {
+    let dir = temp_dir().join(format!("serverbee-audit-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    AuditLog::new(DbWrapper::new(Some(dir)).unwrap()).unwrap()
+}
+
+fn entry(timestamp: u64, actor: &str, action: &str, result: AuditResult) -> AuditEntry {
+    AuditEntry {
+        id: 0,
+        timestamp,
+        actor: actor.to_string(),
+        actor_kind: ActorKind::Account,
+        actor_id: None,
+        ip: "10.0.0.1".to_string(),
+        action: action.to_string(),
+        target: None,
+        result,
+        detail: None,
+    }
+}
+
+#[test]
+fn test_action_of_privileged_routes() {
+    assert_eq!(
+        action_of(&Method::POST, "/config/app"),
+        Some(("config.update", Some("app".to_string())))
+    );
+    assert_eq!(
+        action_of(&Method::POST, "/local/config/tls"),
+        Some(("config.update", Some("tls".to_string())))
+    );
+    assert_eq!(
+        action_of(&Method::DELETE, "/pty/sessions/deploy"),
+        Some(("terminal.close", Some("deploy".to_string())))
+    );
+    assert_eq!(
+        action_of(&Method::DELETE, "/local/bans/203.0.113.7"),
+        Some(("ban.lift", Some("203.0.113.7".to_string())))
+    );
+    assert_eq!(
+        action_of(&Method::POST, "/kill"),
+        Some(("process.kill", None))
+    );
+    assert_eq!(action_of(&Method::GET, "/config/app"), None);
+    assert_eq!(action_of(&Method::GET, "/ws"), None);
+    assert_eq!(action_of(&Method::GET, "/local/audit"), None);
+}
+
+#[test]
+fn test_append_and_query_newest_first() {
+    let log = log("query");
+    let first = log
+        .append(entry(100, "admin", "config.update", AuditResult::Success))
+        .unwrap();
+    let second = log
+        .append(entry(200, "ops", "process.kill", AuditResult::Failure))
+        .unwrap();
+    assert!(second.id > first.id);
+
+    let entries = log.query(&AuditFilter::default());
+    assert_eq!(entries, vec![second.clone(), first.clone()]);
+
+    let filter = AuditFilter {
+        action: Some("config".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(log.query(&filter), vec![first.clone()]);
+
+    let filter = AuditFilter {
+        result: Some(AuditResult::Failure),
+        ..Default::default()
+    };
+    assert_eq!(log.query(&filter), vec![second.clone()]);
+
+    let filter = AuditFilter {
+        since: Some(150),
+        ..Default::default()
+    };
+    assert_eq!(log.query(&filter), vec![second.clone()]);
+
+    let filter = AuditFilter {
+        before: Some(second.id),
+        ..Default::default()
+    };
+    assert_eq!(log.query(&filter), vec![first]);
+}
+
+#[test]
+fn test_export_json_lines_oldest_first() {
+    let log = log("export");
+    log.append(entry(100, "admin", "config.update", AuditResult::Success))
+        .unwrap();
+    log.append(entry(200, "admin", "token.reset", AuditResult::Success))
+        .unwrap();
+    let export = log.export(&AuditFilter::default());
+    let actions: Vec<String> = export
+        .lines()
+        .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap().action)
+        .collect();
+    assert_eq!(actions, vec!["config.update", "token.reset"]);
+}
+
+#[test]
+fn test_prune_by_age_and_count() {
+    let log = log("prune");
+    let day = 24 * 60 * 60;
+    let now = 100 * day;
+    log.append(entry(now - 40 * day, "a", "exec.run", AuditResult::Success))
+        .unwrap();
+    log.append(entry(now - 20 * day, "b", "exec.run", AuditResult::Success))
+        .unwrap();
+    log.append(entry(now - 10 * day, "c", "exec.run", AuditResult::Success))
+        .unwrap();
+    log.append(entry(now, "d", "exec.run", AuditResult::Success))
+        .unwrap();
+
+    assert_eq!(log.prune(30, 0, now), 1);
+    assert_eq!(log.count(), 3);
+    assert_eq!(log.prune(30, 2, now), 1);
+    let actors: Vec<String> = log
+        .query(&AuditFilter::default())
+        .into_iter()
+        .map(|entry| entry.actor)
+        .collect();
+    assert_eq!(actors, vec!["d", "c"]);
+    assert_eq!(log.prune(0, 0, now), 0);
+}
@@ -0,0 +1,88 @@
+use crate::config::server::ServerConfig;
+use crate::db::cipher::{generate_key, load_key, Cipher, KeySource};
+use crate::db::db_wrapper::DbWrapper;
+use crate::utils::common_util::mask_token;
+use std::env::temp_dir;
+use std::fs::{read_to_string, remove_dir_all};
+use std::path::PathBuf;
+
+fn dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("serverbee-cipher-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    dir
+}
+
+fn cipher() -> Cipher {
+    Cipher::new(&[7u8; 32]).unwrap()
+}
+
+#[test]
+fn test_seal_and_open() {
+    let cipher = cipher();
+    let sealed = cipher.seal("server-token");
+    assert!(Cipher::is_sealed(&sealed));
+    assert!(!sealed.contains("server-token"));
+    assert_ne!(sealed, cipher.seal("server-token"));
+    assert_eq!(cipher.open(&sealed).unwrap(), "server-token");
+
+    let other = Cipher::new(&[8u8; 32]).unwrap();
+    assert!(other.open(&sealed).is_err());
+    assert!(Cipher::new(&[7u8; 16]).is_err());
+}
+
+#[test]
+fn test_plaintext_is_migrated() {
+    let dir = dir("migrate");
+    let config = ServerConfig::new(Some("server-token".into()), None, false);
+    DbWrapper::new(Some(dir.clone()))
+        .unwrap()
+        .set("server_config", &config);
+
+    let db = DbWrapper::new(Some(dir)).unwrap().with_cipher(cipher());
+    let read = db.get_sealed::<ServerConfig>("server_config").unwrap();
+    assert_eq!(read, Some(config.clone()));
+
+    let stored = db.get::<ServerConfig>("server_config").unwrap().unwrap();
+    assert!(Cipher::is_sealed(&stored.token().unwrap()));
+    assert_eq!(
+        db.get_sealed::<ServerConfig>("server_config").unwrap(),
+        Some(config)
+    );
+}
+
+#[test]
+fn test_sealed_value_needs_key() {
+    let dir = dir("needs-key");
+    let db = DbWrapper::new(Some(dir)).unwrap().with_cipher(cipher());
+    db.set_secret("secret", "jwt-secret");
+    assert_eq!(db.get_secret("secret").unwrap(), Some("jwt-secret".into()));
+    drop(db);
+
+    let dir = temp_dir().join("serverbee-cipher-test-needs-key");
+    let plain = DbWrapper::new(Some(dir.clone())).unwrap();
+    assert!(plain.get_secret("secret").is_err());
+    drop(plain);
+    let wrong = DbWrapper::new(Some(dir))
+        .unwrap()
+        .with_cipher(Cipher::new(&[9u8; 32]).unwrap());
+    assert!(wrong.get_secret("secret").is_err());
+}
+
+#[test]
+fn test_key_file_is_generated_once() {
+    let dir = dir("key-file");
+    let path = dir.join("custom.key");
+    let (key, source) = load_key(Some(&path), &dir).unwrap();
+    assert_eq!(key.len(), 32);
+    assert_eq!(source, KeySource::File(path.clone()));
+    assert_eq!(read_to_string(&path).unwrap().trim().len(), 44);
+    assert_eq!(load_key(Some(&path), &dir).unwrap().0, key);
+    assert_eq!(generate_key().len(), 44);
+}
+
+#[test]
+fn test_mask_token() {
+    assert_eq!(mask_token("abcdefghijklmnop"), "abcd********mnop");
+    assert_eq!(mask_token("short"), "********");
+    assert_eq!(mask_token(""), "********");
+}
@@ -0,0 +1,181 @@
+use crate::config::constant::{CONFIG_DOCUMENT_VERSION, SERVER_CONFIG, WEB_SERVER_CONFIG};
+use crate::config::document::{ConfigDocument, ConfigFormat, ConfigSections, Credentials};
+use crate::config::server::ServerConfig;
+use crate::config::web_server::WebServerConfig;
+use crate::db::cipher::Cipher;
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{hash_token, Account, AccountStore, Role};
+use crate::token::api_token::ApiTokenStore;
+use crate::token::scope::Scope;
+use serde_json::json;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+
+fn sections() -> ConfigSections {
+    ConfigSections {
+        web_server: Some(WebServerConfig::new(9600)),
+        server: Some(ServerConfig::new(
+            Some("server-token".into()),
+            Some("serverhub.app".into()),
+            false,
+        )),
+        access: Some(Default::default()),
+        ..Default::default()
+    }
+}
+
+fn db(name: &str) -> DbWrapper {
+    let dir = temp_dir().join(format!("serverbee-document-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    DbWrapper::new(Some(dir))
+        .unwrap()
+        .with_cipher(Cipher::new(&[7u8; 32]).unwrap())
+}
+
+#[test]
+fn test_round_trip() {
+    let document = ConfigDocument::new(&sections(), Credentials::default(), false);
+    assert_eq!(document.version, CONFIG_DOCUMENT_VERSION);
+    for format in [ConfigFormat::Yaml, ConfigFormat::Json] {
+        let content = document.format(format).unwrap();
+        let parsed = ConfigDocument::parse(&content).unwrap();
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.sections().unwrap(), sections());
+    }
+    assert_eq!(sections().names(), vec!["access", "server", "web_server"]);
+}
+
+#[test]
+fn test_redact() {
+    let document = ConfigDocument::new(&sections(), Credentials::default(), true);
+    assert!(document.redacted);
+    assert_eq!(document.config["server"]["token"], json!(null));
+    assert_eq!(document.config["server"]["host"], json!("serverhub.app"));
+    assert!(!document
+        .format(ConfigFormat::Yaml)
+        .unwrap()
+        .contains("server-token"));
+}
+
+#[test]
+fn test_validation() {
+    let parse = |content: &str| ConfigDocument::parse(content).and_then(|d| d.sections());
+    assert_eq!(
+        parse(r#"{"version": 1, "config": {}}"#).unwrap(),
+        ConfigSections::default()
+    );
+    assert!(parse(r#"{"version": 2, "config": {}}"#)
+        .unwrap_err()
+        .to_string()
+        .contains("version 2"));
+    assert!(parse(r#"{"version": 0, "config": {}}"#).is_err());
+    assert!(parse(r#"{"config": {}}"#).is_err());
+    assert!(parse("version: 1\nconfig: []\n").is_err());
+
+    let error = parse(
+        r#"{"version": 1, "config": {
+            "web_server": {"port": "http"},
+            "access": {"metrics": {"allow": ["10.0.0.0/33"]}},
+            "server": {"tokn": "x"},
+            "data_dir": "/tmp"
+        }}"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("web_server: "));
+    assert!(error.contains("access: "));
+    assert!(error.contains("server.tokn"));
+    assert!(error.contains("data_dir: "));
+}
+
+#[test]
+fn test_store() {
+    let db = db("store");
+    sections().store(&db, None).unwrap();
+    assert_eq!(
+        db.get::<WebServerConfig>(WEB_SERVER_CONFIG).unwrap(),
+        Some(WebServerConfig::new(9600))
+    );
+
+    // a redacted import keeps the token in place
+    let redacted = ConfigDocument::new(&sections(), Credentials::default(), true)
+        .sections()
+        .unwrap();
+    redacted.store(&db, None).unwrap();
+    let server = db
+        .get_sealed::<ServerConfig>(SERVER_CONFIG)
+        .unwrap()
+        .unwrap();
+    assert_eq!(server.token().as_deref(), Some("server-token"));
+
+    let with_app: ConfigSections = ConfigDocument::parse(
+        r#"{"version": 1, "config": {"app": {"token": "a-new-admin-token"}}}"#,
+    )
+    .unwrap()
+    .sections()
+    .unwrap();
+    with_app.store(&db, None).unwrap();
+    assert!(AccountStore::new(db.clone())
+        .authenticate("a-new-admin-token")
+        .is_some());
+}
+
+fn credentials() -> Credentials {
+    let source = db("credentials-source");
+    AccountStore::new(source.clone())
+        .upsert("ops", Role::Operator, Some("ops-token".into()))
+        .unwrap();
+    ApiTokenStore::new(source.clone())
+        .create("scraper", vec![Scope::Metrics], None)
+        .unwrap();
+    Credentials::read(&source)
+}
+
+#[test]
+fn test_export_credentials() {
+    let credentials = credentials();
+    let document = ConfigDocument::new(&sections(), credentials.clone(), false);
+    let content = document.format(ConfigFormat::Yaml).unwrap();
+    assert!(content.contains(&hash_token("ops-token")));
+    let parsed = ConfigDocument::parse(&content).unwrap();
+    assert_eq!(parsed.credentials, Some(credentials.clone()));
+
+    let redacted = ConfigDocument::new(&sections(), credentials, true);
+    assert_eq!(redacted.credentials, None);
+    assert!(!redacted
+        .format(ConfigFormat::Json)
+        .unwrap()
+        .contains("credentials"));
+}
+
+#[test]
+fn test_store_credentials() {
+    let credentials = credentials();
+    let db = db("store-credentials");
+    sections().store(&db, Some(&credentials)).unwrap();
+    let account = AccountStore::new(db.clone())
+        .authenticate("ops-token")
+        .unwrap();
+    assert_eq!(account.role, Role::Operator);
+    assert_eq!(
+        ApiTokenStore::new(db.clone()).list(),
+        credentials.api_tokens
+    );
+}
+
+#[test]
+fn test_store_nothing_when_invalid() {
+    let db = db("store-invalid");
+    let credentials = Credentials {
+        accounts: vec![Account {
+            name: "ops".into(),
+            role: Role::Operator,
+            token_hash: "not-a-hash".into(),
+            created_at: 1,
+        }],
+        ..Default::default()
+    };
+    assert!(sections().store(&db, Some(&credentials)).is_err());
+    assert_eq!(db.get::<WebServerConfig>(WEB_SERVER_CONFIG).unwrap(), None);
+    assert!(AccountStore::new(db.clone()).is_empty());
+}
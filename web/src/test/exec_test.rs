@@ -0,0 +1,154 @@
+use crate::config::exec::ExecConfig;
+use crate::exec::executor::{prepare, run, stream, ExecEvent, ExecRequest};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+fn exec_config(allow_free_form: bool, max_output: usize) -> ExecConfig {
+    serde_json::from_value(serde_json::json!({
+        "scripts": [{
+            "name": "greet",
+            "command": "sh",
+            "args": ["-c", "echo hello $NAME"],
+            "env": { "NAME": "bee" },
+        }],
+        "allow_free_form": allow_free_form,
+        "max_output": max_output,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_prepare_rejects_disallowed_requests() {
+    let config = exec_config(false, 1024);
+    let free_form = ExecRequest {
+        command: Some("echo hi".to_string()),
+        ..Default::default()
+    };
+    assert!(prepare(&config, &free_form).is_err());
+    let unknown = ExecRequest {
+        script: Some("missing".to_string()),
+        ..Default::default()
+    };
+    assert!(prepare(&config, &unknown).is_err());
+    assert!(prepare(&config, &ExecRequest::default()).is_err());
+}
+
+#[test]
+fn test_prepare_rejects_script_env_and_cwd() {
+    let config = exec_config(false, 1024);
+    let mut with_env = ExecRequest {
+        script: Some("greet".to_string()),
+        ..Default::default()
+    };
+    with_env
+        .env
+        .insert("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string());
+    assert!(prepare(&config, &with_env).is_err());
+    let with_cwd = ExecRequest {
+        script: Some("greet".to_string()),
+        cwd: Some("/tmp".to_string()),
+        ..Default::default()
+    };
+    assert!(prepare(&config, &with_cwd).is_err());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_run_script_with_env() {
+    let config = exec_config(false, 1024);
+    let request = ExecRequest {
+        script: Some("greet".to_string()),
+        ..Default::default()
+    };
+    let cmd = prepare(&config, &request).unwrap();
+    let output = run(cmd, Duration::from_secs(10), config.max_output())
+        .await
+        .unwrap();
+    assert_eq!(output.exit_code, Some(0));
+    assert_eq!(output.stdout, "hello bee\n");
+    assert!(!output.timed_out);
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_run_free_form_exit_code_and_truncation() {
+    let config = exec_config(true, 4);
+    let request = ExecRequest {
+        command: Some("echo 123456 >&2; exit 7".to_string()),
+        ..Default::default()
+    };
+    let cmd = prepare(&config, &request).unwrap();
+    let output = run(cmd, Duration::from_secs(10), config.max_output())
+        .await
+        .unwrap();
+    assert_eq!(output.exit_code, Some(7));
+    assert_eq!(output.stderr, "1234");
+    assert!(output.truncated);
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_run_times_out() {
+    let config = exec_config(true, 1024);
+    let request = ExecRequest {
+        command: Some("sleep 30".to_string()),
+        ..Default::default()
+    };
+    let cmd = prepare(&config, &request).unwrap();
+    let output = run(cmd, Duration::from_millis(200), config.max_output())
+        .await
+        .unwrap();
+    assert!(output.timed_out);
+    assert_eq!(output.exit_code, None);
+    assert!(output.duration < 5000);
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_run_hides_server_env() {
+    std::env::set_var("SERVERBEE_EXEC_TEST_SECRET", "secret");
+    let config = exec_config(true, 1024);
+    let request = ExecRequest {
+        command: Some("echo \"[$SERVERBEE_EXEC_TEST_SECRET]\"".to_string()),
+        ..Default::default()
+    };
+    let cmd = prepare(&config, &request).unwrap();
+    let output = run(cmd, Duration::from_secs(10), config.max_output())
+        .await
+        .unwrap();
+    std::env::remove_var("SERVERBEE_EXEC_TEST_SECRET");
+    assert_eq!(output.stdout, "[]\n");
+}
+
+#[test]
+fn test_timeout_is_at_least_one_second() {
+    let config = exec_config(false, 1024);
+    assert_eq!(config.timeout(Some(0)), 1);
+    assert!(config.validate().is_ok());
+    let config: ExecConfig = serde_json::from_str(r#"{"default_timeout": 0}"#).unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_stream_keeps_split_character() {
+    let config = exec_config(true, 1024);
+    let request = ExecRequest {
+        command: Some(r"printf '\344\275'; sleep 0.2; printf '\240\n'".to_string()),
+        ..Default::default()
+    };
+    let cmd = prepare(&config, &request).unwrap();
+    let mut stdout = String::new();
+    stream(
+        cmd,
+        Duration::from_secs(10),
+        CancellationToken::new(),
+        |event| {
+            if let ExecEvent::Stdout { data } = event {
+                stdout.push_str(&data);
+            }
+        },
+    )
+    .await;
+    assert_eq!(stdout, "你\n");
+}
@@ -0,0 +1,174 @@
+use crate::cli::Args;
+use crate::config::document::ConfigSections;
+use crate::config::layers::{
+    cli_layer, env_layer, parse_file, ConfigLayers, Overlays, ValueSource,
+};
+use crate::config::server::ServerConfig;
+use crate::config::tls::TlsConfig;
+use crate::config::web_server::WebServerConfig;
+use clap::Parser;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+fn env(vars: &[(&str, &str)]) -> (Value, BTreeMap<String, String>) {
+    let vars: BTreeMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    env_layer(|name| vars.get(name).cloned())
+}
+
+fn layers(database: Value, file: Value, env_vars: &[(&str, &str)], args: &[&str]) -> ConfigLayers {
+    let (env, ambiguous) = env(env_vars);
+    let mut argv = vec!["serverbee-web"];
+    argv.extend(args);
+    let cli = cli_layer(&Args::parse_from(argv));
+    ConfigLayers::new(
+        database,
+        Overlays::new(
+            Some(PathBuf::from("/etc/serverbee.yml")),
+            file,
+            env,
+            ambiguous,
+            cli,
+        ),
+    )
+}
+
+#[test]
+fn test_parse_file() {
+    let yaml = "web_server:\n  port: 9600\naccess:\n  metrics:\n    allow: [10.0.0.0/8]\n";
+    let value = parse_file(Path::new("serverbee.yml"), yaml).unwrap();
+    assert_eq!(value["web_server"]["port"], json!(9600));
+    assert_eq!(value["access"]["metrics"]["allow"], json!(["10.0.0.0/8"]));
+
+    let toml = "data_dir = \"/var/lib/serverbee\"\n[tls]\nenabled = true\nself_signed = true\n";
+    let value = parse_file(Path::new("serverbee.toml"), toml).unwrap();
+    assert_eq!(value["data_dir"], json!("/var/lib/serverbee"));
+    assert_eq!(value["tls"]["self_signed"], json!(true));
+
+    assert!(parse_file(Path::new("empty.yml"), "").unwrap().is_object());
+    let error = parse_file(Path::new("typo.yml"), "web_server:\n  prot: 1\nfoo: 2\n")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("web_server.prot"));
+    assert!(error.contains("foo"));
+    assert!(parse_file(Path::new("list.yml"), "- 1\n").is_err());
+    assert!(parse_file(Path::new("broken.toml"), "[tls\n").is_err());
+}
+
+#[test]
+fn test_env_layer() {
+    let (layer, ambiguous) = env(&[
+        ("SERVERBEE_WEB_SERVER_PORT", "9600"),
+        ("SERVERBEE_SERVER_HOST", "example.com"),
+        ("SERVERBEE_SERVER_TOKEN", "123456"),
+        (
+            "SERVERBEE_ACCESS_METRICS_ALLOW",
+            "10.0.0.0/8, 192.168.0.0/16",
+        ),
+        ("SERVERBEE_ACCESS_CONFIG_DENY", "[\"0.0.0.0/0\"]"),
+        ("SERVERBEE_UNKNOWN", "1"),
+    ]);
+    assert_eq!(layer["web_server"]["port"], json!(9600));
+    assert_eq!(layer["server"]["host"], json!("example.com"));
+    assert_eq!(layer["server"]["token"], json!(123456));
+    assert_eq!(ambiguous.get("server.token").unwrap(), "123456");
+    assert_eq!(
+        layer["access"]["metrics"]["allow"],
+        json!(["10.0.0.0/8", "192.168.0.0/16"])
+    );
+    assert_eq!(layer["access"]["config"]["deny"], json!(["0.0.0.0/0"]));
+    assert!(layer.get("unknown").is_none());
+}
+
+#[test]
+fn test_precedence() {
+    let layers = layers(
+        json!({ "web_server": { "port": 9001 }, "server": { "host": "db.example.com" } }),
+        json!({ "web_server": { "port": 9002 }, "tls": { "reload_interval": 60 } }),
+        &[
+            ("SERVERBEE_WEB_SERVER_PORT", "9003"),
+            ("SERVERBEE_SERVER_TOKEN", "123456"),
+        ],
+        &["-p", "9004", "--tls-self-signed"],
+    );
+    let mut errors = vec![];
+    let web_server: WebServerConfig = layers.section("web_server", &mut errors);
+    let server: ServerConfig = layers.section("server", &mut errors);
+    let tls: TlsConfig = layers.section("tls", &mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    assert_eq!(web_server.port(), 9004);
+    assert_eq!(layers.source("web_server.port"), ValueSource::Cli);
+    // read as a number from the environment, but the token is a string
+    assert_eq!(server.token().as_deref(), Some("123456"));
+    assert_eq!(layers.source("server.token"), ValueSource::Env);
+    assert_eq!(server.host().as_deref(), Some("db.example.com"));
+    assert_eq!(layers.source("server.host"), ValueSource::Database);
+    assert_eq!(tls.reload_interval(), 60);
+    assert_eq!(layers.source("tls.reload_interval"), ValueSource::File);
+    assert!(tls.enabled() && tls.self_signed());
+    assert_eq!(layers.source("tls.enabled"), ValueSource::Cli);
+    assert_eq!(layers.source("audit.retention_days"), ValueSource::Default);
+}
+
+#[test]
+fn test_section_errors() {
+    let layers = layers(
+        json!({}),
+        json!({ "web_server": { "port": 70000 } }),
+        &[],
+        &[],
+    );
+    let mut errors = vec![];
+    let web_server: WebServerConfig = layers.section("web_server", &mut errors);
+    assert_eq!(web_server, WebServerConfig::default());
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("web_server: "));
+    assert!(errors[0].contains("the config file /etc/serverbee.yml"));
+}
+
+#[test]
+fn test_sections_are_validated() {
+    let layers = layers(
+        json!({}),
+        json!({ "access": { "metrics": { "allow": ["10.0.0.0/33"] } } }),
+        &[],
+        &[],
+    );
+    let error = ConfigSections::from_layers(&layers)
+        .unwrap_err()
+        .to_string();
+    assert!(error.starts_with("access: "));
+    assert!(error.contains("(set by the config file /etc/serverbee.yml)"));
+}
+
+#[test]
+fn test_effective() {
+    let mut layers = layers(
+        json!({}),
+        json!({ "web_server": { "port": 9002 } }),
+        &[("SERVERBEE_SERVER_TOKEN", "a-long-server-token-value")],
+        &[],
+    );
+    layers.settle(layers.value());
+
+    let mut current = layers.value();
+    current["audit"]["retention_days"] = json!(7);
+    let effective = layers.effective(&current);
+    assert_eq!(
+        effective["web_server.port"],
+        (json!(9002), ValueSource::File)
+    );
+    assert_eq!(
+        effective["server.token"],
+        (json!("a-lo********alue"), ValueSource::Env)
+    );
+    assert_eq!(
+        effective["audit.retention_days"],
+        (json!(7), ValueSource::Database)
+    );
+    assert_eq!(effective["data_dir"], (Value::Null, ValueSource::Default));
+}
@@ -0,0 +1,119 @@
+use crate::config::rate_limit::RateLimitConfig;
+use crate::limiter::lockout::Lockout;
+use crate::limiter::rate_limiter::{RateLimiter, MAX_BUCKETS};
+use std::time::{Duration, Instant};
+
+fn config() -> RateLimitConfig {
+    serde_json::from_str(
+        r#"{"max_auth_failures": 3, "failure_window": 60, "ban_duration": 10, "max_ban_duration": 35}"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_ban_duration_doubles_up_to_max() {
+    let config = config();
+    assert_eq!(config.ban_duration_for(1), 10);
+    assert_eq!(config.ban_duration_for(2), 20);
+    assert_eq!(config.ban_duration_for(3), 35);
+    assert_eq!(config.ban_duration_for(100), 35);
+}
+
+#[test]
+fn test_lockout_after_repeated_failures() {
+    let config = config();
+    let lockout = Lockout::new();
+    let now = 1_000;
+    assert_eq!(lockout.record_failure("10.0.0.1", &config, now), None);
+    assert_eq!(lockout.record_failure("10.0.0.1", &config, now + 1), None);
+    assert_eq!(lockout.banned_for("10.0.0.1", now + 1), None);
+    assert_eq!(
+        lockout.record_failure("10.0.0.1", &config, now + 2),
+        Some(10)
+    );
+    assert_eq!(lockout.banned_for("10.0.0.1", now + 2), Some(10));
+    assert_eq!(lockout.banned_for("10.0.0.2", now + 2), None);
+
+    let bans = lockout.bans(now + 2);
+    assert_eq!(bans.len(), 1);
+    assert_eq!(bans[0].ip, "10.0.0.1");
+    assert_eq!(bans[0].banned_until, now + 12);
+
+    // the next ban after it expires is twice as long
+    let later = now + 20;
+    assert!(lockout.bans(later).is_empty());
+    for offset in 0..2 {
+        assert_eq!(
+            lockout.record_failure("10.0.0.1", &config, later + offset),
+            None
+        );
+    }
+    assert_eq!(
+        lockout.record_failure("10.0.0.1", &config, later + 2),
+        Some(20)
+    );
+
+    assert!(lockout.unban("10.0.0.1"));
+    assert_eq!(lockout.banned_for("10.0.0.1", later + 2), None);
+    assert!(!lockout.unban("10.0.0.1"));
+}
+
+#[test]
+fn test_lockout_window_and_success() {
+    let config = config();
+    let lockout = Lockout::new();
+    lockout.record_failure("10.0.0.3", &config, 0);
+    lockout.record_failure("10.0.0.3", &config, 1);
+    // old failures fall out of the window
+    assert_eq!(lockout.record_failure("10.0.0.3", &config, 100), None);
+
+    lockout.record_failure("10.0.0.3", &config, 101);
+    lockout.record_success("10.0.0.3");
+    assert_eq!(lockout.record_failure("10.0.0.3", &config, 102), None);
+
+    // a success clears the failures but not the bans
+    for now in 200..203 {
+        lockout.record_failure("10.0.0.5", &config, now);
+    }
+    lockout.record_success("10.0.0.5");
+    assert_eq!(lockout.banned_for("10.0.0.5", 203), Some(9));
+    lockout.record_failure("10.0.0.5", &config, 220);
+    lockout.record_failure("10.0.0.5", &config, 221);
+    assert_eq!(lockout.record_failure("10.0.0.5", &config, 222), Some(20));
+
+    let disabled: RateLimitConfig = serde_json::from_str(r#"{"max_auth_failures": 0}"#).unwrap();
+    for now in 0..10 {
+        assert_eq!(lockout.record_failure("10.0.0.4", &disabled, now), None);
+    }
+}
+
+#[test]
+fn test_rate_limiter_refills() {
+    let limiter = RateLimiter::new();
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.allow_at("10.0.0.1", 3, start));
+    }
+    assert!(!limiter.allow_at("10.0.0.1", 3, start));
+    assert!(limiter.allow_at("10.0.0.2", 3, start));
+    assert!(limiter.allow_at("10.0.0.1", 3, start + Duration::from_secs(20)));
+    assert!(!limiter.allow_at("10.0.0.1", 3, start + Duration::from_secs(21)));
+    assert!(limiter.allow_at("10.0.0.1", 0, start));
+}
+
+#[test]
+fn test_rate_limiter_evicts_oldest_when_full() {
+    let limiter = RateLimiter::new();
+    let start = Instant::now();
+    for i in 0..MAX_BUCKETS {
+        let now = start + Duration::from_millis(i as u64);
+        assert!(limiter.allow_at(&format!("ip-{}", i), 3, now));
+    }
+    let now = start + Duration::from_millis(MAX_BUCKETS as u64);
+    assert!(limiter.allow_at("10.0.0.1", 3, now));
+    // ip-0 was dropped to make room and starts over with a full bucket
+    for _ in 0..3 {
+        assert!(limiter.allow_at("ip-0", 3, now));
+    }
+    assert!(!limiter.allow_at("ip-0", 3, now));
+}
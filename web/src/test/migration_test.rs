@@ -0,0 +1,185 @@
+use crate::audit::audit_log::{ActorKind, AuditEntry, AuditFilter, AuditLog, AuditResult};
+use crate::config::app::AppConfig;
+use crate::config::constant::{APP_CONFIG, SCHEMA_VERSION, SERVER_CONFIG, TLS_CONFIG};
+use crate::config::layers::database_layer;
+use crate::config::server::ServerConfig;
+use crate::db::cipher::Cipher;
+use crate::db::db_command::malformed_values;
+use crate::db::db_wrapper::{db_path, is_corrupt, is_locked, DbWrapper};
+use crate::db::migration::{latest_version, migrate, pending, schema_version};
+use crate::token::account::AccountStore;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::Duration;
+
+fn dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("serverbee-migration-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    dir
+}
+
+/// sled lets go of its lock a moment after the last handle is dropped.
+fn wait_unlocked(dir: &Path) {
+    let path = db_path(Some(dir.to_path_buf()));
+    let probe = sled::Error::Io(Error::other("probe"));
+    for _ in 0..50 {
+        if !is_locked(&path, &probe) {
+            return;
+        }
+        sleep(Duration::from_millis(100));
+    }
+}
+
+fn cipher() -> Cipher {
+    Cipher::new(&[7u8; 32]).unwrap()
+}
+
+#[test]
+fn test_new_database_starts_at_latest() {
+    let db = DbWrapper::new(Some(dir("new"))).unwrap();
+    assert!(migrate(&db).unwrap().is_empty());
+    assert_eq!(schema_version(&db).unwrap(), latest_version());
+    assert!(pending(&db).unwrap().is_empty());
+}
+
+#[test]
+fn test_legacy_database_is_migrated() {
+    let dir = dir("legacy");
+    let legacy = DbWrapper::new(Some(dir.clone())).unwrap();
+    legacy.set(APP_CONFIG, &AppConfig::new(Some("app-token".into())));
+    legacy.set(
+        SERVER_CONFIG,
+        &ServerConfig::new(Some("server-token".into()), None, false),
+    );
+    drop(legacy);
+
+    let db = DbWrapper::new(Some(dir)).unwrap().with_cipher(cipher());
+    assert_eq!(schema_version(&db).unwrap(), 0);
+    let applied: Vec<u32> = migrate(&db).unwrap().iter().map(|m| m.version).collect();
+    assert_eq!(applied, vec![1, 2]);
+    assert_eq!(schema_version(&db).unwrap(), latest_version());
+
+    assert!(AccountStore::new(db.clone())
+        .authenticate("app-token")
+        .is_some());
+    assert_eq!(db.get::<AppConfig>(APP_CONFIG).unwrap(), None);
+    let stored = db.get::<ServerConfig>(SERVER_CONFIG).unwrap().unwrap();
+    assert!(Cipher::is_sealed(&stored.token().unwrap()));
+
+    // applied once
+    assert!(migrate(&db).unwrap().is_empty());
+}
+
+#[test]
+fn test_newer_schema_is_refused() {
+    let db = DbWrapper::new(Some(dir("newer"))).unwrap();
+    db.set(SCHEMA_VERSION, &(latest_version() + 1));
+    let e = migrate(&db).unwrap_err().to_string();
+    assert!(e.contains("upgrade serverbee-web"), "{}", e);
+}
+
+#[test]
+fn test_malformed_values_are_reported() {
+    let db = DbWrapper::new(Some(dir("malformed"))).unwrap();
+    db.set(TLS_CONFIG, &"not a tls config");
+    db.set(SCHEMA_VERSION, &"two");
+
+    let keys: Vec<String> = malformed_values(&db).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(
+        keys,
+        vec![SCHEMA_VERSION.to_string(), TLS_CONFIG.to_string()]
+    );
+    let e = database_layer(&db).unwrap_err().to_string();
+    assert!(
+        e.contains(TLS_CONFIG) && e.contains("db check --fix"),
+        "{}",
+        e
+    );
+
+    db.remove(TLS_CONFIG).unwrap();
+    db.remove(SCHEMA_VERSION).unwrap();
+    assert!(malformed_values(&db).is_empty());
+    assert!(database_layer(&db).is_ok());
+}
+
+#[test]
+fn test_locked_database_is_reported() {
+    let dir = dir("locked");
+    let _db = DbWrapper::new(Some(dir.clone())).unwrap();
+    let e = DbWrapper::new(Some(dir)).unwrap_err().to_string();
+    assert!(e.contains("already running"), "{}", e);
+}
+
+#[test]
+fn test_lock_is_found_whatever_the_message() {
+    let dir = dir("lock-message");
+    let path = db_path(Some(dir.clone()));
+    let other = sled::Error::Io(Error::other("worded differently"));
+    let would_block = sled::Error::Io(Error::from(ErrorKind::WouldBlock));
+    assert!(is_locked(&path, &would_block));
+
+    let db = DbWrapper::new(Some(dir.clone())).unwrap();
+    assert!(is_locked(&path, &other));
+    drop(db);
+    wait_unlocked(&dir);
+    assert!(!is_locked(&path, &other));
+}
+
+#[test]
+fn test_io_errors_are_not_corruption() {
+    for kind in [ErrorKind::InvalidData, ErrorKind::UnexpectedEof] {
+        assert!(!is_corrupt(&sled::Error::Io(Error::from(kind))));
+    }
+    assert!(is_corrupt(&sled::Error::Unsupported("conf".into())));
+}
+
+#[test]
+fn test_compact_keeps_values() {
+    let dir = dir("compact");
+    let db = DbWrapper::new(Some(dir.clone())).unwrap();
+    for i in 0..100u32 {
+        db.set("last_login", &i);
+    }
+    db.flush().unwrap();
+    drop(db);
+    wait_unlocked(&dir);
+
+    DbWrapper::compact(Some(dir.clone())).unwrap();
+    let db = DbWrapper::new(Some(dir)).unwrap();
+    assert_eq!(db.get::<u32>("last_login").unwrap(), Some(99));
+}
+
+#[test]
+fn test_compact_keeps_audit_entries() {
+    let dir = dir("compact-audit");
+    let log = AuditLog::new(DbWrapper::new(Some(dir.clone())).unwrap()).unwrap();
+    let entry = |actor: &str| AuditEntry {
+        id: 0,
+        timestamp: 1,
+        actor: actor.to_string(),
+        actor_kind: ActorKind::Account,
+        actor_id: None,
+        ip: "10.0.0.1".to_string(),
+        action: "login".to_string(),
+        target: None,
+        result: AuditResult::Success,
+        detail: None,
+    };
+    let first = log.append(entry("first")).unwrap();
+    drop(log);
+    wait_unlocked(&dir);
+
+    DbWrapper::compact(Some(dir.clone())).unwrap();
+    let log = AuditLog::new(DbWrapper::new(Some(dir)).unwrap()).unwrap();
+    let second = log.append(entry("second")).unwrap();
+    assert!(second.id > first.id);
+    let actors: Vec<_> = log
+        .query(&AuditFilter::default())
+        .into_iter()
+        .map(|entry| entry.actor)
+        .collect();
+    assert_eq!(actors, vec!["second", "first"]);
+}
@@ -1 +1,37 @@
+#[cfg(test)]
+mod access_test;
+#[cfg(test)]
+mod account_test;
+#[cfg(test)]
+mod api_token_test;
+#[cfg(test)]
+mod audit_test;
+#[cfg(test)]
+mod cipher_test;
+#[cfg(test)]
+mod document_test;
+#[cfg(test)]
+mod exec_test;
+#[cfg(test)]
+mod layers_test;
+#[cfg(test)]
+mod limiter_test;
+#[cfg(test)]
+mod migration_test;
+#[cfg(test)]
+mod profile_test;
+#[cfg(test)]
+mod pty_manager_test;
+#[cfg(test)]
+mod rebind_test;
+#[cfg(test)]
+mod recording_test;
+#[cfg(test)]
+mod service_test;
+#[cfg(test)]
+mod session_test;
+#[cfg(test)]
+mod shutdown_test;
 mod system_info_test;
+#[cfg(test)]
+mod tls_test;
@@ -0,0 +1,105 @@
+use crate::config::terminal::TerminalConfig;
+use crate::pty::profile::{check_switch_user, command_for, resolve_profile};
+
+fn config() -> TerminalConfig {
+    serde_json::from_str(
+        r#"{
+            "profiles": [
+                {"name": "ops", "command": "/bin/bash", "args": ["-l"], "uid": 1000},
+                {"name": "restricted", "command": "/bin/sh", "env": {"PS1": "$ "}}
+            ],
+            "default_profile": "restricted"
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_resolve_configured_profile() {
+    let profile = resolve_profile(&config(), Some("ops")).unwrap();
+    assert_eq!(profile.command, "/bin/bash");
+    assert_eq!(profile.args, vec!["-l"]);
+    assert_eq!(profile.uid, Some(1000));
+}
+
+#[test]
+fn test_resolve_builtin_shell() {
+    let profile = resolve_profile(&TerminalConfig::default(), Some("bash")).unwrap();
+    assert_eq!(profile.command, "bash");
+    assert_eq!(profile.uid, None);
+}
+
+#[test]
+fn test_unknown_shell_keeps_default_user() {
+    let config: TerminalConfig = serde_json::from_str(
+        r#"{
+            "profiles": [{"name": "ops", "command": "/bin/bash", "uid": 1000, "gid": 1000}],
+            "default_profile": "ops"
+        }"#,
+    )
+    .unwrap();
+    for shell in ["bash", "/bin/sh", "ops2"] {
+        let profile = resolve_profile(&config, Some(shell)).unwrap();
+        assert_eq!(profile.name, "ops");
+        assert_eq!(profile.uid, Some(1000));
+        assert_eq!(profile.gid, Some(1000));
+    }
+}
+
+#[test]
+fn test_unknown_shell_without_default_is_rejected() {
+    let config: TerminalConfig = serde_json::from_str(
+        r#"{"profiles": [{"name": "ops", "command": "/bin/bash", "uid": 1000}]}"#,
+    )
+    .unwrap();
+    assert!(resolve_profile(&config, Some("bash")).is_err());
+    assert!(resolve_profile(&config, None).is_err());
+}
+
+#[test]
+fn test_resolve_default_profile() {
+    let profile = resolve_profile(&config(), None).unwrap();
+    assert_eq!(profile.name, "restricted");
+    assert_eq!(profile.env.get("PS1").map(|s| s.as_str()), Some("$ "));
+}
+
+#[test]
+fn test_switch_user_needs_root() {
+    let profile = config().profile("ops").unwrap();
+    assert!(check_switch_user(&profile, 0).is_ok());
+    let e = check_switch_user(&profile, 998).unwrap_err().to_string();
+    assert!(e.contains("ops") && e.contains("root"), "{}", e);
+}
+
+#[test]
+fn test_privileged_profiles() {
+    assert_eq!(config().privileged_profiles(), vec!["ops"]);
+    assert!(TerminalConfig::default().privileged_profiles().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_target_gid() {
+    use crate::pty::privilege::{target_gid, UserEntry};
+
+    let user = UserEntry {
+        name: "ops".to_string(),
+        home: "/home/ops".to_string(),
+        gid: 1000,
+    };
+    assert_eq!(target_gid(Some(1000), None, Some(&user)).unwrap(), 1000);
+    assert_eq!(target_gid(Some(1000), Some(50), Some(&user)).unwrap(), 50);
+    assert_eq!(target_gid(Some(4242), Some(50), None).unwrap(), 50);
+    assert!(target_gid(Some(4242), None, None).is_err());
+}
+
+#[test]
+fn test_command_hides_server_env() {
+    std::env::set_var("SERVERBEE_PROFILE_TEST_SECRET", "secret");
+    let profile = config().profile("restricted").unwrap();
+    let cmd = command_for(&profile).unwrap();
+    std::env::remove_var("SERVERBEE_PROFILE_TEST_SECRET");
+    assert_eq!(cmd.get_env("SERVERBEE_PROFILE_TEST_SECRET"), None);
+    assert!(cmd.get_env("PATH").is_some());
+    assert_eq!(cmd.get_env("PS1").and_then(|v| v.to_str()), Some("$ "));
+}
@@ -0,0 +1,88 @@
+use crate::config::terminal::TerminalProfile;
+use crate::pty::pty_manager::{PtyManager, PtyMessage};
+use crate::pty::session::PtySession;
+use actix::{Actor, Context, Handler};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct Viewer(Sender<PtyMessage>);
+
+impl Actor for Viewer {
+    type Context = Context<Self>;
+}
+
+impl Handler<PtyMessage> for Viewer {
+    type Result = ();
+
+    fn handle(&mut self, msg: PtyMessage, _ctx: &mut Self::Context) {
+        let _ = self.0.send(msg);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn test_exit_code_is_reported() {
+    let mut pty_manager = PtyManager::new(&TerminalProfile::new("sh", "sh")).unwrap();
+    let (tx, rx) = channel();
+    pty_manager
+        .start(move |msg| {
+            let _ = tx.send(msg);
+        })
+        .unwrap();
+    pty_manager.write_to_pty("exit 3\n").unwrap();
+
+    let exit = rx
+        .iter()
+        .find(|msg| matches!(msg, PtyMessage::Exit(_)))
+        .unwrap();
+    assert_eq!(exit, PtyMessage::Exit(3));
+    assert!(rx.recv_timeout(Duration::from_secs(1)).is_err());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn test_kill_ends_session() {
+    let mut pty_manager = PtyManager::new(&TerminalProfile::new("sh", "sh")).unwrap();
+    let (tx, rx) = channel();
+    pty_manager
+        .start(move |msg| {
+            let _ = tx.send(msg);
+        })
+        .unwrap();
+    pty_manager.kill();
+
+    let exited = rx.iter().any(|msg| matches!(msg, PtyMessage::Exit(_)));
+    assert!(exited);
+}
+
+#[cfg(not(target_os = "windows"))]
+#[actix_rt::test]
+async fn test_viewer_after_exit_is_told() {
+    let mut profile = TerminalProfile::new("sh", "sh");
+    profile.args = vec!["-c".to_string(), "exit 4".to_string()];
+    let session = Arc::new(PtySession::new("exited".to_string(), profile, 1024).unwrap());
+    let (tx, rx) = channel();
+    let forward = Arc::downgrade(&session);
+    session
+        .start(move |msg| {
+            if let (PtyMessage::Exit(code), Some(session)) = (msg, forward.upgrade()) {
+                session.exit_viewers(code);
+                let _ = tx.send(());
+            }
+        })
+        .unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    let (tx, rx) = channel();
+    session.attach(1, Viewer(tx).start().recipient(), "127.0.0.1");
+    assert_eq!(session.viewers(), 0);
+    for _ in 0..50 {
+        if let Ok(msg) = rx.try_recv() {
+            assert_eq!(msg, PtyMessage::Exit(4));
+            return;
+        }
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the viewer was not told the shell exited");
+}
@@ -0,0 +1,112 @@
+use crate::config::web_server::WebServerConfig;
+use crate::rebind::{listen_addrs, ListenAddr, Listeners};
+use std::net::{SocketAddr, TcpListener};
+
+fn config(json: &str) -> WebServerConfig {
+    serde_json::from_str(json).unwrap()
+}
+
+fn addr(value: &str) -> SocketAddr {
+    value.parse().unwrap()
+}
+
+#[test]
+fn test_web_server_config() {
+    let web_server = config(r#"{"port": 9600}"#);
+    assert_eq!(web_server.workers(), 2);
+    assert_eq!(web_server.json_limit(), 4096);
+    assert_eq!(web_server.shutdown_timeout(), 10);
+    assert!(web_server.validate().is_ok());
+
+    assert!(config(r#"{"port": 0}"#).validate().is_err());
+    assert!(config(r#"{"workers": 0}"#).validate().is_err());
+    assert!(config(r#"{"json_limit": 0}"#).validate().is_err());
+    assert!(config(r#"{"shutdown_timeout": 0}"#).validate().is_err());
+    assert!(config(r#"{"shutdown_timeout": 301}"#).validate().is_err());
+    assert!(config(r#"{"bind": ["localhost"]}"#).validate().is_err());
+    assert!(serde_json::from_str::<WebServerConfig>(r#"{"ip_mode": "ipv5"}"#).is_err());
+}
+
+#[test]
+fn test_listen_addrs() {
+    let dual = config(r#"{"port": 9600}"#);
+    assert_eq!(
+        listen_addrs(&dual, true).unwrap(),
+        vec![ListenAddr {
+            addr: addr("[::]:9600"),
+            v6_only: false
+        }]
+    );
+    assert_eq!(
+        listen_addrs(&dual, false).unwrap()[0].addr,
+        addr("0.0.0.0:9600")
+    );
+
+    let ipv6 = config(r#"{"port": 9600, "ip_mode": "ipv6"}"#);
+    assert!(listen_addrs(&ipv6, true).unwrap()[0].v6_only);
+    let ipv4 = config(r#"{"port": 9600, "ip_mode": "ipv4"}"#);
+    assert_eq!(
+        listen_addrs(&ipv4, true).unwrap()[0].addr,
+        addr("0.0.0.0:9600")
+    );
+
+    let bind = config(r#"{"port": 9600, "bind": ["127.0.0.1", "::1"], "ip_mode": "ipv4"}"#);
+    assert_eq!(
+        listen_addrs(&bind, true).unwrap(),
+        vec![
+            ListenAddr {
+                addr: addr("127.0.0.1:9600"),
+                v6_only: false
+            },
+            ListenAddr {
+                addr: addr("[::1]:9600"),
+                v6_only: true
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_rebind_shares_bound_sockets() {
+    let first = Listeners::default()
+        .rebind(&[ListenAddr {
+            addr: addr("127.0.0.1:0"),
+            v6_only: false,
+        }])
+        .unwrap();
+    let bound = first.sockets().unwrap()[0].local_addr().unwrap();
+
+    // the same address again doesn't conflict with the socket still listening
+    let second = first
+        .rebind(&[ListenAddr {
+            addr: addr("127.0.0.1:0"),
+            v6_only: false,
+        }])
+        .unwrap();
+    assert_eq!(second.sockets().unwrap()[0].local_addr().unwrap(), bound);
+
+    let exact = ListenAddr {
+        addr: bound,
+        v6_only: false,
+    };
+    assert!(Listeners::default().rebind(&[exact]).is_err());
+}
+
+#[test]
+fn test_rebind_fails_as_a_whole() {
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addrs = [
+        ListenAddr {
+            addr: addr("127.0.0.1:0"),
+            v6_only: false,
+        },
+        ListenAddr {
+            addr: taken.local_addr().unwrap(),
+            v6_only: false,
+        },
+    ];
+    let error = Listeners::default().rebind(&addrs).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&taken.local_addr().unwrap().to_string()));
+}
@@ -0,0 +1,131 @@
+use crate::pty::recorder::{Recorder, RecordingHeader};
+use crate::pty::recording_store::{playback_delay, RecordingStore};
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+use std::time::Duration;
+
+fn header() -> RecordingHeader {
+    RecordingHeader {
+        version: 2,
+        width: 80,
+        height: 24,
+        timestamp: 1,
+        session: Some("test".into()),
+        remote_ip: Some("127.0.0.1".into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_record_and_read_back() {
+    let dir = temp_dir().join("serverbee-recording-test");
+    let _ = remove_dir_all(&dir);
+    let store = RecordingStore::new(dir.clone());
+
+    let path = store.new_recording_path("test");
+    let mut recorder = Recorder::create(&path, &header(), false).unwrap();
+    recorder.output(b"hello");
+    recorder.input("secret");
+    recorder.resize(30, 100);
+    drop(recorder);
+
+    let recordings = store.list();
+    assert_eq!(recordings.len(), 1);
+    assert_eq!(recordings[0].header.remote_ip.as_deref(), Some("127.0.0.1"));
+
+    let events = store.output_events(&recordings[0].id).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].1, "hello");
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[test]
+fn test_record_split_character() {
+    let dir = temp_dir().join("serverbee-recording-split-test");
+    let _ = remove_dir_all(&dir);
+    let store = RecordingStore::new(dir.clone());
+
+    let path = store.new_recording_path("test");
+    let mut recorder = Recorder::create(&path, &header(), false).unwrap();
+    let bytes = "a你好".as_bytes();
+    recorder.output(&bytes[..2]);
+    recorder.output(&bytes[2..5]);
+    recorder.output(&bytes[5..]);
+    drop(recorder);
+
+    let id = &store.list()[0].id;
+    let output: String = store
+        .output_events(id)
+        .unwrap()
+        .into_iter()
+        .map(|(_, data)| data)
+        .collect();
+    assert_eq!(output, "a你好");
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[test]
+fn test_invalid_recording_id() {
+    let store = RecordingStore::new(temp_dir());
+    assert!(store.path("../etc/passwd").is_none());
+    assert!(!RecordingStore::is_valid_id(""));
+}
+
+#[test]
+fn test_playback_delay_uses_offset() {
+    let ms = Duration::from_millis;
+    // a long pause then a short gap, the second frame comes 0.1s after the first
+    assert_eq!(playback_delay(5.0, 1.0, ms(0)), ms(5000));
+    assert_eq!(playback_delay(5.1, 1.0, ms(5000)), ms(100));
+    assert_eq!(playback_delay(5.1, 2.0, ms(2500)), ms(50));
+    // behind schedule, sent right away
+    assert_eq!(playback_delay(1.0, 1.0, ms(3000)), Duration::ZERO);
+}
+
+#[test]
+fn test_finish_keeps_cut_off_tail() {
+    let dir = temp_dir().join("serverbee-recording-finish-test");
+    let _ = remove_dir_all(&dir);
+    let store = RecordingStore::new(dir.clone());
+
+    let path = store.new_recording_path("test");
+    let mut recorder = Recorder::create(&path, &header(), false).unwrap();
+    recorder.output(&"a你".as_bytes()[..2]);
+    recorder.finish("kill");
+    drop(recorder);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let last = content.lines().last().unwrap();
+    assert!(last.ends_with(r#""m","kill"]"#), "{}", last);
+    let id = &store.list()[0].id;
+    let output: String = store
+        .output_events(id)
+        .unwrap()
+        .into_iter()
+        .map(|(_, data)| data)
+        .collect();
+    assert_eq!(output, "a\u{FFFD}");
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_recordings_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir().join("serverbee-recording-private-test");
+    let _ = remove_dir_all(&dir);
+    let store = RecordingStore::new(dir.clone());
+
+    let path = store.new_recording_path("test");
+    drop(Recorder::create(&path, &header(), true).unwrap());
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&dir), 0o700);
+    assert_eq!(mode(&path), 0o600);
+
+    let _ = remove_dir_all(&dir);
+}
@@ -0,0 +1,101 @@
+use crate::service::unit::{ServiceUnit, Step};
+use std::path::PathBuf;
+
+fn unit() -> ServiceUnit {
+    ServiceUnit {
+        name: "serverbee-web".into(),
+        binary: PathBuf::from("/usr/local/bin/serverbee-web"),
+        user: "serverbee".into(),
+        data_dir: PathBuf::from("/var/lib/serverbee"),
+        log_dir: PathBuf::from("/var/log/serverbee"),
+        config: None,
+        secret_key_file: None,
+        stop_timeout: 305,
+    }
+}
+
+#[test]
+fn test_render_unit() {
+    let content = unit().render();
+    for line in [
+        "User=serverbee",
+        "ExecStart=/usr/local/bin/serverbee-web -d /var/lib/serverbee -l /var/log/serverbee",
+        "ExecReload=/bin/kill -HUP $MAINPID",
+        "Restart=on-failure",
+        "TimeoutStopSec=305",
+        "NoNewPrivileges=true",
+        "ProtectSystem=strict",
+        "ReadWritePaths=/var/lib/serverbee /var/log/serverbee",
+        "WantedBy=multi-user.target",
+    ] {
+        assert!(content.lines().any(|l| l == line), "missing {}", line);
+    }
+    assert_eq!(
+        unit().path(),
+        PathBuf::from("/etc/systemd/system/serverbee-web.service")
+    );
+}
+
+#[test]
+fn test_render_quotes_paths() {
+    let unit = ServiceUnit {
+        data_dir: PathBuf::from("/srv/server bee"),
+        log_dir: PathBuf::from("/srv/server bee"),
+        config: Some(PathBuf::from("/etc/serverbee/config.yml")),
+        ..unit()
+    };
+    let content = unit.render();
+    assert!(content.contains(
+        "-d \"/srv/server bee\" -l \"/srv/server bee\" --config /etc/serverbee/config.yml\n"
+    ));
+    assert!(content.contains("ReadWritePaths=\"/srv/server bee\"\n"));
+}
+
+#[test]
+fn test_install_plan() {
+    let steps = unit().install_plan(true);
+    let lines: Vec<String> = steps.iter().map(|s| s.to_string()).collect();
+    assert!(lines[0].starts_with("useradd --system"));
+    assert_eq!(
+        lines[1],
+        "install -d -o serverbee -g serverbee -m 0750 /var/lib/serverbee"
+    );
+    assert_eq!(lines[3], "write /etc/systemd/system/serverbee-web.service");
+    assert_eq!(lines.last().unwrap(), "systemctl restart serverbee-web");
+    assert!(matches!(&steps[3], Step::Write(_, content) if *content == unit().render()));
+
+    // an existing user and one directory for both
+    let unit = ServiceUnit {
+        log_dir: PathBuf::from("/var/lib/serverbee"),
+        ..unit()
+    };
+    let steps = unit.install_plan(false);
+    assert!(matches!(&steps[0], Step::Run(args) if args[0] == "install"));
+    assert!(matches!(&steps[1], Step::Write(..)));
+}
+
+#[test]
+fn test_steps_print_shell_quoted() {
+    let unit = ServiceUnit {
+        data_dir: PathBuf::from("/srv/server bee"),
+        ..unit()
+    };
+    let steps = unit.install_plan(false);
+    assert_eq!(
+        steps[0].to_string(),
+        "install -d -o serverbee -g serverbee -m 0750 '/srv/server bee'"
+    );
+    let lines: Vec<String> = unit
+        .uninstall_plan()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "systemctl disable --now serverbee-web",
+            "rm /etc/systemd/system/serverbee-web.service",
+            "systemctl daemon-reload",
+        ]
+    );
+}
@@ -0,0 +1,62 @@
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::Role;
+use crate::token::communication_token::{Principal, PrincipalKind};
+use crate::token::session::SessionStore;
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+
+fn store(name: &str) -> SessionStore {
+    let dir = temp_dir().join(format!("serverbee-session-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    SessionStore::new(DbWrapper::new(Some(dir)).unwrap())
+}
+
+#[test]
+fn test_login_refresh_logout() {
+    let sessions = store("login");
+    let tokens = sessions.login("admin").unwrap();
+    assert_eq!(
+        sessions.verify(&tokens.access_token),
+        Some("admin".to_string())
+    );
+    assert!(sessions.verify("not.a.jwt").is_none());
+
+    // refresh tokens are rotated
+    let refreshed = sessions.refresh(&tokens.refresh_token).unwrap();
+    assert!(sessions.refresh(&tokens.refresh_token).is_err());
+    assert!(sessions.verify(&refreshed.access_token).is_some());
+
+    assert_eq!(
+        sessions.logout(None, Some(&refreshed.access_token), false),
+        1
+    );
+    assert!(sessions.verify(&tokens.access_token).is_none());
+    assert!(sessions.refresh(&refreshed.refresh_token).is_err());
+}
+
+#[test]
+fn test_logout_all_and_revoke_account() {
+    let sessions = store("logout-all");
+    let first = sessions.login("admin").unwrap();
+    let second = sessions.login("admin").unwrap();
+    let other = sessions.login("ops").unwrap();
+    assert_eq!(sessions.logout(Some(&first.refresh_token), None, true), 2);
+    assert!(sessions.verify(&second.access_token).is_none());
+    assert!(sessions.verify(&other.access_token).is_some());
+
+    sessions.revoke_account("ops");
+    assert!(sessions.verify(&other.access_token).is_none());
+}
+
+#[test]
+fn test_ticket_is_one_time() {
+    let sessions = store("ticket");
+    let ticket = sessions.issue_ticket(Principal {
+        name: "admin".to_string(),
+        kind: PrincipalKind::Account(Role::Admin),
+        scopes: Role::Admin.scopes(),
+    });
+    assert_eq!(sessions.redeem_ticket(&ticket).unwrap().name, "admin");
+    assert!(sessions.redeem_ticket(&ticket).is_none());
+    assert!(sessions.redeem_ticket("unknown").is_none());
+}
@@ -0,0 +1,39 @@
+use crate::shutdown::Shutdown;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[actix_rt::test]
+async fn test_tasks_finish_after_cancel() {
+    let shutdown = Shutdown::new();
+    let finished = Arc::new(AtomicBool::new(false));
+    let task_finished = Arc::clone(&finished);
+    let task_shutdown = shutdown.clone();
+    shutdown.spawn(async move {
+        task_shutdown.cancelled().await;
+        // work after the signal, like sending a last report
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        task_finished.store(true, Ordering::SeqCst);
+    });
+
+    shutdown.cancel();
+    shutdown.join().await;
+    assert!(finished.load(Ordering::SeqCst));
+}
+
+#[actix_rt::test]
+async fn test_cancellable_tasks_are_dropped() {
+    let shutdown = Shutdown::new();
+    let finished = Arc::new(AtomicBool::new(false));
+    let task_finished = Arc::clone(&finished);
+    shutdown.spawn_cancellable(async move {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        task_finished.store(true, Ordering::SeqCst);
+    });
+
+    shutdown.cancel();
+    tokio::time::timeout(Duration::from_secs(1), shutdown.join())
+        .await
+        .unwrap();
+    assert!(!finished.load(Ordering::SeqCst));
+}
@@ -0,0 +1,171 @@
+use crate::config::tls::TlsConfig;
+use crate::tls::cert_resolver::ReloadingCertResolver;
+use crate::tls::certificate::{ensure_self_signed, fingerprint, load_certified_key};
+use crate::tls::client_cert::{client_verifier, ClientCertificate};
+use crate::tls::redirect::https_location;
+use crate::tls::server_config;
+use crate::token::scope::Scope;
+use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+use std::env::temp_dir;
+use std::fs::{copy, create_dir_all, remove_dir_all, write, File};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("serverbee-tls-test-{}", name));
+    let _ = remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_self_signed_is_generated_once() {
+    let dir = dir("self-signed");
+    let (cert, key) = ensure_self_signed(&dir).unwrap();
+    let first = load_certified_key(&cert, &key).unwrap();
+    assert_eq!(
+        ensure_self_signed(&dir).unwrap(),
+        (cert.clone(), key.clone())
+    );
+    let second = load_certified_key(&cert, &key).unwrap();
+    assert_eq!(first.cert, second.cert);
+
+    let fingerprint = fingerprint(&first.cert[0].0);
+    assert_eq!(fingerprint.len(), 32 * 3 - 1);
+    assert!(fingerprint.split(':').all(|b| b.len() == 2));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = key.metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_reload_if_changed() {
+    let (cert, key) = ensure_self_signed(&dir("reload-a")).unwrap();
+    let resolver = ReloadingCertResolver::new(cert.clone(), key.clone()).unwrap();
+    let before = resolver.fingerprint();
+    assert!(!resolver.reload_if_changed().unwrap());
+
+    let (renewed_cert, renewed_key) = ensure_self_signed(&dir("reload-b")).unwrap();
+    copy(&renewed_cert, &cert).unwrap();
+    copy(&renewed_key, &key).unwrap();
+    let later = SystemTime::now() + Duration::from_secs(10);
+    for path in [&cert, &key] {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+
+    assert!(resolver.reload_if_changed().unwrap());
+    assert_ne!(resolver.fingerprint(), before);
+    assert!(!resolver.reload_if_changed().unwrap());
+}
+
+#[test]
+fn test_server_config_requires_certificate() {
+    let data_dir = dir("server-config");
+    assert!(server_config(&TlsConfig::default(), &data_dir).is_err());
+
+    let mut tls = TlsConfig::default();
+    assert!(tls.merge_args(None, None, true, Some(8080)));
+    assert!(tls.enabled());
+    assert!(!tls.merge_args(None, None, false, None));
+    assert!(server_config(&tls, &data_dir).is_ok());
+    assert!(data_dir.join("tls").is_dir());
+
+    let mut half = TlsConfig::default();
+    half.merge_args(Some("cert.pem".to_string()), None, false, None);
+    assert!(server_config(&half, &data_dir).is_err());
+}
+
+fn ca() -> Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "serverbee test ca");
+    Certificate::from_params(params).unwrap()
+}
+
+fn client(subject: &str, ca: &Certificate) -> rustls::Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.distinguished_name.push(DnType::CommonName, subject);
+    let cert = Certificate::from_params(params).unwrap();
+    rustls::Certificate(cert.serialize_der_with_signer(ca).unwrap())
+}
+
+#[test]
+fn test_client_certificate_is_verified_against_ca() {
+    let dir = dir("client-ca");
+    create_dir_all(&dir).unwrap();
+    let ca = ca();
+    let ca_path = dir.join("ca.pem");
+    write(&ca_path, ca.serialize_pem().unwrap()).unwrap();
+
+    let verifier = client_verifier(&ca_path, true).unwrap();
+    assert!(verifier.client_auth_mandatory());
+    let hub = client("hub", &ca);
+    assert!(verifier
+        .verify_client_cert(&hub, &[], SystemTime::now())
+        .is_ok());
+    let stranger = client("hub", &self::ca());
+    assert!(verifier
+        .verify_client_cert(&stranger, &[], SystemTime::now())
+        .is_err());
+    assert!(!client_verifier(&ca_path, false)
+        .unwrap()
+        .client_auth_mandatory());
+
+    let identity = ClientCertificate::from_der(&hub.0).unwrap();
+    assert_eq!(identity.subject, "hub");
+    assert_eq!(identity.fingerprint, fingerprint(&hub.0));
+
+    let empty = dir.join("empty.pem");
+    write(&empty, "").unwrap();
+    assert!(client_verifier(&empty, true).is_err());
+    assert!(client_verifier(&dir.join("missing.pem"), true).is_err());
+}
+
+#[test]
+fn test_client_cert_scopes() {
+    let tls: TlsConfig = serde_json::from_str(
+        r#"{"client_certs": [
+            {"subject": "hub", "role": "viewer", "scopes": ["exec"]},
+            {"subject": "admin-laptop", "role": "admin"},
+            {"subject": "*", "scopes": ["metrics"]}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        tls.client_cert_scopes("hub"),
+        Some(vec![Scope::Metrics, Scope::Exec])
+    );
+    assert_eq!(
+        tls.client_cert_scopes("admin-laptop"),
+        Some(Scope::ALL.to_vec())
+    );
+    assert_eq!(
+        tls.client_cert_scopes("someone"),
+        Some(vec![Scope::Metrics])
+    );
+    assert_eq!(TlsConfig::default().client_cert_scopes("hub"), None);
+}
+
+#[test]
+fn test_https_location() {
+    assert_eq!(
+        https_location("example.com", 443, "/ws?a=1"),
+        "https://example.com/ws?a=1"
+    );
+    assert_eq!(
+        https_location("example.com:80", 9527, "/"),
+        "https://example.com:9527/"
+    );
+    assert_eq!(https_location("[::1]:80", 9527, "/"), "https://[::1]:9527/");
+    assert_eq!(https_location("[::1]", 443, "/"), "https://[::1]/");
+}
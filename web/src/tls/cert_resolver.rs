@@ -0,0 +1,85 @@
+use crate::tls::certificate::{fingerprint, load_certified_key};
+use anyhow::Result;
+use log::{error, info};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use std::fs::metadata;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Serves one certificate and swaps it in place when its files change,
+/// so renewed certificates apply without a restart.
+pub struct ReloadingCertResolver {
+    cert: PathBuf,
+    key: PathBuf,
+    current: RwLock<Arc<CertifiedKey>>,
+    modified: Mutex<Option<(SystemTime, SystemTime)>>,
+}
+
+impl ReloadingCertResolver {
+    pub fn new(cert: PathBuf, key: PathBuf) -> Result<Self> {
+        let current = load_certified_key(&cert, &key)?;
+        let resolver = Self {
+            current: RwLock::new(Arc::new(current)),
+            modified: Mutex::new(None),
+            cert,
+            key,
+        };
+        *resolver.modified.lock().unwrap() = resolver.files_modified();
+        Ok(resolver)
+    }
+
+    fn files_modified(&self) -> Option<(SystemTime, SystemTime)> {
+        let cert = metadata(&self.cert).and_then(|m| m.modified()).ok()?;
+        let key = metadata(&self.key).and_then(|m| m.modified()).ok()?;
+        Some((cert, key))
+    }
+
+    /// Fingerprint of the leaf certificate being served.
+    pub fn fingerprint(&self) -> String {
+        let current = self.current.read().unwrap();
+        current
+            .cert
+            .first()
+            .map(|cert| fingerprint(&cert.0))
+            .unwrap_or_default()
+    }
+
+    /// Load the files again if they changed since the last load.
+    /// Returns true if a new certificate is now served, a broken one keeps the old in place.
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let modified = self.files_modified();
+        if modified.is_none() || modified == *self.modified.lock().unwrap() {
+            return Ok(false);
+        }
+        let certified_key = load_certified_key(&self.cert, &self.key)?;
+        *self.current.write().unwrap() = Arc::new(certified_key);
+        *self.modified.lock().unwrap() = modified;
+        Ok(true)
+    }
+
+    pub async fn watch(resolver: Arc<Self>, interval: u64) {
+        if interval == 0 {
+            return;
+        }
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            match resolver.reload_if_changed() {
+                Ok(true) => info!(
+                    "TLS certificate reloaded, SHA-256 fingerprint: {}",
+                    resolver.fingerprint()
+                ),
+                Ok(false) => {}
+                Err(e) => error!("Error reloading TLS certificate: {}", e),
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Context, Result};
+use log::warn;
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls::{Certificate, PrivateKey};
+use rustls_pemfile::Item;
+use sha2::{Digest, Sha256};
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use sysinfo::{System, SystemExt};
+
+const SELF_SIGNED_CERT: &str = "self-signed.crt";
+const SELF_SIGNED_KEY: &str = "self-signed.key";
+
+/// Read a PEM certificate chain and its private key.
+pub fn load_certified_key(cert: &Path, key: &Path) -> Result<CertifiedKey> {
+    let chain = rustls_pemfile::certs(&mut BufReader::new(
+        File::open(cert).with_context(|| format!("open certificate {}", cert.display()))?,
+    ))?;
+    if chain.is_empty() {
+        return Err(anyhow!("no certificate found in {}", cert.display()));
+    }
+
+    let mut reader = BufReader::new(
+        File::open(key).with_context(|| format!("open private key {}", key.display()))?,
+    );
+    let private_key = rustls_pemfile::read_all(&mut reader)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(der) | Item::RSAKey(der) | Item::ECKey(der) => Some(der),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("no private key found in {}", key.display()))?;
+
+    let signing_key = any_supported_type(&PrivateKey(private_key))
+        .map_err(|e| anyhow!("unsupported private key {}: {}", key.display(), e))?;
+    Ok(CertifiedKey::new(
+        chain.into_iter().map(Certificate).collect(),
+        signing_key,
+    ))
+}
+
+/// SHA-256 of a DER certificate, as colon separated hex.
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Return the self-signed certificate and key in `dir`, generating them on first use.
+pub fn ensure_self_signed(dir: &Path) -> Result<(PathBuf, PathBuf)> {
+    let cert_path = dir.join(SELF_SIGNED_CERT);
+    let key_path = dir.join(SELF_SIGNED_KEY);
+    if cert_path.is_file() && key_path.is_file() {
+        return Ok((cert_path, key_path));
+    }
+
+    let mut names = vec![
+        "localhost".to_string(),
+        "127.0.0.1".to_string(),
+        "::1".to_string(),
+    ];
+    if let Some(host_name) = System::new().host_name() {
+        names.push(host_name);
+    }
+    let cert = rcgen::generate_simple_self_signed(names)?;
+    let cert_pem = cert.serialize_pem()?;
+
+    create_dir_all(dir)?;
+    File::create(&cert_path)?.write_all(cert_pem.as_bytes())?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&key_path)?
+        .write_all(cert.serialize_private_key_pem().as_bytes())?;
+
+    warn!(
+        "Generated a self-signed certificate at {}, clients have to trust its fingerprint",
+        cert_path.display()
+    );
+    Ok((cert_path, key_path))
+}
@@ -0,0 +1,72 @@
+use crate::tls::certificate::fingerprint;
+use actix_tls::accept::rustls_0_21::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use anyhow::{anyhow, Context, Result};
+use rustls::server::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientCertVerifier,
+};
+use rustls::RootCertStore;
+use std::any::Any;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Identity of the verified client certificate a connection was made with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientCertificate {
+    /// Common name of the subject.
+    pub subject: String,
+    pub fingerprint: String,
+}
+
+impl ClientCertificate {
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        let subject = cert
+            .subject()
+            .iter_common_name()
+            .next()?
+            .as_str()
+            .ok()?
+            .to_string();
+        Some(Self {
+            subject,
+            fingerprint: fingerprint(der),
+        })
+    }
+}
+
+/// Attach the client certificate of a TLS connection to it, rustls already verified the chain.
+pub fn on_connect(conn: &dyn Any, extensions: &mut Extensions) {
+    if let Some(stream) = conn.downcast_ref::<TlsStream<TcpStream>>() {
+        let (_, session) = stream.get_ref();
+        if let Some(cert) = session
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| ClientCertificate::from_der(&cert.0))
+        {
+            extensions.insert(cert);
+        }
+    }
+}
+
+/// Verify client certificates against the CAs in `ca`.
+/// Without `required` clients may still connect without one and use a token.
+pub fn client_verifier(ca: &Path, required: bool) -> Result<Arc<dyn ClientCertVerifier>> {
+    let ders = rustls_pemfile::certs(&mut BufReader::new(
+        File::open(ca).with_context(|| format!("open client CA {}", ca.display()))?,
+    ))?;
+    let mut roots = RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(&ders);
+    if added == 0 {
+        return Err(anyhow!("no CA certificate found in {}", ca.display()));
+    }
+    Ok(if required {
+        AllowAnyAuthenticatedClient::new(roots).boxed()
+    } else {
+        AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed()
+    })
+}
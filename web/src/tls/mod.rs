@@ -0,0 +1,56 @@
+pub mod cert_resolver;
+pub mod certificate;
+pub mod client_cert;
+pub mod redirect;
+
+use crate::config::tls::TlsConfig;
+use crate::tls::cert_resolver::ReloadingCertResolver;
+use crate::tls::certificate::ensure_self_signed;
+use crate::tls::client_cert::client_verifier;
+use anyhow::{anyhow, Result};
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Build the rustls config for the web server from the TLS section.
+/// The returned resolver serves the certificate and picks up renewals.
+pub fn server_config(
+    tls: &TlsConfig,
+    data_dir: &Path,
+) -> Result<(rustls::ServerConfig, Arc<ReloadingCertResolver>)> {
+    let (cert, key) = match (tls.cert(), tls.key()) {
+        (Some(cert), Some(key)) => (PathBuf::from(cert), PathBuf::from(key)),
+        (None, None) if tls.self_signed() => ensure_self_signed(&data_dir.join("tls"))?,
+        (None, None) => {
+            return Err(anyhow!(
+                "TLS is enabled without a certificate, set cert and key or self_signed"
+            ))
+        }
+        _ => return Err(anyhow!("TLS cert and key must be set together")),
+    };
+
+    let resolver = Arc::new(ReloadingCertResolver::new(cert, key)?);
+    info!(
+        "TLS certificate SHA-256 fingerprint: {}",
+        resolver.fingerprint()
+    );
+
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let builder = match tls.client_ca() {
+        Some(ca) => {
+            info!(
+                "Client certificates are verified against {}, required: {}",
+                ca,
+                tls.require_client_cert()
+            );
+            builder.with_client_cert_verifier(client_verifier(
+                Path::new(&ca),
+                tls.require_client_cert(),
+            )?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut config = builder.with_cert_resolver(resolver.clone());
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok((config, resolver))
+}
@@ -0,0 +1,35 @@
+use crate::config::config::Config;
+use actix_web::http::header::LOCATION;
+use actix_web::{web, HttpRequest, HttpResponse};
+use std::sync::{Arc, RwLock};
+
+/// Where a plain HTTP request for `host` and `path` lives on the HTTPS port.
+pub fn https_location(host: &str, https_port: u16, path: &str) -> String {
+    // drop the port of the plain listener, keeping IPv6 brackets intact
+    let host = match host.rfind(':') {
+        Some(index) if !host[index..].contains(']') => &host[..index],
+        _ => host,
+    };
+    if https_port == 443 {
+        format!("https://{}{}", host, path)
+    } else {
+        format!("https://{}:{}{}", host, https_port, path)
+    }
+}
+
+pub async fn redirect_to_https(
+    req: HttpRequest,
+    config: web::Data<Arc<RwLock<Config>>>,
+) -> HttpResponse {
+    // read per request, the HTTPS port may move with a rebind
+    let https_port = config.read().unwrap().server_port();
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or("/");
+    let location = https_location(req.connection_info().host(), https_port, path);
+    HttpResponse::PermanentRedirect()
+        .insert_header((LOCATION, location))
+        .finish()
+}
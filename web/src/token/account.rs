@@ -0,0 +1,183 @@
+use crate::config::constant::{ACCOUNTS, DEFAULT_ACCOUNT};
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::scope::Scope;
+use crate::utils::common_util::get_now_timestamp;
+use anyhow::{anyhow, Result};
+use log::info;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const TOKEN_LENGTH: usize = 32;
+
+/// What an account may do, each role includes the ones before it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read metrics.
+    Viewer,
+    /// Kill processes and run commands.
+    Operator,
+    /// Change config, open terminals and manage accounts.
+    Admin,
+}
+
+impl Role {
+    pub fn scopes(&self) -> Vec<Scope> {
+        match self {
+            Role::Viewer => vec![Scope::Metrics],
+            Role::Operator => vec![Scope::Metrics, Scope::Process, Scope::Exec],
+            Role::Admin => Scope::ALL.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A named user or API key, only the SHA-256 of its token is stored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Account {
+    pub name: String,
+    pub role: Role,
+    pub token_hash: String,
+    pub created_at: u64,
+}
+
+impl Account {
+    /// The default admin account with `token`, which is how the app token is kept.
+    pub fn admin(token: &str) -> Account {
+        Account {
+            name: DEFAULT_ACCOUNT.to_string(),
+            role: Role::Admin,
+            token_hash: hash_token(token),
+            created_at: get_now_timestamp(),
+        }
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Put `account` in place of the one with its name, keeping when that was created.
+/// Its token must not be used by another account.
+pub fn put_account(accounts: &mut Vec<Account>, account: Account) -> Result<()> {
+    if !AccountStore::is_valid_name(&account.name) {
+        return Err(anyhow!("账户名只能包含字母、数字、-、_ 和 ."));
+    }
+    if accounts
+        .iter()
+        .any(|a| a.name != account.name && a.token_hash == account.token_hash)
+    {
+        return Err(anyhow!("Token 已被其他账户使用"));
+    }
+    match accounts.iter_mut().find(|a| a.name == account.name) {
+        Some(existing) => {
+            existing.role = account.role;
+            existing.token_hash = account.token_hash;
+        }
+        None => accounts.push(account),
+    }
+    Ok(())
+}
+
+pub fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Accounts kept in sled under a single key.
+#[derive(Debug, Clone)]
+pub struct AccountStore {
+    db: DbWrapper,
+}
+
+impl AccountStore {
+    pub fn new(db: DbWrapper) -> Self {
+        Self { db }
+    }
+
+    /// Account names end up in logs and urls, so only a safe charset is allowed.
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= 64
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    }
+
+    pub fn list(&self) -> Vec<Account> {
+        self.db
+            .get::<Vec<Account>>(ACCOUNTS)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list().is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Account> {
+        self.list().into_iter().find(|a| a.name == name)
+    }
+
+    pub fn authenticate(&self, token: &str) -> Option<Account> {
+        if token.is_empty() {
+            return None;
+        }
+        let hash = hash_token(token);
+        self.list().into_iter().find(|a| a.token_hash == hash)
+    }
+
+    /// Create an account or replace the role and token of an existing one.
+    /// A token is generated when none is given, the plain token is returned
+    /// since this is the only time it is known.
+    pub fn upsert(&self, name: &str, role: Role, token: Option<String>) -> Result<String> {
+        let token = token
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(generate_token);
+        let mut accounts = self.list();
+        put_account(
+            &mut accounts,
+            Account {
+                name: name.to_string(),
+                role,
+                token_hash: hash_token(&token),
+                created_at: get_now_timestamp(),
+            },
+        )?;
+        self.db.set(ACCOUNTS, &accounts);
+        info!("Account {} saved with role {}", name, role);
+        Ok(token)
+    }
+
+    pub fn remove(&self, name: &str) -> bool {
+        let mut accounts = self.list();
+        let len = accounts.len();
+        accounts.retain(|a| a.name != name);
+        if accounts.len() == len {
+            return false;
+        }
+        self.db.set(ACCOUNTS, &accounts);
+        info!("Account {} removed", name);
+        true
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config::constant::{API_TOKENS, API_TOKEN_TOUCH_INTERVAL};
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{generate_token, hash_token};
+use crate::token::scope::Scope;
+use crate::utils::common_util::get_now_timestamp;
+use anyhow::{anyhow, Result};
+use log::info;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const TOKEN_ID_LENGTH: usize = 8;
+
+/// A token limited to some scopes, for integrations such as a metrics scraper.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub token_hash: String,
+    pub created_at: u64,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    pub last_used: Option<u64>,
+    #[serde(default)]
+    pub revoked_at: Option<u64>,
+}
+
+impl ApiToken {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
+
+/// API tokens kept in sled under a single key, revoked ones stay listed.
+#[derive(Debug, Clone)]
+pub struct ApiTokenStore {
+    db: DbWrapper,
+}
+
+impl ApiTokenStore {
+    pub fn new(db: DbWrapper) -> Self {
+        Self { db }
+    }
+
+    pub fn list(&self) -> Vec<ApiToken> {
+        self.db
+            .get::<Vec<ApiToken>>(API_TOKENS)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    fn save(&self, tokens: &Vec<ApiToken>) {
+        self.db.set(API_TOKENS, tokens);
+    }
+
+    /// Find the token matching `token`, whether it is still valid or not.
+    pub fn authenticate(&self, token: &str) -> Option<ApiToken> {
+        if token.is_empty() {
+            return None;
+        }
+        let hash = hash_token(token);
+        self.list().into_iter().find(|t| t.token_hash == hash)
+    }
+
+    /// Issue a token, returning it with the plain token which isn't stored.
+    pub fn create(
+        &self,
+        name: &str,
+        scopes: Vec<Scope>,
+        expires_at: Option<u64>,
+    ) -> Result<(ApiToken, String)> {
+        if name.is_empty() {
+            return Err(anyhow!("Token 名称不能为空"));
+        }
+        if scopes.is_empty() {
+            return Err(anyhow!("Token 至少需要一个权限范围"));
+        }
+        let now = get_now_timestamp();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(anyhow!("过期时间必须晚于当前时间"));
+        }
+        let token = generate_token();
+        let api_token = ApiToken {
+            id: rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(TOKEN_ID_LENGTH)
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .collect(),
+            name: name.to_string(),
+            scopes,
+            token_hash: hash_token(&token),
+            created_at: now,
+            expires_at,
+            last_used: None,
+            revoked_at: None,
+        };
+        let mut tokens = self.list();
+        tokens.push(api_token.clone());
+        self.save(&tokens);
+        info!("API token {} ({}) created", api_token.id, api_token.name);
+        Ok((api_token, token))
+    }
+
+    pub fn revoke(&self, id: &str) -> bool {
+        let mut tokens = self.list();
+        let token = match tokens.iter_mut().find(|t| t.id == id && !t.is_revoked()) {
+            Some(token) => token,
+            None => return false,
+        };
+        token.revoked_at = Some(get_now_timestamp());
+        self.save(&tokens);
+        info!("API token {} revoked", id);
+        true
+    }
+
+    /// Record a use of the token, at most once per `API_TOKEN_TOUCH_INTERVAL`
+    /// so busy tokens don't rewrite the db on every request.
+    pub fn touch(&self, id: &str) {
+        let now = get_now_timestamp();
+        let mut tokens = self.list();
+        if let Some(token) = tokens.iter_mut().find(|t| t.id == id) {
+            if token
+                .last_used
+                .is_some_and(|last_used| now < last_used + API_TOKEN_TOUCH_INTERVAL)
+            {
+                return;
+            }
+            token.last_used = Some(now);
+            self.save(&tokens);
+        }
+    }
+}
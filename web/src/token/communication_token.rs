@@ -1,87 +1,328 @@
+use crate::access::client_ip;
 use crate::config::config::Config;
+use crate::tls::client_cert::ClientCertificate;
+use crate::token::account::Role;
+use crate::token::scope::Scope;
+use crate::utils::common_util::get_now_timestamp;
+use actix_web::http::StatusCode;
 use actix_web::web::Data;
-use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
 use log::warn;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Deref;
 use std::sync::{Arc, RwLock};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CommunicationToken(String);
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrincipalKind {
+    Account(Role),
+    /// An API token, with its id.
+    ApiToken(String),
+    /// A client certificate, with its fingerprint.
+    Certificate(String),
+}
 
-impl CommunicationToken {
-    pub fn is_valid(&self, config: &Data<Arc<RwLock<Config>>>) -> bool {
-        let token = match config.read() {
-            Ok(guard) => guard.app_token(),
-            Err(e) => {
-                warn!("Failed to acquire config read lock: {:?}", e);
-                return false;
-            }
-        };
-
-        if token.is_none() || token.clone().unwrap().is_empty() {
-            if !self.0.is_empty() {
-                match config.write() {
-                    Ok(mut guard) => {
-                        let _ = guard.set_app_token(Some(self.0.clone()));
-                    }
-                    Err(e) => {
-                        warn!("Failed to acquire config write lock: {:?}", e);
-                    }
-                }
+/// Who made a request and what they may do.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub name: String,
+    pub kind: PrincipalKind,
+    pub scopes: Vec<Scope>,
+}
+
+fn is_websocket(req: &HttpRequest) -> bool {
+    req.headers()
+        .get("Upgrade")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// Whether the request tried to authenticate at all, only those count as failures.
+fn has_credentials(req: &HttpRequest) -> bool {
+    let query = req.query_string();
+    query.contains("ticket=")
+        || is_websocket(req)
+            && serde_urlencoded::from_str::<HashMap<String, String>>(query)
+                .ok()
+                .and_then(|params| params.get("token").cloned())
+                .is_some_and(|token| !token.is_empty())
+        || req
+            .headers()
+            .get("Authorization")
+            .is_some_and(|value| !value.is_empty())
+}
+
+/// Authenticate a request unless its IP is banned,
+/// banning it after repeated failures with credentials.
+fn authenticate(req: &HttpRequest) -> Result<Principal, actix_web::Error> {
+    let config = match req.app_data::<Data<Arc<RwLock<Config>>>>() {
+        Some(config) => config,
+        None => {
+            warn!("Failed to get config");
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to get config",
+            ));
+        }
+    };
+    let (lockout, rate_limit) = match config.read() {
+        Ok(guard) => (guard.lockout(), guard.rate_limit_config()),
+        Err(e) => {
+            warn!("Failed to acquire config read lock: {:?}", e);
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to get config",
+            ));
+        }
+    };
+
+    let ip = client_ip(req);
+    let now = get_now_timestamp();
+    if let Some(seconds) = lockout.banned_for(&ip, now) {
+        return Err(actix_web::error::ErrorTooManyRequests(format!(
+            "Too many failed attempts, retry in {} seconds",
+            seconds
+        )));
+    }
+
+    let result = resolve(req);
+    match &result {
+        Ok(principal) => {
+            lockout.record_success(&ip);
+            // the audit log reads who acted from here
+            req.extensions_mut().insert(principal.clone());
+        }
+        Err(e)
+            if e.as_response_error().status_code() == StatusCode::UNAUTHORIZED
+                && has_credentials(req) =>
+        {
+            lockout.record_failure(&ip, &rate_limit, now);
+        }
+        Err(_) => {}
+    }
+    result
+}
+
+/// Resolve who made a request from a one-time `ticket` on WebSocket handshakes,
+/// a mapped client certificate, a token in the `Authorization` header, or in the
+/// `token` query parameter of a WebSocket handshake.
+/// The header may carry a login session JWT as `Bearer <jwt>`.
+fn resolve(req: &HttpRequest) -> Result<Principal, actix_web::Error> {
+    let config = match req.app_data::<Data<Arc<RwLock<Config>>>>() {
+        Some(config) => config,
+        None => {
+            warn!("Failed to get config");
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to get config",
+            ));
+        }
+    };
+    let (accounts, api_tokens, sessions, tls) = match config.read() {
+        Ok(guard) => (
+            guard.accounts(),
+            guard.api_tokens(),
+            guard.sessions(),
+            guard.tls_config(),
+        ),
+        Err(e) => {
+            warn!("Failed to acquire config read lock: {:?}", e);
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Failed to get config",
+            ));
+        }
+    };
+
+    let remote_ip = client_ip(req);
+
+    // Get token from url query string
+    let params: HashMap<String, String> =
+        serde_urlencoded::from_str(req.query_string()).unwrap_or_else(|_| HashMap::new());
+
+    if let Some(ticket) = params.get("ticket") {
+        if !is_websocket(req) {
+            return Err(actix_web::error::ErrorUnauthorized(
+                "Tickets can only open WebSockets",
+            ));
+        }
+        return sessions.redeem_ticket(ticket).ok_or_else(|| {
+            warn!("Ticket is invalid, request from: {}", remote_ip);
+            actix_web::error::ErrorUnauthorized("Ticket is invalid")
+        });
+    }
+
+    if let Some(cert) = req.conn_data::<ClientCertificate>() {
+        match tls.client_cert_scopes(&cert.subject) {
+            Some(scopes) => {
+                return Ok(Principal {
+                    name: cert.subject.clone(),
+                    kind: PrincipalKind::Certificate(cert.fingerprint.clone()),
+                    scopes,
+                })
             }
-            return true;
+            None => warn!(
+                "Client certificate {} is not mapped to any scope, request from: {}",
+                cert.subject, remote_ip
+            ),
+        }
+    }
+
+    // a URL ends up in proxy and access logs, only a WebSocket handshake that can't
+    // send headers takes the token from there
+    let token_from_param = params
+        .get("token")
+        .filter(|_| is_websocket(req))
+        .map(|value| value.to_owned())
+        .unwrap_or_default();
+
+    // Get token from Authorization header
+    let token_from_header = req
+        .headers()
+        .get("Authorization")
+        .map(|value| value.to_str().unwrap_or_default())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).to_owned())
+        .unwrap_or_default();
+
+    // a login session, its account is looked up again so role changes apply at once
+    if let Some(account) = sessions
+        .verify(&token_from_header)
+        .and_then(|name| accounts.get(&name))
+    {
+        return Ok(Principal {
+            name: account.name,
+            scopes: account.role.scopes(),
+            kind: PrincipalKind::Account(account.role),
+        });
+    }
+
+    let account = accounts
+        .authenticate(&token_from_param)
+        .or_else(|| accounts.authenticate(&token_from_header));
+    let api_token = || {
+        api_tokens
+            .authenticate(&token_from_param)
+            .or_else(|| api_tokens.authenticate(&token_from_header))
+    };
+
+    let principal = if let Some(account) = account {
+        Principal {
+            name: account.name,
+            scopes: account.role.scopes(),
+            kind: PrincipalKind::Account(account.role),
         }
+    } else if let Some(api_token) = api_token() {
+        if api_token.is_revoked() {
+            warn!(
+                "API token {} is revoked, request from: {}",
+                api_token.id, remote_ip
+            );
+            return Err(actix_web::error::ErrorUnauthorized("Token is revoked"));
+        }
+        if api_token.is_expired(get_now_timestamp()) {
+            warn!(
+                "API token {} is expired, request from: {}",
+                api_token.id, remote_ip
+            );
+            return Err(actix_web::error::ErrorUnauthorized("Token is expired"));
+        }
+        api_tokens.touch(&api_token.id);
+        Principal {
+            name: api_token.name,
+            scopes: api_token.scopes,
+            kind: PrincipalKind::ApiToken(api_token.id),
+        }
+    } else {
+        if accounts.is_empty() {
+            warn!(
+                "No account configured, request from: {} rejected. Create one with POST /local/users",
+                remote_ip
+            );
+        } else {
+            warn!("Token is invalid, request from: {}", remote_ip);
+        }
+        return Err(actix_web::error::ErrorUnauthorized("Token is invalid"));
+    };
+    Ok(principal)
+}
 
-        self.0 == token.unwrap()
+/// Authenticate a request and check it was granted `scope`.
+fn authorize(req: &HttpRequest, scope: Scope) -> Result<Principal, actix_web::Error> {
+    let principal = authenticate(req)?;
+    if !principal.scopes.contains(&scope) {
+        warn!(
+            "{} without scope {} is not allowed to {} {}, request from: {}",
+            principal.name,
+            scope,
+            req.method(),
+            req.path(),
+            client_ip(req)
+        );
+        return Err(actix_web::error::ErrorForbidden(format!(
+            "Scope {} required",
+            scope
+        )));
     }
+    Ok(principal)
 }
 
-impl FromRequest for CommunicationToken {
+/// Any authenticated request, whatever its scopes.
+impl FromRequest for Principal {
     type Error = actix_web::Error;
     type Future = futures_util::future::Ready<Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        let config = match req.app_data::<Data<Arc<RwLock<Config>>>>() {
-            Some(config) => config,
-            None => {
-                warn!("Failed to get config");
-                return futures_util::future::ready(Err(
-                    actix_web::error::ErrorInternalServerError("Failed to get config"),
-                ));
+        futures_util::future::ready(authenticate(req))
+    }
+}
+
+macro_rules! scoped_token {
+    ($(#[$doc:meta])* $name:ident, $scope:expr) => {
+        $(#[$doc])*
+        #[derive(Debug)]
+        pub struct $name(Principal);
+
+        impl Deref for $name {
+            type Target = Principal;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
             }
-        };
-
-        // Get token from url query string
-        let params: HashMap<String, String> =
-            serde_urlencoded::from_str(req.query_string()).unwrap_or_else(|_| HashMap::new());
-        let token_from_param = params
-            .get("token")
-            .map(|value| value.to_owned())
-            .unwrap_or_default();
-
-        // Get token from Authorization header
-        let token_from_header = req
-            .headers()
-            .get("Authorization")
-            .map(|value| value.to_str().unwrap_or_default().to_owned())
-            .unwrap_or_default();
+        }
 
-        return if CommunicationToken(token_from_param.clone()).is_valid(config)
-            || CommunicationToken(token_from_header.clone()).is_valid(config)
-        {
-            futures_util::future::ready(Ok(CommunicationToken(token_from_param)))
-        } else {
-            warn!(
-                "Token: {} is invalid, request from: {}",
-                token_from_param,
-                req.connection_info()
-                    .realip_remote_addr()
-                    .unwrap_or("unknown")
-            );
-            futures_util::future::ready(Err(actix_web::error::ErrorUnauthorized(
-                "Token is invalid",
-            )))
-        };
-    }
+        impl FromRequest for $name {
+            type Error = actix_web::Error;
+            type Future = futures_util::future::Ready<Result<Self, Self::Error>>;
+
+            fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+                futures_util::future::ready(authorize(req, $scope).map($name))
+            }
+        }
+    };
 }
+
+scoped_token!(
+    /// A token with the `metrics` scope, which every account has.
+    CommunicationToken,
+    Scope::Metrics
+);
+scoped_token!(
+    /// A token with the `process` scope.
+    ProcessToken,
+    Scope::Process
+);
+scoped_token!(
+    /// A token with the `exec` scope.
+    ExecToken,
+    Scope::Exec
+);
+scoped_token!(
+    /// A token with the `terminal` scope.
+    TerminalToken,
+    Scope::Terminal
+);
+scoped_token!(
+    /// A token with the `config` scope.
+    ConfigToken,
+    Scope::Config
+);
+scoped_token!(
+    /// A token with the `accounts` scope.
+    AccountsToken,
+    Scope::Accounts
+);
@@ -1 +1,6 @@
+pub(crate) mod account;
+pub(crate) mod api_token;
 pub(crate) mod communication_token;
+pub(crate) mod scope;
+pub(crate) mod session;
+pub(crate) mod token_command;
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A capability a route requires, granted to accounts through their role
+/// and to API tokens explicitly.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Read metrics over `/ws`.
+    Metrics,
+    /// Kill processes.
+    Process,
+    /// Run commands with `/exec`.
+    Exec,
+    /// Open terminals, manage sessions and recordings.
+    Terminal,
+    /// Read and change config.
+    Config,
+    /// Manage accounts.
+    Accounts,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::Metrics,
+        Scope::Process,
+        Scope::Exec,
+        Scope::Terminal,
+        Scope::Config,
+        Scope::Accounts,
+    ];
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scope::Metrics => "metrics",
+            Scope::Process => "process",
+            Scope::Exec => "exec",
+            Scope::Terminal => "terminal",
+            Scope::Config => "config",
+            Scope::Accounts => "accounts",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.to_string() == s)
+            .ok_or_else(|| format!("unknown scope {}", s))
+    }
+}
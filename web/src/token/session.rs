@@ -0,0 +1,261 @@
+use crate::config::constant::{
+    AUTH_SESSIONS, SESSION_ACCESS_TTL, SESSION_REFRESH_TTL, SESSION_SECRET, WS_TICKET_TTL,
+};
+use crate::db::db_wrapper::DbWrapper;
+use crate::token::account::{generate_token, hash_token};
+use crate::token::communication_token::Principal;
+use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::get_now_timestamp;
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use log::info;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Claims {
+    /// Account name.
+    sub: String,
+    /// Session id, the token is only valid while the session exists.
+    sid: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// A login of an account, kept until logout or until the refresh token expires.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuthSession {
+    pub id: String,
+    pub account: String,
+    pub refresh_hash: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SessionTokens {
+    pub access_token: String,
+    /// Seconds until `access_token` expires.
+    pub expires_in: u64,
+    pub refresh_token: String,
+    /// Seconds until `refresh_token` expires.
+    pub refresh_expires_in: u64,
+}
+
+struct Ticket {
+    principal: Principal,
+    expires_at: Instant,
+}
+
+/// Login sessions signed as short-lived JWTs, and one-time WebSocket tickets.
+#[derive(Clone)]
+pub struct SessionStore {
+    db: DbWrapper,
+    secret: Arc<Vec<u8>>,
+    tickets: Arc<Mutex<HashMap<String, Ticket>>>,
+}
+
+impl std::fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionStore").finish_non_exhaustive()
+    }
+}
+
+impl SessionStore {
+    /// Load the signing secret, generating it on first start.
+    pub fn new(db: DbWrapper) -> Self {
+        let stored = match db.get_secret(SESSION_SECRET) {
+            Ok(Some(secret)) => BASE64.decode(secret).ok(),
+            // kept as plain bytes before secrets were encrypted
+            _ => db
+                .get::<Vec<u8>>(SESSION_SECRET)
+                .ok()
+                .flatten()
+                .inspect(|secret| db.set_secret(SESSION_SECRET, &BASE64.encode(secret))),
+        };
+        let secret = match stored {
+            Some(secret) if !secret.is_empty() => secret,
+            _ => {
+                let mut secret = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                db.set_secret(SESSION_SECRET, &BASE64.encode(&secret));
+                secret
+            }
+        };
+        Self {
+            db,
+            secret: Arc::new(secret),
+            tickets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn sessions(&self) -> Vec<AuthSession> {
+        self.db
+            .get::<Vec<AuthSession>>(AUTH_SESSIONS)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Save sessions, dropping the ones whose refresh token expired.
+    fn save(&self, mut sessions: Vec<AuthSession>) {
+        let now = get_now_timestamp();
+        sessions.retain(|s| s.expires_at > now);
+        self.db.set(AUTH_SESSIONS, &sessions);
+    }
+
+    fn issue(&self, session: &AuthSession, refresh_token: String) -> Result<SessionTokens> {
+        let now = get_now_timestamp();
+        let claims = Claims {
+            sub: session.account.clone(),
+            sid: session.id.clone(),
+            iat: now,
+            exp: now + SESSION_ACCESS_TTL,
+        };
+        let access_token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(&self.secret),
+        )?;
+        Ok(SessionTokens {
+            access_token,
+            expires_in: SESSION_ACCESS_TTL,
+            refresh_token,
+            refresh_expires_in: session.expires_at.saturating_sub(now),
+        })
+    }
+
+    /// Start a session for an account that presented its credential.
+    pub fn login(&self, account: &str) -> Result<SessionTokens> {
+        let now = get_now_timestamp();
+        let refresh_token = generate_token();
+        let session = AuthSession {
+            id: generate_token(),
+            account: account.to_string(),
+            refresh_hash: hash_token(&refresh_token),
+            created_at: now,
+            expires_at: now + SESSION_REFRESH_TTL,
+        };
+        let mut sessions = self.sessions();
+        sessions.push(session.clone());
+        self.save(sessions);
+        info!("Account {} logged in", account);
+        self.issue(&session, refresh_token)
+    }
+
+    /// Trade a refresh token for a new access token, the refresh token is
+    /// rotated so each one works only once.
+    pub fn refresh(&self, refresh_token: &str) -> Result<SessionTokens> {
+        let hash = hash_token(refresh_token);
+        let now = get_now_timestamp();
+        let mut sessions = self.sessions();
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.refresh_hash == hash && s.expires_at > now)
+            .ok_or_else(|| anyhow!("Refresh Token 无效或已过期"))?;
+        let refresh_token = generate_token();
+        session.refresh_hash = hash_token(&refresh_token);
+        let session = session.clone();
+        self.save(sessions);
+        self.issue(&session, refresh_token)
+    }
+
+    /// The account of a valid access token.
+    pub fn verify(&self, access_token: &str) -> Option<String> {
+        let claims = decode::<Claims>(
+            access_token,
+            &DecodingKey::from_secret(&self.secret),
+            &Validation::new(Algorithm::HS256),
+        )
+        .ok()?
+        .claims;
+        let now = get_now_timestamp();
+        self.sessions()
+            .iter()
+            .any(|s| s.id == claims.sid && s.expires_at > now)
+            .then_some(claims.sub)
+    }
+
+    fn session_id(&self, access_token: &str) -> Option<String> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        // logging out with an expired access token is fine
+        validation.validate_exp = false;
+        decode::<Claims>(
+            access_token,
+            &DecodingKey::from_secret(&self.secret),
+            &validation,
+        )
+        .ok()
+        .map(|data| data.claims.sid)
+    }
+
+    /// End the session of a refresh or access token, or every session of
+    /// its account when `all` is set. Returns how many sessions ended.
+    pub fn logout(
+        &self,
+        refresh_token: Option<&str>,
+        access_token: Option<&str>,
+        all: bool,
+    ) -> usize {
+        let mut sessions = self.sessions();
+        let refresh_hash = refresh_token.map(hash_token);
+        let session_id = access_token.and_then(|token| self.session_id(token));
+        let account = match sessions.iter().find(|s| {
+            Some(&s.refresh_hash) == refresh_hash.as_ref() || Some(&s.id) == session_id.as_ref()
+        }) {
+            Some(session) => session.clone(),
+            None => return 0,
+        };
+        let len = sessions.len();
+        sessions.retain(|s| {
+            if all {
+                s.account != account.account
+            } else {
+                s.id != account.id
+            }
+        });
+        let ended = len - sessions.len();
+        self.save(sessions);
+        info!(
+            "Account {} logged out of {} session(s)",
+            account.account, ended
+        );
+        ended
+    }
+
+    /// End every session of an account, used when it is removed or its token changes.
+    pub fn revoke_account(&self, account: &str) {
+        let mut sessions = self.sessions();
+        sessions.retain(|s| s.account != account);
+        self.save(sessions);
+    }
+
+    /// A one-time ticket to open a WebSocket as `principal`,
+    /// so no long-lived token ends up in a URL.
+    pub fn issue_ticket(&self, principal: Principal) -> String {
+        let ticket = generate_token();
+        let mut tickets = self.tickets.lock().unwrap();
+        let now = Instant::now();
+        tickets.retain(|_, t| t.expires_at > now);
+        tickets.insert(
+            hash_token(&ticket),
+            Ticket {
+                principal,
+                expires_at: now + Duration::from_secs(WS_TICKET_TTL),
+            },
+        );
+        ticket
+    }
+
+    pub fn redeem_ticket(&self, ticket: &str) -> Option<Principal> {
+        let ticket = self.tickets.lock().unwrap().remove(&hash_token(ticket))?;
+        (ticket.expires_at > Instant::now()).then_some(ticket.principal)
+    }
+}
+
+impl JsonResponder for SessionTokens {}
@@ -0,0 +1,53 @@
+use crate::cli::{Args, TokenAction};
+use crate::config::config::Config;
+use crate::config::layers::Overlays;
+use crate::token::api_token::ApiTokenStore;
+use crate::utils::common_util::{get_now_timestamp, get_terminal_time_format};
+use crate::vo::formator::Convert;
+use anyhow::{anyhow, Result};
+
+fn format_time(timestamp: Option<u64>) -> String {
+    timestamp
+        .map(get_terminal_time_format)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// `serverbee-web token ...`, works on the db directly so the server must be stopped.
+pub fn run_token_command(args: &Args, action: TokenAction) -> Result<()> {
+    let (db, _) = Config::open_database(&Overlays::load(args)?)?;
+    let store = ApiTokenStore::new(db.clone());
+    match action {
+        TokenAction::List => {
+            println!(
+                "{:<10} {:<20} {:<10} {:<18} {:<18} SCOPES",
+                "ID", "NAME", "STATUS", "EXPIRES", "LAST USED"
+            );
+            for token in store.list() {
+                let vo = token.convert();
+                let scopes: Vec<String> = vo.scopes.iter().map(|s| s.to_string()).collect();
+                println!(
+                    "{:<10} {:<20} {:<10} {:<18} {:<18} {}",
+                    vo.id,
+                    vo.name,
+                    vo.status,
+                    format_time(vo.expires_at),
+                    format_time(vo.last_used),
+                    scopes.join(",")
+                );
+            }
+        }
+        TokenAction::Create { name, scopes, ttl } => {
+            let expires_at = ttl.map(|ttl| get_now_timestamp() + ttl);
+            let (api_token, token) = store.create(&name, scopes, expires_at)?;
+            eprintln!("Created API token {} ({})", api_token.id, api_token.name);
+            println!("{}", token);
+        }
+        TokenAction::Revoke { id } => {
+            if !store.revoke(&id) {
+                return Err(anyhow!("token {} not found or already revoked", id));
+            }
+            eprintln!("Revoked API token {}", id);
+        }
+    }
+    db.flush()
+}
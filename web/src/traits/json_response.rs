@@ -3,7 +3,17 @@ use actix_web::http::header::ContentType;
 use actix_web::{HttpRequest, HttpResponse, Responder};
 use serde::Serialize;
 
-pub trait JsonResponder: Serialize + Sized {}
+pub trait JsonResponder: Serialize + Sized {
+    /// Why the response reports a failure, if it does.
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Kept in the extensions of a response whose body reports a failure,
+/// so the audit log can tell it apart from a success.
+#[derive(Debug, Clone)]
+pub struct FailureMessage(pub String);
 
 impl JsonResponder for () {}
 
@@ -15,8 +25,12 @@ impl<T: JsonResponder> Responder for JsonResponse<T> {
     fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
         let body = serde_json::to_string(&self).unwrap();
 
-        HttpResponse::Ok()
+        let mut response = HttpResponse::Ok()
             .content_type(ContentType::json())
-            .body(body)
+            .body(body);
+        if let Some(message) = self.0.failure() {
+            response.extensions_mut().insert(FailureMessage(message));
+        }
+        response
     }
 }
@@ -1,4 +1,6 @@
+use crate::config::constant::CONFIG_ENV_PREFIX;
 use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::ffi::OsString;
 
 pub fn get_now_timestamp() -> u64 {
     let now = Utc::now();
@@ -13,3 +15,69 @@ pub fn get_terminal_time_format(timestamp: u64) -> String {
     let datetime = Local.from_utc_datetime(&naive_datetime.unwrap());
     datetime.format("%a %b %e %T").to_string()
 }
+
+/// Hide a token but its first and last 4 characters, short tokens are hidden entirely.
+pub fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() <= 12 {
+        return "*".repeat(8);
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(8), tail)
+}
+
+/// The `SERVERBEE_` variables of this process, they can hold tokens and the secret key
+/// so shells and commands started for clients don't inherit them.
+pub fn server_env_names() -> Vec<OsString> {
+    std::env::vars_os()
+        .map(|(name, _)| name)
+        .filter(|name| name.to_string_lossy().starts_with(CONFIG_ENV_PREFIX))
+        .collect()
+}
+
+/// Length of a multi-byte character cut off at the end of `data`.
+fn incomplete_tail(data: &[u8]) -> usize {
+    for back in 1..=data.len().min(3) {
+        let byte = data[data.len() - back];
+        if byte & 0xC0 != 0x80 {
+            let len = match byte {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            return if len > back { back } else { 0 };
+        }
+    }
+    0
+}
+
+/// Text of output read in chunks, a character split across two reads is kept
+/// until the rest of it arrives.
+#[derive(Debug, Default)]
+pub struct Utf8Buffer {
+    pending: Vec<u8>,
+}
+
+impl Utf8Buffer {
+    /// The text complete so far, `None` when all of it is still waiting.
+    pub fn push(&mut self, data: &[u8]) -> Option<String> {
+        self.pending.extend_from_slice(data);
+        let end = self.pending.len() - incomplete_tail(&self.pending);
+        if end == 0 {
+            return None;
+        }
+        let chunk: Vec<u8> = self.pending.drain(..end).collect();
+        Some(String::from_utf8_lossy(&chunk).into_owned())
+    }
+
+    /// Whatever is still waiting once the output ended, a cut off character turns into U+FFFD.
+    pub fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let chunk = std::mem::take(&mut self.pending);
+        Some(String::from_utf8_lossy(&chunk).into_owned())
+    }
+}
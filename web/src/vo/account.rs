@@ -0,0 +1,35 @@
+use crate::token::account::{Account, Role};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AccountVo {
+    pub name: String,
+    pub role: Role,
+    pub created_at: u64,
+}
+
+impl Convert<AccountVo> for Account {
+    fn convert(&self) -> AccountVo {
+        AccountVo {
+            name: self.name.clone(),
+            role: self.role,
+            created_at: self.created_at,
+        }
+    }
+}
+
+impl JsonResponder for AccountVo {}
+
+impl JsonResponder for Vec<AccountVo> {}
+
+/// Returned once when an account is saved, the token can't be read back later.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AccountTokenVo {
+    pub name: String,
+    pub role: Role,
+    pub token: String,
+}
+
+impl JsonResponder for AccountTokenVo {}
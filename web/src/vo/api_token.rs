@@ -0,0 +1,55 @@
+use crate::token::api_token::ApiToken;
+use crate::token::scope::Scope;
+use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::get_now_timestamp;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ApiTokenVo {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub last_used: Option<u64>,
+    pub revoked_at: Option<u64>,
+    /// active, expired or revoked
+    pub status: String,
+}
+
+impl Convert<ApiTokenVo> for ApiToken {
+    fn convert(&self) -> ApiTokenVo {
+        let status = if self.is_revoked() {
+            "revoked"
+        } else if self.is_expired(get_now_timestamp()) {
+            "expired"
+        } else {
+            "active"
+        };
+        ApiTokenVo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            scopes: self.scopes.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_used: self.last_used,
+            revoked_at: self.revoked_at,
+            status: status.to_string(),
+        }
+    }
+}
+
+impl JsonResponder for ApiTokenVo {}
+
+impl JsonResponder for Vec<ApiTokenVo> {}
+
+/// Returned once when a token is created, the token can't be read back later.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatedApiTokenVo {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenVo,
+}
+
+impl JsonResponder for CreatedApiTokenVo {}
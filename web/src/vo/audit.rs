@@ -0,0 +1,42 @@
+use crate::audit::audit_log::{ActorKind, AuditEntry, AuditResult};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AuditEntryVo {
+    pub id: u64,
+    pub timestamp: u64,
+    pub actor: String,
+    pub actor_kind: ActorKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+    pub ip: String,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub result: AuditResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Convert<AuditEntryVo> for AuditEntry {
+    fn convert(&self) -> AuditEntryVo {
+        AuditEntryVo {
+            id: self.id,
+            timestamp: self.timestamp,
+            actor: self.actor.clone(),
+            actor_kind: self.actor_kind,
+            actor_id: self.actor_id.clone(),
+            ip: self.ip.clone(),
+            action: self.action.clone(),
+            target: self.target.clone(),
+            result: self.result,
+            detail: self.detail.clone(),
+        }
+    }
+}
+
+impl JsonResponder for AuditEntryVo {}
+
+impl JsonResponder for Vec<AuditEntryVo> {}
@@ -0,0 +1,11 @@
+use crate::traits::json_response::JsonResponder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TicketVo {
+    pub ticket: String,
+    /// Seconds the ticket can be redeemed.
+    pub expires_in: u64,
+}
+
+impl JsonResponder for TicketVo {}
@@ -0,0 +1,26 @@
+use crate::limiter::lockout::Ban;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BanVo {
+    pub ip: String,
+    /// How many times the IP has been banned, the current ban included.
+    pub bans: u32,
+    pub banned_until: u64,
+}
+
+impl Convert<BanVo> for Ban {
+    fn convert(&self) -> BanVo {
+        BanVo {
+            ip: self.ip.clone(),
+            bans: self.bans,
+            banned_until: self.banned_until,
+        }
+    }
+}
+
+impl JsonResponder for BanVo {}
+
+impl JsonResponder for Vec<BanVo> {}
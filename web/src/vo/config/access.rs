@@ -0,0 +1,27 @@
+use crate::config::access::{AccessConfig, AccessGroup, AccessRule};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AccessConfigVo {
+    pub trusted_proxies: Vec<String>,
+    pub metrics: AccessRule,
+    pub config: AccessRule,
+    pub terminal: AccessRule,
+    pub local: AccessRule,
+}
+
+impl Convert<AccessConfigVo> for AccessConfig {
+    fn convert(&self) -> AccessConfigVo {
+        AccessConfigVo {
+            trusted_proxies: self.trusted_proxies(),
+            metrics: self.rule(AccessGroup::Metrics).clone(),
+            config: self.rule(AccessGroup::Config).clone(),
+            terminal: self.rule(AccessGroup::Terminal).clone(),
+            local: self.rule(AccessGroup::Local).clone(),
+        }
+    }
+}
+
+impl JsonResponder for AccessConfigVo {}
@@ -1,5 +1,6 @@
 use crate::config::app::AppConfig;
 use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::mask_token;
 use crate::vo::formator::Convert;
 use serde::{Deserialize, Serialize};
 
@@ -12,7 +13,7 @@ pub struct AppConfigVo {
 impl Convert<AppConfigVo> for AppConfig {
     fn convert(&self) -> AppConfigVo {
         AppConfigVo {
-            token: self.token(),
+            token: self.token().as_deref().map(mask_token),
         }
     }
 }
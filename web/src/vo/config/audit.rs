@@ -0,0 +1,21 @@
+use crate::config::audit::AuditConfig;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AuditConfigVo {
+    pub retention_days: u64,
+    pub max_entries: usize,
+}
+
+impl Convert<AuditConfigVo> for AuditConfig {
+    fn convert(&self) -> AuditConfigVo {
+        AuditConfigVo {
+            retention_days: self.retention_days(),
+            max_entries: self.max_entries(),
+        }
+    }
+}
+
+impl JsonResponder for AuditConfigVo {}
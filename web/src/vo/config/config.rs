@@ -1,7 +1,13 @@
 use crate::config::config::Config;
 use crate::traits::json_response::JsonResponder;
+use crate::vo::config::access::AccessConfigVo;
 use crate::vo::config::app::AppConfigVo;
+use crate::vo::config::audit::AuditConfigVo;
+use crate::vo::config::exec::ExecConfigVo;
+use crate::vo::config::rate_limit::RateLimitConfigVo;
 use crate::vo::config::server::ServerConfigVo;
+use crate::vo::config::terminal::TerminalConfigVo;
+use crate::vo::config::tls::TlsConfigVo;
 use crate::vo::config::web_server::WebServerConfigVo;
 use crate::vo::formator::Convert;
 use serde::{Deserialize, Serialize};
@@ -11,6 +17,12 @@ pub struct ConfigVo {
     pub web_server: WebServerConfigVo,
     pub server: ServerConfigVo,
     pub app: AppConfigVo,
+    pub terminal: TerminalConfigVo,
+    pub exec: ExecConfigVo,
+    pub tls: TlsConfigVo,
+    pub rate_limit: RateLimitConfigVo,
+    pub access: AccessConfigVo,
+    pub audit: AuditConfigVo,
 }
 
 impl Convert<ConfigVo> for Config {
@@ -19,6 +31,12 @@ impl Convert<ConfigVo> for Config {
             web_server: self.web_server_config().convert(),
             server: self.server_config().convert(),
             app: self.app_config().convert(),
+            terminal: self.terminal_config().convert(),
+            exec: self.exec_config().convert(),
+            tls: self.tls_config().convert(),
+            rate_limit: self.rate_limit_config().convert(),
+            access: self.access_config().convert(),
+            audit: self.audit_config().convert(),
         }
     }
 }
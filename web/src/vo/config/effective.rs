@@ -0,0 +1,44 @@
+use crate::config::config::EffectiveConfig;
+use crate::config::layers::ValueSource;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EffectiveSettingVo {
+    pub value: Value,
+    pub source: ValueSource,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EffectiveConfigVo {
+    /// Config file read at start, if any.
+    pub file: Option<String>,
+    /// Settings by dotted path, e.g. `web_server.port`.
+    pub settings: BTreeMap<String, EffectiveSettingVo>,
+}
+
+impl Convert<EffectiveConfigVo> for EffectiveConfig {
+    fn convert(&self) -> EffectiveConfigVo {
+        EffectiveConfigVo {
+            file: self.file.as_ref().map(|file| file.display().to_string()),
+            settings: self
+                .settings
+                .iter()
+                .map(|(path, (value, source))| {
+                    (
+                        path.clone(),
+                        EffectiveSettingVo {
+                            value: value.clone(),
+                            source: *source,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonResponder for EffectiveConfigVo {}
@@ -0,0 +1,25 @@
+use crate::config::exec::{ExecConfig, ExecScript};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ExecConfigVo {
+    pub scripts: Vec<ExecScript>,
+    pub allow_free_form: bool,
+    pub default_timeout: u64,
+    pub max_output: usize,
+}
+
+impl Convert<ExecConfigVo> for ExecConfig {
+    fn convert(&self) -> ExecConfigVo {
+        ExecConfigVo {
+            scripts: self.scripts(),
+            allow_free_form: self.allow_free_form(),
+            default_timeout: self.default_timeout(),
+            max_output: self.max_output(),
+        }
+    }
+}
+
+impl JsonResponder for ExecConfigVo {}
@@ -1,4 +1,11 @@
+pub(crate) mod access;
 pub(crate) mod app;
+pub(crate) mod audit;
 pub(crate) mod config;
+pub(crate) mod effective;
+pub(crate) mod exec;
+pub(crate) mod rate_limit;
 pub(crate) mod server;
+pub(crate) mod terminal;
+pub(crate) mod tls;
 pub(crate) mod web_server;
@@ -0,0 +1,27 @@
+use crate::config::rate_limit::RateLimitConfig;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RateLimitConfigVo {
+    pub max_auth_failures: u32,
+    pub failure_window: u64,
+    pub ban_duration: u64,
+    pub max_ban_duration: u64,
+    pub heavy_requests_per_minute: u32,
+}
+
+impl Convert<RateLimitConfigVo> for RateLimitConfig {
+    fn convert(&self) -> RateLimitConfigVo {
+        RateLimitConfigVo {
+            max_auth_failures: self.max_auth_failures(),
+            failure_window: self.failure_window(),
+            ban_duration: self.ban_duration(),
+            max_ban_duration: self.max_ban_duration(),
+            heavy_requests_per_minute: self.heavy_requests_per_minute(),
+        }
+    }
+}
+
+impl JsonResponder for RateLimitConfigVo {}
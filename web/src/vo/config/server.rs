@@ -1,5 +1,6 @@
 use crate::config::server::ServerConfig;
 use crate::traits::json_response::JsonResponder;
+use crate::utils::common_util::mask_token;
 use crate::vo::formator::Convert;
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +16,7 @@ pub struct ServerConfigVo {
 impl Convert<ServerConfigVo> for ServerConfig {
     fn convert(&self) -> ServerConfigVo {
         ServerConfigVo {
-            token: self.token(),
+            token: self.token().as_deref().map(mask_token),
             host: self.host(),
             disable_ssl: self.disable_ssl(),
         }
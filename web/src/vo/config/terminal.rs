@@ -0,0 +1,34 @@
+use crate::config::terminal::{TerminalConfig, TerminalProfile};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TerminalConfigVo {
+    pub session_idle_timeout: u64,
+    pub scrollback_size: usize,
+    pub record: bool,
+    pub record_input: bool,
+    pub recording_retention_days: u64,
+    pub recording_max_files: usize,
+    pub profiles: Vec<TerminalProfile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+}
+
+impl Convert<TerminalConfigVo> for TerminalConfig {
+    fn convert(&self) -> TerminalConfigVo {
+        TerminalConfigVo {
+            session_idle_timeout: self.session_idle_timeout(),
+            scrollback_size: self.scrollback_size(),
+            record: self.record(),
+            record_input: self.record_input(),
+            recording_retention_days: self.recording_retention_days(),
+            recording_max_files: self.recording_max_files(),
+            profiles: self.profiles(),
+            default_profile: self.default_profile(),
+        }
+    }
+}
+
+impl JsonResponder for TerminalConfigVo {}
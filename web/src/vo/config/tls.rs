@@ -0,0 +1,35 @@
+use crate::config::tls::{ClientCertRule, TlsConfig};
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TlsConfigVo {
+    pub enabled: bool,
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub self_signed: bool,
+    pub redirect_port: Option<u16>,
+    pub reload_interval: u64,
+    pub client_ca: Option<String>,
+    pub require_client_cert: bool,
+    pub client_certs: Vec<ClientCertRule>,
+}
+
+impl Convert<TlsConfigVo> for TlsConfig {
+    fn convert(&self) -> TlsConfigVo {
+        TlsConfigVo {
+            enabled: self.enabled(),
+            cert: self.cert(),
+            key: self.key(),
+            self_signed: self.self_signed(),
+            redirect_port: self.redirect_port(),
+            reload_interval: self.reload_interval(),
+            client_ca: self.client_ca(),
+            require_client_cert: self.require_client_cert(),
+            client_certs: self.client_certs(),
+        }
+    }
+}
+
+impl JsonResponder for TlsConfigVo {}
@@ -1,16 +1,28 @@
-use crate::config::web_server::WebServerConfig;
+use crate::config::web_server::{IpMode, WebServerConfig};
 use crate::traits::json_response::JsonResponder;
 use crate::vo::formator::Convert;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct WebServerConfigVo {
     pub port: u16,
+    pub bind: Vec<String>,
+    pub ip_mode: IpMode,
+    pub workers: usize,
+    pub json_limit: usize,
+    pub shutdown_timeout: u64,
 }
 
 impl Convert<WebServerConfigVo> for WebServerConfig {
     fn convert(&self) -> WebServerConfigVo {
-        WebServerConfigVo { port: self.port() }
+        WebServerConfigVo {
+            port: self.port(),
+            bind: self.bind(),
+            ip_mode: self.ip_mode(),
+            workers: self.workers(),
+            json_limit: self.json_limit(),
+            shutdown_timeout: self.shutdown_timeout(),
+        }
     }
 }
 
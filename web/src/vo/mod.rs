@@ -1,3 +1,8 @@
+pub mod account;
+pub mod api_token;
+pub mod audit;
+pub mod auth;
+pub mod ban;
 pub mod component;
 pub mod config;
 pub mod cpu;
@@ -9,7 +14,9 @@ pub mod memory;
 pub mod network;
 pub mod overview;
 pub mod process;
+pub mod pty_session;
 pub mod realtime_status;
+pub mod recording;
 pub mod result;
 pub mod simple_process;
 pub mod usage;
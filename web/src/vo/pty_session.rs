@@ -0,0 +1,30 @@
+use crate::pty::session::PtySession;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::Convert;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PtySessionVo {
+    pub name: String,
+    pub shell: String,
+    pub created_at: u64,
+    pub viewers: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_seconds: Option<u64>,
+}
+
+impl Convert<PtySessionVo> for PtySession {
+    fn convert(&self) -> PtySessionVo {
+        PtySessionVo {
+            name: self.name().to_string(),
+            shell: self.shell().to_string(),
+            created_at: self.created_at(),
+            viewers: self.viewers(),
+            idle_seconds: self.idle_for().map(|idle| idle.as_secs()),
+        }
+    }
+}
+
+impl JsonResponder for PtySessionVo {}
+
+impl JsonResponder for Vec<PtySessionVo> {}
@@ -0,0 +1,36 @@
+use crate::model::recording::Recording;
+use crate::traits::json_response::JsonResponder;
+use crate::vo::formator::{Convert, FormatData, Formator};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct RecordingVo {
+    pub id: String,
+    pub size: FormatData,
+    pub started_at: u64,
+    pub modified: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<String>,
+}
+
+impl Convert<RecordingVo> for Recording {
+    fn convert(&self) -> RecordingVo {
+        RecordingVo {
+            id: self.id.clone(),
+            size: Formator::new().format_from_byte(self.size),
+            started_at: self.header.timestamp,
+            modified: self.modified,
+            session: self.header.session.clone(),
+            shell: self.header.shell.clone(),
+            remote_ip: self.header.remote_ip.clone(),
+        }
+    }
+}
+
+impl JsonResponder for RecordingVo {}
+
+impl JsonResponder for Vec<RecordingVo> {}